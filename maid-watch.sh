@@ -3,11 +3,13 @@
 
 if [ "$1" == "--help" ] || [ "$1" == "-h" ]; then
     echo "maid-watch - File monitor for Maid"
-    echo "Usage: maid-watch [--interval=SECONDS] [--path=DIRECTORY]"
+    echo "Usage: maid-watch [--interval=SECONDS] [--path=DIRECTORY] [--classify-only-changed]"
     echo ""
     echo "Options:"
     echo "  --interval=SECONDS  Set check interval in seconds (default: 60)"
     echo "  --path=DIRECTORY    Set directory to monitor (default: current directory)"
+    echo "  --classify-only-changed"
+    echo "                      Clean each changed file on its own instead of suggesting a full clean"
     echo "  --help, -h          Show this help message"
     exit 0
 fi
@@ -15,6 +17,7 @@ fi
 # Default values
 INTERVAL=60
 MONITOR_PATH="."
+ONLY_CHANGED=false
 
 # Parse arguments
 for arg in "$@"; do
@@ -25,6 +28,9 @@ for arg in "$@"; do
         --path=*)
         MONITOR_PATH="${arg#*=}"
         ;;
+        --classify-only-changed)
+        ONLY_CHANGED=true
+        ;;
     esac
 done
 
@@ -49,9 +55,14 @@ while true; do
                 if [ "$mod_time" -gt "${last_modified[$file]}" ]; then
                     echo "$(date '+%H:%M:%S') 📝 [Maid] Change detected: $file"
                     
-                    # Optional: Automatically suggest actions
-                    filetype=$(basename "$file" | grep -q "\.md$" && echo "markdown" || echo "script")
-                    echo "    Suggested action: maid clean --path $(dirname "$file") --verbose"
+                    if [ "$ONLY_CHANGED" = true ]; then
+                        # Classify and route just this file, relative to the watched directory
+                        maid clean --path "$file" --base-dir "$MONITOR_PATH" --restructure --yes
+                    else
+                        # Optional: Automatically suggest actions
+                        filetype=$(basename "$file" | grep -q "\.md$" && echo "markdown" || echo "script")
+                        echo "    Suggested action: maid clean --path $(dirname "$file") --verbose"
+                    fi
                 fi
             fi
             
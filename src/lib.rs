@@ -0,0 +1,5802 @@
+// maid - CLI tool for cleaning up and restructuring AI-generated files
+// Copyright (C) 2025 Realvonmakeheat
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! The document-processing core of maid: classifying files, naming and
+//! placing them, and deciding which ones to keep. The `maid` binary is a
+//! command-line front end to it.
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use colored::*;
+use globset::{Glob, GlobMatcher};
+use ignore::WalkBuilder;
+use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use chrono::Datelike;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard};
+
+/// Set by --quiet: only errors are printed
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Print nothing but errors from now on (--quiet)
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Whether --quiet is in effect
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Files open at once unless --io-concurrency says otherwise
+const DEFAULT_IO_CONCURRENCY: usize = 64;
+
+/// Bounds file IO shared by every worker thread (--io-concurrency)
+static IO_LIMIT: IoLimit = IoLimit::new(DEFAULT_IO_CONCURRENCY);
+
+/// Let at most `limit` files be read or copied at once from now on (--io-concurrency)
+pub fn set_io_concurrency(limit: usize) {
+    IO_LIMIT.set_limit(limit);
+}
+
+/// A counting semaphore for file IO, so parallel runs over large trees stay
+/// under the open file limit however many `--jobs` there are
+pub struct IoLimit {
+    limit: AtomicUsize,
+    in_use: Mutex<usize>,
+    released: Condvar,
+}
+
+/// A slot held in an [`IoLimit`] until dropped
+pub struct IoPermit<'a> {
+    limit: &'a IoLimit,
+}
+
+impl IoLimit {
+    /// Allow `limit` holders at once (at least one)
+    pub const fn new(limit: usize) -> Self {
+        IoLimit {
+            limit: AtomicUsize::new(if limit == 0 { 1 } else { limit }),
+            in_use: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+    
+    fn set_limit(&self, limit: usize) {
+        self.limit.store(limit.max(1), Ordering::Relaxed);
+        self.released.notify_all();
+    }
+    
+    fn lock(&self) -> MutexGuard<'_, usize> {
+        self.in_use.lock().unwrap_or_else(|e| e.into_inner())
+    }
+    
+    /// Wait for a free slot and hold it until the permit is dropped
+    pub fn acquire(&self) -> IoPermit<'_> {
+        let mut in_use = self.lock();
+        while *in_use >= self.limit.load(Ordering::Relaxed) {
+            in_use = self.released.wait(in_use).unwrap_or_else(|e| e.into_inner());
+        }
+        *in_use += 1;
+        IoPermit { limit: self }
+    }
+}
+
+impl Drop for IoPermit<'_> {
+    fn drop(&mut self) {
+        *self.limit.lock() -= 1;
+        self.limit.released.notify_one();
+    }
+}
+
+/// The progress bar of the run in progress, which printed lines go above
+static ACTIVE_PROGRESS: Mutex<Option<ProgressBar>> = Mutex::new(None);
+
+/// Print lines above `pb` until the returned guard is dropped, instead of
+/// tearing through it
+fn show_progress_bar(pb: &ProgressBar) -> ActiveProgress {
+    *ACTIVE_PROGRESS.lock().unwrap_or_else(|e| e.into_inner()) = Some(pb.clone());
+    ActiveProgress
+}
+
+/// Keeps a progress bar active for printing; see [`show_progress_bar`]
+struct ActiveProgress;
+
+impl Drop for ActiveProgress {
+    fn drop(&mut self) {
+        *ACTIVE_PROGRESS.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+/// Print a line, hiding the progress bar (if one is showing) while it's written
+#[doc(hidden)]
+pub fn print_line(line: std::fmt::Arguments) {
+    let pb = ACTIVE_PROGRESS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    match pb {
+        Some(pb) => pb.suspend(|| std::println!("{}", line)),
+        None => std::println!("{}", line),
+    }
+}
+
+// Regular output goes through these, so --quiet silences it in one place
+// and lines printed during a run go above its progress bar. Errors are
+// written with `eprintln!`, which --quiet leaves alone.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! println {
+    () => {
+        $crate::println!("")
+    };
+    ($($arg:tt)*) => {
+        if !$crate::quiet() {
+            $crate::print_line(format_args!($($arg)*))
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! print {
+    ($($arg:tt)*) => {
+        if !$crate::quiet() {
+            std::print!($($arg)*)
+        }
+    };
+}
+
+/// File types that we handle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileType {
+    Markdown,
+    Shell,
+    Python,
+    Yaml,
+    Other,
+}
+
+impl FileType {
+    /// The type a file's extension says it is, ignoring case (`.MD` is markdown)
+    fn from_path(path: &Path) -> Self {
+        let extension = path.extension().and_then(|ext| ext.to_str()).map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("md") => FileType::Markdown,
+            Some("sh") => FileType::Shell,
+            Some("py") => FileType::Python,
+            Some("yml") | Some("yaml") => FileType::Yaml,
+            _ => FileType::Other,
+        }
+    }
+    
+    /// Like `from_path`, but a file without an extension is a script when it
+    /// starts with a shebang for Python or a shell (`sh`, `bash` or `zsh`)
+    fn detect(path: &Path) -> Self {
+        if path.extension().is_some() {
+            return Self::from_path(path);
+        }
+        
+        let mut first_line = String::new();
+        match File::open(path).and_then(|file| io::BufReader::new(file.take(256)).read_line(&mut first_line)) {
+            Ok(_) => Self::from_path_and_content(path, &first_line),
+            Err(_) => FileType::Other,
+        }
+    }
+    
+    /// `detect` for content that has already been read
+    fn from_path_and_content(path: &Path, content: &str) -> Self {
+        if path.extension().is_some() {
+            return Self::from_path(path);
+        }
+        
+        match content.lines().next().and_then(shebang_interpreter) {
+            Some(interpreter) if interpreter.starts_with("python") => FileType::Python,
+            Some("sh" | "bash" | "zsh") => FileType::Shell,
+            _ => FileType::Other,
+        }
+    }
+    
+    /// The type of a file like `report.md.txt`, going by the extension before
+    /// a trailing one that isn't a known type
+    fn from_double_extension(path: &Path) -> Option<Self> {
+        if path.extension().is_none() || Self::from_path(path) != FileType::Other {
+            return None;
+        }
+        
+        match Self::from_path(Path::new(path.file_stem()?)) {
+            FileType::Other => None,
+            file_type => Some(file_type),
+        }
+    }
+    
+    /// The extension files of this type get, if it has one
+    fn extension(self) -> Option<&'static str> {
+        match self {
+            FileType::Markdown => Some("md"),
+            FileType::Shell => Some("sh"),
+            FileType::Python => Some("py"),
+            FileType::Yaml => Some("yml"),
+            FileType::Other => None,
+        }
+    }
+}
+
+/// The program a `#!` line runs, looking through `env`
+/// (`#!/usr/bin/env bash` and `#!/bin/bash` both give `bash`)
+fn shebang_interpreter(line: &str) -> Option<&str> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        words.find(|word| !word.starts_with('-'))
+    } else {
+        Some(program)
+    }
+}
+
+/// The kind of document based on content analysis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DocumentKind {
+    Rubric,
+    Report,
+    Guide,
+    Summary,
+    /// Dataset descriptions and data dictionaries
+    Data,
+    /// Frequently asked questions
+    Faq,
+    /// Endpoint and function references
+    ApiReference,
+    /// TODO lists and task trackers, left where they are
+    TaskList,
+    Script,
+    Changelog,
+    License,
+    Unknown,
+}
+
+impl DocumentKind {
+    const ALL: [DocumentKind; 12] = [
+        DocumentKind::Rubric,
+        DocumentKind::Report,
+        DocumentKind::Guide,
+        DocumentKind::Summary,
+        DocumentKind::Data,
+        DocumentKind::Faq,
+        DocumentKind::ApiReference,
+        DocumentKind::TaskList,
+        DocumentKind::Script,
+        DocumentKind::Changelog,
+        DocumentKind::License,
+        DocumentKind::Unknown,
+    ];
+    
+    /// Color the kind is printed in unless configured otherwise
+    fn default_color(self) -> Color {
+        match self {
+            DocumentKind::Rubric => Color::Green,
+            DocumentKind::Report => Color::Blue,
+            DocumentKind::Guide => Color::Cyan,
+            DocumentKind::Summary => Color::Magenta,
+            DocumentKind::Data => Color::BrightCyan,
+            DocumentKind::Faq => Color::BrightYellow,
+            DocumentKind::ApiReference => Color::BrightGreen,
+            DocumentKind::TaskList => Color::BrightMagenta,
+            DocumentKind::Script => Color::Yellow,
+            DocumentKind::Changelog => Color::BrightBlue,
+            DocumentKind::License => Color::BrightWhite,
+            DocumentKind::Unknown => Color::BrightBlack,
+        }
+    }
+}
+
+/// Parse an `--include-ext` value, accepting it with or without the leading dot
+pub fn parse_extension(value: &str) -> std::result::Result<String, String> {
+    let ext = value.trim().trim_start_matches('.');
+    if ext.is_empty() {
+        return Err("extension can't be empty".to_string());
+    }
+    
+    Ok(ext.to_lowercase())
+}
+
+/// Parse a `--name` glob, so a bad pattern is reported before any file is touched
+pub fn parse_name_glob(value: &str) -> std::result::Result<GlobMatcher, String> {
+    Glob::new(value)
+        .map(|glob| glob.compile_matcher())
+        .map_err(|e| e.to_string())
+}
+
+/// Parse a document kind name, case-insensitively and singular or plural
+/// (`report`, `Reports`, `summaries`)
+pub fn parse_kind(value: &str) -> std::result::Result<DocumentKind, String> {
+    let name = value.trim().to_lowercase().replace(['-', '_'], "");
+    let singular = match name.strip_suffix("ies") {
+        Some(stem) => format!("{}y", stem),
+        None => name.strip_suffix('s').unwrap_or(&name).to_string(),
+    };
+    DocumentKind::ALL
+        .into_iter()
+        .find(|kind| {
+            let kind = format!("{:?}", kind).to_lowercase();
+            kind == name || kind == singular
+        })
+        .ok_or_else(|| {
+            let kinds: Vec<String> = DocumentKind::ALL
+                .iter()
+                .map(|kind| format!("{:?}", kind).to_lowercase())
+                .collect();
+            format!("unknown document kind '{}' (expected one of: {})", value, kinds.join(", "))
+        })
+}
+
+/// Parse a `KIND=COLOR` pair for `--kind-color`
+pub fn parse_kind_color(value: &str) -> std::result::Result<(DocumentKind, String), String> {
+    let (kind, color) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected KIND=COLOR, got '{}'", value))?;
+    let kind = parse_kind(kind)?;
+    let color = color.trim().to_string();
+    color
+        .parse::<Color>()
+        .map_err(|_| format!("unknown color '{}'", color))?;
+    
+    Ok((kind, color))
+}
+
+/// What Clean does (or would do) with a single file
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum PlanAction {
+    Copy,
+    Move,
+    Symlink,
+    Skip,
+}
+
+/// What Clean does when a file's target already exists
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    /// Leave the file where it is
+    Skip,
+    /// Add " (2)", " (3)", ... before the extension
+    Rename,
+    /// Replace the existing target
+    Overwrite,
+}
+
+/// How paths are written in JSON output, plan files and reports
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ReportPaths {
+    /// Full path from the filesystem root
+    Absolute,
+    /// Relative to the directory being cleaned
+    Relative,
+    /// Just the file name
+    NameOnly,
+}
+
+impl ReportPaths {
+    fn apply(self, path: &Path, base_dir: &Path) -> PathBuf {
+        match self {
+            ReportPaths::Absolute => std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()),
+            ReportPaths::Relative => path
+                .strip_prefix(base_dir)
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|_| path.to_path_buf()),
+            ReportPaths::NameOnly => path
+                .file_name()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| path.to_path_buf()),
+        }
+    }
+}
+
+/// A single planned operation, as written by `--dry-run-json-to`
+#[derive(Debug, Serialize)]
+pub struct PlannedOperation {
+    source: PathBuf,
+    target: PathBuf,
+    action: PlanAction,
+    doc_kind: DocumentKind,
+    /// How a clash with an existing target was resolved, if there was one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    conflict: Option<ConflictStrategy>,
+}
+
+impl PlannedOperation {
+    /// The same operation with its paths written the way `--report-paths` asks
+    fn with_report_paths(self, base_dir: &Path, mode: ReportPaths) -> Self {
+        PlannedOperation {
+            source: mode.apply(&self.source, base_dir),
+            target: mode.apply(&self.target, base_dir),
+            ..self
+        }
+    }
+}
+
+/// Hex-encoded SHA-256 digest of some data
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// A completed Clean operation, recorded so `undo` can reverse it
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalEntry {
+    source: PathBuf,
+    target: PathBuf,
+    action: PlanAction,
+    /// SHA-256 of the target when it was written, to detect later edits
+    target_hash: Option<String>,
+}
+
+/// A file Keep moved to the trash bin, recorded so `restore` can put it back
+#[derive(Debug, Serialize, Deserialize)]
+struct TrashEntry {
+    trashed: PathBuf,
+    original: PathBuf,
+}
+
+/// Name of the manifest Keep writes inside each trash bin
+const TRASH_MANIFEST: &str = "maid-manifest.json";
+
+fn read_trash_manifest(trash_dir: &Path) -> Result<Vec<TrashEntry>> {
+    let manifest_path = trash_dir.join(TRASH_MANIFEST);
+    if !manifest_path.exists() {
+        return Ok(Vec::new());
+    }
+    
+    let json = fs::read_to_string(&manifest_path).context("Failed to read trash manifest")?;
+    serde_json::from_str(&json).context("Failed to parse trash manifest")
+}
+
+fn write_trash_manifest(trash_dir: &Path, entries: &[TrashEntry]) -> Result<()> {
+    let manifest_path = trash_dir.join(TRASH_MANIFEST);
+    let json = serde_json::to_string_pretty(entries).context("Failed to serialize trash manifest")?;
+    fs::write(&manifest_path, json)
+        .with_context(|| format!("Failed to write trash manifest to {}", manifest_path.display()))
+}
+
+/// How Clean reports its results
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable, colored output
+    Text,
+    /// A single JSON object on stdout
+    Json,
+}
+
+/// A renamed or relocated file, as listed by `--keep-originals-index`
+#[derive(Debug, Serialize)]
+struct RenamedFile {
+    original_name: String,
+    new_name: String,
+    original_path: PathBuf,
+    new_path: PathBuf,
+}
+
+/// Write the rename ledger: JSON, or CSV when the path ends in `.csv`
+fn write_originals_index(path: &Path, plan: &[PlannedOperation]) -> Result<()> {
+    let file_name = |path: &Path| path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let renamed: Vec<RenamedFile> = plan
+        .iter()
+        .filter(|op| !matches!(op.action, PlanAction::Skip) && op.source != op.target)
+        .map(|op| RenamedFile {
+            original_name: file_name(&op.source),
+            new_name: file_name(&op.target),
+            original_path: op.source.clone(),
+            new_path: op.target.clone(),
+        })
+        .collect();
+    
+    let is_csv = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let content = if is_csv {
+        let mut csv = String::from("original_name,new_name,original_path,new_path\n");
+        for file in &renamed {
+            let fields = [
+                file.original_name.clone(),
+                file.new_name.clone(),
+                file.original_path.display().to_string(),
+                file.new_path.display().to_string(),
+            ];
+            let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    } else {
+        serde_json::to_string_pretty(&renamed).context("Failed to serialize originals index")?
+    };
+    
+    fs::write(path, content).with_context(|| format!("Failed to write originals index to {}", path.display()))
+}
+
+/// Quote a CSV field if it has a comma, quote or line break in it
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Machine-readable result of a Clean run, printed with `--format json`
+#[derive(Debug, Serialize)]
+struct CleanSummary {
+    total_files: usize,
+    processed: usize,
+    skipped: usize,
+    /// Total size of the processed files, before any restructuring
+    bytes_processed: u64,
+    markdown: usize,
+    shell: usize,
+    python: usize,
+    yaml: usize,
+    /// Files picked up through `--include-ext` or `--include-other`
+    other: usize,
+    /// Files whose target already existed, resolved with `on_conflict`
+    conflicts: usize,
+    on_conflict: ConflictStrategy,
+    files: Vec<PlannedOperation>,
+    /// Directories removed by `--prune-empty` after being emptied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pruned_dirs: Option<usize>,
+    /// Files classified differently than in the `--report-kind-transitions` plan
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind_transitions: Option<Vec<KindTransition>>,
+}
+
+/// The part of a saved plan entry that `--report-kind-transitions` compares
+#[derive(Debug, Deserialize)]
+struct PlannedKind {
+    source: PathBuf,
+    doc_kind: DocumentKind,
+}
+
+/// A file whose document kind differs from an earlier plan
+#[derive(Debug, Serialize)]
+struct KindTransition {
+    source: PathBuf,
+    from: DocumentKind,
+    to: DocumentKind,
+}
+
+/// Files in `plan` classified differently than in the saved plan at
+/// `previous`, matched by source path
+fn kind_transitions(previous: &Path, plan: &[PlannedOperation]) -> Result<Vec<KindTransition>> {
+    let json = fs::read_to_string(previous)
+        .with_context(|| format!("Failed to read plan {}", previous.display()))?;
+    let previous: Vec<PlannedKind> = serde_json::from_str(&json)
+        .with_context(|| format!("Failed to parse plan {}", previous.display()))?;
+    let previous: HashMap<PathBuf, DocumentKind> = previous
+        .into_iter()
+        .map(|entry| (entry.source, entry.doc_kind))
+        .collect();
+    
+    Ok(plan
+        .iter()
+        .filter_map(|op| {
+            let from = *previous.get(&op.source)?;
+            (from != op.doc_kind).then(|| KindTransition {
+                source: op.source.clone(),
+                from,
+                to: op.doc_kind,
+            })
+        })
+        .collect())
+}
+
+/// How a Clean or Keep run went, so the caller can pick an exit code
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RunOutcome {
+    /// Files renamed, moved or trashed (or that would be, in a dry run)
+    pub processed: usize,
+    /// Files left where they were, including failed ones
+    pub skipped: usize,
+    /// Files skipped because they couldn't be read or processed
+    pub failed: usize,
+}
+
+impl RunOutcome {
+    /// The outcome of cleaning one file with `process_single_file`
+    pub fn for_single_file(operation: Option<&PlannedOperation>) -> Self {
+        match operation {
+            Some(op) if !matches!(op.action, PlanAction::Skip) => RunOutcome { processed: 1, ..Self::default() },
+            _ => RunOutcome { skipped: 1, ..Self::default() },
+        }
+    }
+}
+
+/// Errors a caller may want to tell apart from a failed run
+#[derive(Debug, thiserror::Error)]
+pub enum MaidError {
+    /// Options that can't be used together or a malformed argument
+    #[error("{0}")]
+    Usage(String),
+    #[error("Directory does not exist: {}", .0.display())]
+    DirectoryNotFound(PathBuf),
+    #[error("Not a directory: {}", .0.display())]
+    NotADirectory(PathBuf),
+}
+
+/// Options controlling a Clean run
+#[derive(Clone)]
+pub struct CleanOptions {
+    pub recursive: bool,
+    pub max_depth: Option<usize>,
+    pub stdin: bool,
+    pub restructure: bool,
+    pub flat: bool,
+    pub preserve_tree: bool,
+    pub docs_root: Option<PathBuf>,
+    pub scripts_root: Option<PathBuf>,
+    pub target_dir: Option<PathBuf>,
+    pub normalize_headings_to_sentence_case: bool,
+    pub dry_run: bool,
+    pub verbose: bool,
+    pub move_files: bool,
+    pub link: bool,
+    pub preserve_timestamps: bool,
+    pub on_conflict: ConflictStrategy,
+    pub verify_shell_syntax: bool,
+    pub dry_run_json_to: Option<PathBuf>,
+    pub keep_originals_index: Option<PathBuf>,
+    pub report_kind_transitions: Option<PathBuf>,
+    pub prune_empty: bool,
+    pub cache_file: Option<PathBuf>,
+    pub classify_trace: Option<PathBuf>,
+    pub resume: Option<PathBuf>,
+    pub since_last_run: bool,
+    pub since_commit: Option<String>,
+    pub newer_than: Option<chrono::DateTime<chrono::Local>>,
+    pub older_than: Option<chrono::DateTime<chrono::Local>>,
+    pub min_confidence: f32,
+    pub annotate_confidence_in_name: bool,
+    pub jobs: Option<usize>,
+    pub preview_limit: Option<usize>,
+    pub progress: bool,
+    pub no_progress: bool,
+    pub format: OutputFormat,
+    pub interactive_classify: bool,
+    pub interactive: bool,
+    pub skip_error_output: bool,
+    pub classify_report_vs_summary_by_structure: bool,
+    pub exclude_content: Vec<Regex>,
+    pub kind_color: Vec<(DocumentKind, String)>,
+    pub include_ext: Vec<String>,
+    pub include_other: bool,
+    pub include_extensionless: bool,
+    pub add_extension: bool,
+    pub strict_extensions: bool,
+    pub name: Vec<GlobMatcher>,
+    pub name_regex: Vec<Regex>,
+    pub report_paths: ReportPaths,
+    pub report: Option<PathBuf>,
+    pub output_report_template: Option<PathBuf>,
+    pub yes: bool,
+}
+
+/// Classification results shared across runs and directories, keyed by
+/// canonical path and content hash
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ClassificationCache {
+    entries: HashMap<String, DocumentKind>,
+    /// Confidence of each entry; caches written before confidences existed
+    /// don't have them, and those entries are classified again
+    #[serde(default)]
+    confidences: HashMap<String, f32>,
+    #[serde(skip)]
+    hits: usize,
+}
+
+impl ClassificationCache {
+    /// Load the cache from disk, starting empty if it doesn't exist yet
+    fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        
+        let file = File::open(path).context("Failed to open cache file")?;
+        file.lock_shared().context("Failed to lock cache file")?;
+        let cache = Self::read_entries(&file)?;
+        file.unlock()?;
+        
+        Ok(cache)
+    }
+    
+    fn read_entries(mut file: &File) -> Result<Self> {
+        let mut content = String::new();
+        file.read_to_string(&mut content)
+            .context("Failed to read cache file")?;
+        
+        if content.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        
+        serde_json::from_str(&content).context("Failed to parse cache file")
+    }
+    
+    /// Build the cache key for a file from its canonical path and content
+    fn key(path: &Path, content: &str) -> Option<String> {
+        let canonical = fs::canonicalize(path).ok()?;
+        Some(format!("{}:{}", canonical.display(), sha256_hex(content.as_bytes())))
+    }
+    
+    /// Classify a file, reusing a cached result when path and content match
+    fn classify(
+        cache: &Mutex<Self>,
+        path: &Path,
+        name: &str,
+        content: &str,
+        file_type: FileType,
+    ) -> (DocumentKind, f32) {
+        let Some(key) = Self::key(path, content) else {
+            return classify_document_kind(name, content, &HashMap::new(), file_type);
+        };
+        
+        let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let (Some(&kind), Some(&confidence)) = (cache.entries.get(&key), cache.confidences.get(&key)) {
+            cache.hits += 1;
+            return (kind, confidence);
+        }
+        
+        let (kind, confidence) = classify_document_kind(name, content, &HashMap::new(), file_type);
+        cache.entries.insert(key.clone(), kind);
+        cache.confidences.insert(key, confidence);
+        (kind, confidence)
+    }
+    
+    /// Merge our entries into the cache on disk while holding an exclusive lock,
+    /// so concurrent runs sharing the file don't drop each other's results
+    fn save(&self, path: &Path) -> Result<()> {
+        let mut file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .context("Failed to open cache file")?;
+        file.lock().context("Failed to lock cache file")?;
+        
+        let mut merged = Self::read_entries(&file).unwrap_or_default();
+        merged.entries.extend(self.entries.iter().map(|(k, v)| (k.clone(), *v)));
+        merged.confidences.extend(self.confidences.iter().map(|(k, v)| (k.clone(), *v)));
+        
+        let json = serde_json::to_string(&merged).context("Failed to serialize cache")?;
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(json.as_bytes())?;
+        file.unlock()?;
+        
+        Ok(())
+    }
+}
+
+/// User configuration read from `maid.toml` in the directory being processed
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    rules: Vec<ClassificationRule>,
+    /// Extra keywords per kind, checked alongside the built-in ones
+    #[serde(default)]
+    keywords: HashMap<DocumentKind, KindKeywords>,
+    /// Colors to print each kind in, overriding the defaults
+    #[serde(default)]
+    colors: HashMap<DocumentKind, String>,
+    /// Phrases marking a file as a refusal or error instead of real content
+    /// (replaces the built-in list when set)
+    error_phrases: Option<Vec<String>>,
+}
+
+/// Phrases that show up in failed generations saved as files
+const DEFAULT_ERROR_PHRASES: &[&str] = &[
+    "I cannot help with that",
+    "I can't help with that",
+    "I'm unable to help with",
+    "I'm sorry, but I can't",
+    "As an AI language model",
+    "Error: rate limit exceeded",
+    "Rate limit reached",
+    "Internal server error",
+    "The model is currently overloaded",
+    "Request timed out",
+];
+
+/// Files longer than this are treated as real content even if they mention an error
+const ERROR_OUTPUT_MAX_WORDS: usize = 80;
+
+/// Classify files whose name matches `pattern` (a glob) as `kind`
+#[derive(Debug, Deserialize)]
+struct ClassificationRule {
+    pattern: String,
+    kind: DocumentKind,
+}
+
+/// Additional keywords that mark a file as a given kind
+#[derive(Debug, Default, Deserialize)]
+pub struct KindKeywords {
+    #[serde(default)]
+    pub filename: Vec<String>,
+    #[serde(default)]
+    pub content: Vec<String>,
+}
+
+impl Config {
+    fn path(dir_path: &Path) -> PathBuf {
+        dir_path.join("maid.toml")
+    }
+    
+    /// `$XDG_CONFIG_HOME/maid/maid.toml`, falling back to `~/.config/maid/maid.toml`
+    fn user_path() -> Option<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_home.join("maid/maid.toml"))
+    }
+    
+    /// Load `maid.toml` from a directory, then from the user config directory,
+    /// using defaults if neither exists
+    fn load(dir_path: &Path) -> Result<Self> {
+        let Some(path) = std::iter::once(Self::path(dir_path))
+            .chain(Self::user_path())
+            .find(|path| path.exists())
+        else {
+            return Ok(Self::default());
+        };
+        
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: Self = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        
+        for (kind, color) in &config.colors {
+            if color.parse::<Color>().is_err() {
+                anyhow::bail!("Unknown color '{}' for {:?} in {}", color, kind, path.display());
+            }
+        }
+        
+        Ok(config)
+    }
+    
+    /// The color a kind is printed in
+    fn kind_color(&self, kind: DocumentKind) -> Color {
+        self.colors
+            .get(&kind)
+            .and_then(|color| color.parse().ok())
+            .unwrap_or_else(|| kind.default_color())
+    }
+    
+    /// The kind assigned by the first rule matching the file's name, if any
+    fn kind_for(&self, path: &Path) -> Option<DocumentKind> {
+        let file_name = path.file_name()?;
+        self.rules
+            .iter()
+            .find(|rule| {
+                Glob::new(&rule.pattern)
+                    .map(|glob| glob.compile_matcher().is_match(file_name))
+                    .unwrap_or(false)
+            })
+            .map(|rule| rule.kind)
+    }
+    
+    /// Whether content is just a refusal or error message rather than a real document
+    fn is_error_output(&self, content: &str) -> bool {
+        if content.split_whitespace().count() > ERROR_OUTPUT_MAX_WORDS {
+            return false;
+        }
+        
+        let content = content.to_lowercase();
+        let contains = |phrase: &str| content.contains(&phrase.to_lowercase());
+        match &self.error_phrases {
+            Some(phrases) => phrases.iter().any(|phrase| contains(phrase)),
+            None => DEFAULT_ERROR_PHRASES.iter().any(|phrase| contains(phrase)),
+        }
+    }
+    
+    /// Append a rule to `maid.toml`, creating the file if needed
+    fn append_rule(&mut self, dir_path: &Path, rule: ClassificationRule) -> Result<()> {
+        let path = Self::path(dir_path);
+        let entry = format!(
+            "\n[[rules]]\npattern = {}\nkind = {}\n",
+            toml::Value::String(rule.pattern.clone()),
+            toml::Value::String(format!("{:?}", rule.kind))
+        );
+        
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        file.write_all(entry.as_bytes())?;
+        
+        self.rules.push(rule);
+        Ok(())
+    }
+}
+
+/// Write a commented `maid.toml` with the built-in keywords and error phrases
+/// filled in, into `dir_path` or (`global`) the user config directory.
+/// Returns where it was written. An existing file is only replaced with `force`.
+pub fn init_config(dir_path: &Path, global: bool, force: bool) -> Result<PathBuf> {
+    let path = if global {
+        Config::user_path().context("Couldn't find a config directory (set HOME or XDG_CONFIG_HOME)")?
+    } else {
+        Config::path(dir_path)
+    };
+    if path.exists() && !force {
+        anyhow::bail!("{} already exists; use --force to overwrite it", path.display());
+    }
+    
+    let list = |items: &[&str]| {
+        let quoted: Vec<String> = items.iter().map(|item| toml::Value::String(item.to_string()).to_string()).collect();
+        format!("[{}]", quoted.join(", "))
+    };
+    
+    let mut content = String::from(
+        "# maid configuration. maid reads this from the directory it cleans, or from\n\
+         # the user config directory (~/.config/maid/maid.toml) if there isn't one.\n\
+         \n\
+         # Phrases marking a file as a failed generation for --skip-error-output.\n\
+         # Setting this replaces the built-in list, which is what it starts out as.\n",
+    );
+    content.push_str(&format!("error_phrases = {}\n", list(DEFAULT_ERROR_PHRASES)));
+    content.push_str(
+        "\n# Keywords marking a file as each kind, in filenames and in content (matched\n\
+         # case-insensitively). These start out as the built-in ones, which are always\n\
+         # checked, so add your own project's words to them.\n",
+    );
+    for (kind, filename, keywords) in BUILTIN_KEYWORDS {
+        content.push_str(&format!(
+            "\n[keywords.{:?}]\nfilename = {}\ncontent = {}\n",
+            kind,
+            list(filename),
+            list(keywords)
+        ));
+    }
+    content.push_str(
+        "\n# Colors to print kinds in with --verbose\n\
+         # [colors]\n\
+         # Report = \"red\"\n\
+         \n\
+         # Always give files matching a glob a kind (the first matching rule wins)\n\
+         # [[rules]]\n\
+         # pattern = \"*_NOTES.md\"\n\
+         # kind = \"Summary\"\n",
+    );
+    
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// How much of a file is checked before reading it as text
+const TEXT_SNIFF_BYTES: usize = 8 * 1024;
+
+/// A file that looks binary or isn't UTF-8, found without reading all of it
+#[derive(Debug, thiserror::Error)]
+#[error("not a text file ({0})")]
+struct NotText(&'static str);
+
+/// Check the start of a file for signs that it isn't UTF-8 text
+fn sniff_text(file: &mut File) -> Result<()> {
+    let mut head = Vec::with_capacity(TEXT_SNIFF_BYTES);
+    (&*file).take(TEXT_SNIFF_BYTES as u64).read_to_end(&mut head)?;
+    file.rewind()?;
+    
+    if head.contains(&0) {
+        return Err(NotText("contains NUL bytes").into());
+    }
+    
+    let control_bytes = head
+        .iter()
+        .filter(|&&b| (b < 0x20 && !b"\t\n\r\x0c\x1b".contains(&b)) || b == 0x7f)
+        .count();
+    if control_bytes * 10 > head.len() {
+        return Err(NotText("too many non-text bytes").into());
+    }
+    
+    // A multi-byte character cut off at the end of the sample is fine
+    if let Err(e) = std::str::from_utf8(&head) {
+        if e.error_len().is_some() {
+            return Err(NotText("invalid UTF-8").into());
+        }
+    }
+    
+    Ok(())
+}
+
+/// Represents a file with its metadata and classification
+#[derive(Debug)]
+pub struct FileInfo {
+    pub path: PathBuf,
+    pub file_type: FileType,
+    pub doc_kind: DocumentKind,
+    pub name: String,
+    pub content: String,
+    /// Kind named by a `type:` or `kind:` field in YAML frontmatter
+    pub frontmatter_kind: Option<DocumentKind>,
+    /// How sure the classification is, from 0 to 1 (1 for frontmatter and rules)
+    pub confidence: f32,
+    pub created_date: Option<chrono::DateTime<chrono::Local>>,
+    /// Where `created_date` came from
+    pub date_source: Option<DateSource>,
+}
+
+/// Where a file's date was taken from, in order of precedence: a date in
+/// the content or name beats the filesystem's timestamps
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateSource {
+    /// The newest changelog-style version header, like `## [1.2.0] - 2024-03-15`
+    Header,
+    /// A `date:` field in YAML frontmatter
+    Frontmatter,
+    /// A date in the filename, like `report_2024-03-15`
+    Filename,
+    Created,
+    /// The filesystem has no creation time (common on Linux), so the
+    /// modification time stands in
+    Modified,
+}
+
+impl FileInfo {
+    /// Read and analyze a file
+    pub fn new(path: PathBuf) -> Result<Self> {
+        Self::new_with_cache(path, None)
+    }
+    
+    /// Analyze content that isn't (or needn't be read again) from disk. The
+    /// path only supplies the file's name and type, so it's dated only by
+    /// what the content and name say.
+    pub fn from_content(path: PathBuf, content: String) -> Self {
+        Self::analyze(path, content, None)
+    }
+    
+    /// Analyze a file, consulting the classification cache if one is given
+    fn new_with_cache(path: PathBuf, cache: Option<&Mutex<ClassificationCache>>) -> Result<Self> {
+        // Read file content
+        let permit = IO_LIMIT.acquire();
+        let mut file = File::open(&path).context("Failed to open file")?;
+        sniff_text(&mut file)?;
+        let mut content = String::new();
+        file.read_to_string(&mut content)
+            .context("Failed to read file content")?;
+        drop(file);
+        drop(permit);
+
+        let mut info = Self::analyze(path, content, cache);
+        if info.created_date.is_none() {
+            let dated = file_date(&info.path);
+            info.created_date = dated.map(|(date, _)| date);
+            info.date_source = dated.map(|(_, source)| source);
+        }
+        Ok(info)
+    }
+    
+    /// Classify content by its path and what it says
+    fn analyze(path: PathBuf, content: String, cache: Option<&Mutex<ClassificationCache>>) -> Self {
+        let file_type = FileType::from_path_and_content(&path, &content);
+        
+        let name = path
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        
+        // Determine document kind based on content and filename, unless the
+        // frontmatter says what the file is
+        let frontmatter_kind = split_frontmatter(&content).0.and_then(frontmatter_kind);
+        let (doc_kind, confidence) = match (frontmatter_kind, cache) {
+            (Some(kind), _) => (kind, 1.0),
+            (None, Some(cache)) => {
+                ClassificationCache::classify(cache, &path, &name, &content, file_type)
+            }
+            (None, None) => classify_document_kind(&name, &content, &HashMap::new(), file_type),
+        };
+        
+        let frontmatter = split_frontmatter(&content).0;
+        let dated = version_header_date(&content)
+            .map(|date| (date, DateSource::Header))
+            .or_else(|| frontmatter.and_then(frontmatter_date).map(|date| (date, DateSource::Frontmatter)))
+            .or_else(|| filename_date(&name).map(|date| (date, DateSource::Filename)))
+            .and_then(|(date, source)| Some((local_midnight(date)?, source)));
+
+        FileInfo {
+            path,
+            file_type,
+            doc_kind,
+            name,
+            content,
+            frontmatter_kind,
+            confidence,
+            created_date: dated.map(|(date, _)| date),
+            date_source: dated.map(|(_, source)| source),
+        }
+    }
+    
+    /// The content after any YAML frontmatter
+    fn body(&self) -> &str {
+        split_frontmatter(&self.content).1
+    }
+    
+    /// Words in the content, not counting frontmatter
+    fn word_count(&self) -> usize {
+        self.body().split_whitespace().count()
+    }
+
+    /// Treat a file like `report.md.txt` as the `report.md` it was meant to
+    /// be, so the trailing extension is dropped when it's renamed
+    fn drop_trailing_extension(&mut self) {
+        let Some(file_type) = FileType::from_double_extension(&self.path) else {
+            return;
+        };
+        
+        self.file_type = file_type;
+        if let Some(stem) = Path::new(&self.name).file_stem().and_then(|stem| stem.to_str()) {
+            self.name = stem.to_string();
+        }
+    }
+    
+    /// Reclassify with the configured keywords, then let a matching
+    /// configuration rule override the detected kind
+    fn apply_rules(&mut self, config: &Config) {
+        if !config.keywords.is_empty() && self.frontmatter_kind.is_none() {
+            (self.doc_kind, self.confidence) =
+                classify_document_kind(&self.name, &self.content, &config.keywords, self.file_type);
+        }
+        
+        if let Some(kind) = config.kind_for(&self.path) {
+            self.doc_kind = kind;
+            self.confidence = 1.0;
+        }
+    }
+    
+    /// Decide a file with both report and summary keywords by its structure,
+    /// unless frontmatter or a configuration rule pins its kind
+    fn classify_report_vs_summary_by_structure(&mut self, config: &Config) {
+        if !matches!(self.doc_kind, DocumentKind::Report | DocumentKind::Summary)
+            || self.frontmatter_kind.is_some()
+            || config.kind_for(&self.path).is_some()
+        {
+            return;
+        }
+        
+        let filename_lower = self.name.to_lowercase();
+        let content_lower = self.content.to_lowercase();
+        let has_keywords = |kind| has_kind_keywords(kind, &filename_lower, &content_lower, &config.keywords);
+        if has_keywords(DocumentKind::Report) && has_keywords(DocumentKind::Summary) {
+            self.doc_kind = report_or_summary_by_structure(&self.content);
+        }
+    }
+    
+    /// Generate a better, more human-readable filename
+    pub fn generate_new_filename(&self) -> String {
+        // Changelogs and licenses are found by their conventional names
+        if matches!(self.doc_kind, DocumentKind::Changelog | DocumentKind::License) {
+            if let Some(file_name) = self.path.file_name().and_then(|name| name.to_str()) {
+                return file_name.to_string();
+            }
+        }
+        
+        // Strip a "Kind - " prefix and review marker from an earlier run so
+        // they aren't applied twice
+        let kind_prefix = Regex::new(r"(?i)^(?:rubric|report|guide|summary|data|faq|api reference) - ").unwrap();
+        let name = self.name.strip_suffix(REVIEW_MARKER).unwrap_or(&self.name);
+        let normalized_name = kind_prefix
+            .replace(name, "")
+            .replace(['_', '-'], " ")
+            .to_lowercase();
+
+        // Extract relevant information from AI-generated filenames
+        let re_install = Regex::new(r"(?i)(?:setup|install)[_\-\s]*(.*?)$").ok();
+        let re_test = Regex::new(r"(?i)test[_\-\s]*(.*?)$").ok();
+        let re_launch = Regex::new(r"(?i)launch[_\-\s]*(.*?)$").ok();
+        let re_verify = Regex::new(r"(?i)verify[_\-\s]*(.*?)$").ok();
+        let re_cleanup = Regex::new(r"(?i)cleanup[_\-\s]*(.*?)$").ok();
+        let re_integration = Regex::new(r"(?i)integration[_\-\s]*(.*?)$").ok();
+        let re_config = Regex::new(r"(?i)config(?:uration)?[_\-\s]*(.*?)$").ok();
+        let re_build = Regex::new(r"(?i)build[_\-\s]*(.*?)$").ok();
+        let re_deploy = Regex::new(r"(?i)deploy[_\-\s]*(.*?)$").ok();
+        
+        let mut improved_name = normalized_name.clone();
+        
+        // Apply regex patterns to extract meaningful information
+        if let Some(re) = &re_install {
+            if let Some(caps) = re.captures(&normalized_name) {
+                if let Some(m) = caps.get(1) {
+                    improved_name = format!("Install {}", m.as_str().trim());
+                }
+            }
+        }
+        
+        if let Some(re) = &re_test {
+            if let Some(caps) = re.captures(&normalized_name) {
+                if let Some(m) = caps.get(1) {
+                    improved_name = format!("Test {}", m.as_str().trim());
+                }
+            }
+        }
+        
+        if let Some(re) = &re_launch {
+            if let Some(caps) = re.captures(&normalized_name) {
+                if let Some(m) = caps.get(1) {
+                    improved_name = format!("Launch {}", m.as_str().trim());
+                }
+            }
+        }
+        
+        if let Some(re) = &re_verify {
+            if let Some(caps) = re.captures(&normalized_name) {
+                if let Some(m) = caps.get(1) {
+                    improved_name = format!("Verify {}", m.as_str().trim());
+                }
+            }
+        }
+        
+        if let Some(re) = &re_cleanup {
+            if let Some(caps) = re.captures(&normalized_name) {
+                if let Some(m) = caps.get(1) {
+                    improved_name = format!("Cleanup {}", m.as_str().trim());
+                }
+            }
+        }
+        
+        if let Some(re) = &re_integration {
+            if let Some(caps) = re.captures(&normalized_name) {
+                if let Some(m) = caps.get(1) {
+                    improved_name = format!("Integration {}", m.as_str().trim());
+                }
+            }
+        }
+        
+        if let Some(re) = &re_config {
+            if let Some(caps) = re.captures(&normalized_name) {
+                if let Some(m) = caps.get(1) {
+                    improved_name = format!("Configuration {}", m.as_str().trim());
+                }
+            }
+        }
+        
+        if let Some(re) = &re_build {
+            if let Some(caps) = re.captures(&normalized_name) {
+                if let Some(m) = caps.get(1) {
+                    improved_name = format!("Build {}", m.as_str().trim());
+                }
+            }
+        }
+        
+        if let Some(re) = &re_deploy {
+            if let Some(caps) = re.captures(&normalized_name) {
+                if let Some(m) = caps.get(1) {
+                    improved_name = format!("Deploy {}", m.as_str().trim());
+                }
+            }
+        }
+
+        // Convert to title case
+        let title_case: String = improved_name
+            .split_whitespace()
+            .map(|word| {
+                let mut chars = word.chars();
+                match chars.next() {
+                    None => String::new(),
+                    Some(first) => {
+                        let capitalized = first.to_uppercase().collect::<String>();
+                        capitalized + chars.as_str()
+                    }
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ");
+        let title_case = sanitize_title(&title_case);
+
+        // Add prefix based on document kind
+        let prefixed_name = match self.doc_kind {
+            DocumentKind::Rubric => format!("Rubric - {}", title_case),
+            DocumentKind::Report => format!("Report - {}", title_case),
+            DocumentKind::Guide => format!("Guide - {}", title_case),
+            DocumentKind::Summary => format!("Summary - {}", title_case),
+            DocumentKind::Data => format!("Data - {}", title_case),
+            DocumentKind::Faq => format!("FAQ - {}", title_case),
+            DocumentKind::ApiReference => format!("API Reference - {}", title_case),
+            DocumentKind::Script
+            | DocumentKind::TaskList
+            | DocumentKind::Changelog
+            | DocumentKind::License
+            | DocumentKind::Unknown => title_case,
+        };
+
+        // Scripts found by their shebang stay without an extension
+        // (see `--add-extension`)
+        if self.path.extension().is_none() && matches!(self.file_type, FileType::Shell | FileType::Python) {
+            return prefixed_name;
+        }
+        
+        // Add extension
+        match self.file_type {
+            FileType::Markdown => format!("{}.md", prefixed_name),
+            FileType::Shell => format!("{}.sh", prefixed_name),
+            FileType::Python => format!("{}.py", prefixed_name),
+            // Keep whichever of .yml/.yaml the file already uses
+            FileType::Yaml => format!(
+                "{}.{}",
+                prefixed_name,
+                self.path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .filter(|ext| ext.eq_ignore_ascii_case("yaml"))
+                    .map_or("yml", |_| "yaml")
+            ),
+            // Extensions added with --include-ext keep their extension
+            FileType::Other => match self.path.extension().and_then(|ext| ext.to_str()) {
+                Some(ext) => format!("{}.{}", prefixed_name, ext),
+                None => self
+                    .path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or("unknown")
+                    .to_string(),
+            },
+        }
+    }
+
+    /// Generate suggested target directory based on document kind, with
+    /// `subpath` (the file's directory relative to the scan root, or empty)
+    /// recreated beneath it
+    pub fn suggest_target_directory(&self, base_dir: &Path, roots: &TargetRoots, subpath: &Path) -> PathBuf {
+        let TargetRoots { docs, scripts } = roots;
+        let kind_dir = match (&self.file_type, &self.doc_kind) {
+            (FileType::Markdown, DocumentKind::Rubric) => docs.join("rubrics"),
+            (FileType::Markdown, DocumentKind::Report) => docs.join("reports"),
+            (FileType::Markdown, DocumentKind::Guide) => docs.join("guides"),
+            (FileType::Markdown, DocumentKind::Summary) => docs.join("summaries"),
+            (FileType::Markdown, DocumentKind::Data) => docs.join("data"),
+            (FileType::Markdown, DocumentKind::Faq) => docs.join("faq"),
+            (FileType::Markdown, DocumentKind::ApiReference) => docs.join("reference"),
+            (_, DocumentKind::Changelog) => docs.clone(),
+            (FileType::Other, _) => base_dir.join("misc"),
+            (FileType::Python, _) => scripts.join("python"),
+            (FileType::Yaml, _) => base_dir.join("config"),
+            (FileType::Shell, DocumentKind::Script) => {
+                // Determine script subcategory
+                let content_lower = self.content.to_lowercase();
+                if content_lower.contains("install") || content_lower.contains("setup") {
+                    scripts.join("setup")
+                } else if content_lower.contains("test") {
+                    scripts.join("tests")
+                } else if content_lower.contains("build") {
+                    scripts.join("build")
+                } else {
+                    scripts.clone()
+                }
+            }
+            _ => base_dir.to_path_buf(),
+        };
+        kind_dir.join(subpath)
+    }
+}
+
+/// Where restructuring puts documentation and scripts
+pub struct TargetRoots {
+    pub docs: PathBuf,
+    pub scripts: PathBuf,
+}
+
+impl TargetRoots {
+    /// `docs/` and `scripts/` under the base directory (the directory being
+    /// cleaned, or --target-dir), unless --docs-root or --scripts-root moved
+    /// them elsewhere
+    pub fn new(base_dir: &Path, options: &CleanOptions) -> Self {
+        TargetRoots {
+            docs: options.docs_root.clone().unwrap_or_else(|| base_dir.join("docs")),
+            scripts: options.scripts_root.clone().unwrap_or_else(|| base_dir.join("scripts")),
+        }
+    }
+}
+
+/// Split a leading `---` ... `---` (or `...`) YAML frontmatter block from the
+/// rest of a document. Content without one is all body.
+fn split_frontmatter(content: &str) -> (Option<&str>, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+    
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if matches!(line.trim_end(), "---" | "...") {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    
+    // An unclosed block is just a horizontal rule
+    (None, content)
+}
+
+/// The document kind a frontmatter `type:` or `kind:` field names, if it's one maid knows
+fn frontmatter_kind(frontmatter: &str) -> Option<DocumentKind> {
+    frontmatter.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        if !matches!(key.trim().to_lowercase().as_str(), "type" | "kind") {
+            return None;
+        }
+        parse_kind(value.trim().trim_matches(|c| c == '"' || c == '\'')).ok()
+    })
+}
+
+/// The date in a frontmatter `date:` field, if it starts with an ISO date
+fn frontmatter_date(frontmatter: &str) -> Option<chrono::NaiveDate> {
+    frontmatter.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        if key.trim().to_lowercase() != "date" {
+            return None;
+        }
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+        chrono::NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+    })
+}
+
+/// The newest date among changelog-style version headers such as
+/// `## [1.2.0] - 2024-03-15` or `## v2.0 (2024-03-15)`
+fn version_header_date(content: &str) -> Option<chrono::NaiveDate> {
+    let header = Regex::new(
+        r"(?m)^#{1,6}[ \t]*\[?v?\d+(?:\.\d+)+[^\]\s]*\]?[ \t]*[-–—]?[ \t]*\(?(\d{4}-\d{2}-\d{2})\)?[ \t]*$",
+    )
+    .unwrap();
+    header
+        .captures_iter(content)
+        .filter_map(|captures| chrono::NaiveDate::parse_from_str(&captures[1], "%Y-%m-%d").ok())
+        .max()
+}
+
+/// A date written into a filename, like `report_2024-03-15` or `notes-20240315`
+fn filename_date(name: &str) -> Option<chrono::NaiveDate> {
+    let date = Regex::new(r"(?:^|\D)(\d{4})[-_]?(\d{2})[-_]?(\d{2})(?:\D|$)").unwrap();
+    let captures = date.captures(name)?;
+    chrono::NaiveDate::from_ymd_opt(
+        captures[1].parse().ok()?,
+        captures[2].parse().ok()?,
+        captures[3].parse().ok()?,
+    )
+}
+
+/// The start of a day in local time
+fn local_midnight(date: chrono::NaiveDate) -> Option<chrono::DateTime<chrono::Local>> {
+    date.and_hms_opt(0, 0, 0)?.and_local_timezone(chrono::Local).earliest()
+}
+
+/// Added to the names of files classified below --min-confidence with
+/// --annotate-confidence-in-name, so they stand out for review
+const REVIEW_MARKER: &str = " (review)";
+
+/// Put `REVIEW_MARKER` before a file name's extension
+fn with_review_marker(file_name: &str) -> String {
+    match file_name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{}{}.{}", stem, REVIEW_MARKER, ext),
+        _ => format!("{}{}", file_name, REVIEW_MARKER),
+    }
+}
+
+/// Make a title safe to use as a single path component: path separators become
+/// `-`, the resulting runs of separators collapse, and leading dots are trimmed
+/// so the file is never hidden
+fn sanitize_title(title: &str) -> String {
+    let replaced = title.replace(['/', '\\'], "-");
+    
+    let mut collapsed = String::with_capacity(replaced.len());
+    for c in replaced.chars() {
+        if c == '-' && collapsed.ends_with('-') {
+            continue;
+        }
+        collapsed.push(c);
+    }
+    
+    let trimmed = collapsed
+        .trim_start_matches(|c: char| c == '.' || c == '-' || c.is_whitespace())
+        .trim_end();
+    
+    if trimmed.is_empty() {
+        "Untitled".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Built-in filename and content keywords for each kind, in the order kinds are checked
+const BUILTIN_KEYWORDS: [(DocumentKind, &[&str], &[&str]); 10] = [
+    (
+        DocumentKind::License,
+        &["license", "licence", "copying"],
+        &["spdx-license-identifier"],
+    ),
+    (
+        DocumentKind::Changelog,
+        &["changelog", "history"],
+        &["## [unreleased]", "keep a changelog"],
+    ),
+    (
+        DocumentKind::TaskList,
+        &["todo", "tasks", "backlog"],
+        &[],
+    ),
+    (
+        DocumentKind::Rubric,
+        &["rubric"],
+        &["# rubric", "rubric for", "evaluation rubric", "assessment criteria", "scoring guide"],
+    ),
+    (
+        DocumentKind::Data,
+        &["dataset", "data_dictionary", "data-dictionary", "datasheet", "data_card", "codebook"],
+        &["# data dictionary", "# dataset", "# datasheet"],
+    ),
+    (
+        DocumentKind::Faq,
+        &["faq", "questions"],
+        &[],
+    ),
+    (
+        DocumentKind::Report,
+        &["report", "complete", "status", "analysis", "assessment"],
+        &[
+            "# report",
+            "# completion",
+            "# status",
+            "# analysis",
+            "task completion",
+            "completion report",
+            "status update",
+        ],
+    ),
+    (
+        DocumentKind::Guide,
+        &["guide", "how_to", "howto", "manual", "tutorial", "instructions"],
+        &[
+            "# guide",
+            "# how to",
+            "step by step",
+            "# tutorial",
+            "# instructions",
+            "how to use",
+            "usage instructions",
+        ],
+    ),
+    (
+        DocumentKind::Summary,
+        &["summary", "overview", "recap", "synopsis"],
+        &[
+            "# summary",
+            "## summary",
+            "# overview",
+            "# recap",
+            "in conclusion",
+            "executive summary",
+            "project summary",
+        ],
+    ),
+    // "api" and "reference" are matched as whole filename words instead (see
+    // `has_api_reference_name`), so names like "rapid" or "preferences" don't count
+    (
+        DocumentKind::ApiReference,
+        &[],
+        &["# api reference", "## endpoints", "### parameters", "returns:"],
+    ),
+];
+
+/// How much each kind of evidence adds to a classification's confidence,
+/// which is capped at 1. A filename keyword and a content keyword together
+/// make a fairly sure match; one content phrase alone is a weak one.
+const FILENAME_KEYWORD_CONFIDENCE: f32 = 0.6;
+const CONTENT_KEYWORD_CONFIDENCE: f32 = 0.25;
+const STRUCTURE_CONFIDENCE: f32 = 0.5;
+
+/// Determine document kind based on filename and content, with how
+/// confident the match is (0 to 1)
+pub fn determine_document_kind(filename: &str, content: &str) -> (DocumentKind, f32) {
+    determine_document_kind_with(filename, content, &HashMap::new())
+}
+
+/// Determine document kind, also matching project-specific keywords from `maid.toml`
+pub fn determine_document_kind_with(
+    filename: &str,
+    content: &str,
+    extra: &HashMap<DocumentKind, KindKeywords>,
+) -> (DocumentKind, f32) {
+    classify_document_kind(filename, content, extra, FileType::Markdown)
+}
+
+/// Determine document kind for a file of a known type. API references are
+/// markdown, so other files that only look like one (a saved
+/// `api_response.json`, a script documenting `Returns:`) fall through to the
+/// kinds after it.
+fn classify_document_kind(
+    filename: &str,
+    content: &str,
+    extra: &HashMap<DocumentKind, KindKeywords>,
+    file_type: FileType,
+) -> (DocumentKind, f32) {
+    let filename_lower = filename.to_lowercase();
+    let content_lower = content.to_lowercase();
+    
+    for (kind, _, _) in BUILTIN_KEYWORDS {
+        if kind == DocumentKind::ApiReference && file_type != FileType::Markdown {
+            continue;
+        }
+        
+        // A table describing fields sets data docs apart from generic reports,
+        // repeated question blocks do the same for FAQs,
+        // and so does dense, language-tagged code for API references
+        let mut confidence = keyword_confidence(kind, &filename_lower, &content_lower, extra);
+        if (kind == DocumentKind::Data && has_field_description_table(&content_lower))
+            || (kind == DocumentKind::Faq && has_qa_structure(&content_lower))
+            || (kind == DocumentKind::ApiReference && has_dense_tagged_code(&content_lower))
+        {
+            confidence += STRUCTURE_CONFIDENCE;
+        }
+        if kind == DocumentKind::ApiReference && has_api_reference_name(&filename_lower) {
+            confidence += FILENAME_KEYWORD_CONFIDENCE;
+        }
+        if confidence > 0.0 {
+            return (kind, confidence.min(1.0));
+        }
+    }
+    
+    // Mostly checkboxes with no other kind's keywords makes a task list
+    if has_task_checklist(&content_lower) {
+        return (DocumentKind::TaskList, STRUCTURE_CONFIDENCE);
+    }
+    
+    // Check for scripts (shell files and anything with a shebang are scripts)
+    if filename_lower.ends_with(".sh") || content.starts_with("#!") {
+        return (DocumentKind::Script, 1.0);
+    }
+    let confidence = keyword_confidence(DocumentKind::Script, &filename_lower, &content_lower, extra);
+    if confidence > 0.0 {
+        return (DocumentKind::Script, confidence.min(1.0));
+    }
+    
+    // Default
+    (DocumentKind::Unknown, 0.0)
+}
+
+/// Share of non-blank lines that must be `- [ ]` / `- [x]` items for a task list
+const TASK_LIST_MIN_CHECKBOX_SHARE: f64 = 0.5;
+
+/// Whether lowercased markdown is mostly a checklist, outside code fences
+fn has_task_checklist(content_lower: &str) -> bool {
+    let mut in_fence = false;
+    let mut lines = 0;
+    let mut checkboxes = 0;
+    for line in content_lower.lines().map(str::trim) {
+        if line.starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence || line.is_empty() {
+            continue;
+        }
+        
+        lines += 1;
+        let item = line
+            .strip_prefix("- ")
+            .or_else(|| line.strip_prefix("* "))
+            .unwrap_or_default();
+        if item.starts_with("[ ]") || item.starts_with("[x]") {
+            checkboxes += 1;
+        }
+    }
+    
+    checkboxes > 0 && checkboxes as f64 >= lines as f64 * TASK_LIST_MIN_CHECKBOX_SHARE
+}
+
+/// Fewest questions, and the share of sections that must be questions, for an FAQ
+const FAQ_MIN_QUESTIONS: usize = 3;
+const FAQ_MIN_QUESTION_SHARE: f64 = 0.5;
+
+/// Whether lowercased markdown is structured as repeated `### Q:` or `**Q:**`
+/// blocks, outside code fences. Questions have to make up most of the
+/// sections, so a guide with one FAQ section isn't taken for an FAQ.
+fn has_qa_structure(content_lower: &str) -> bool {
+    let mut in_fence = false;
+    let mut sections = 0;
+    let mut questions = 0;
+    for line in content_lower.lines().map(str::trim) {
+        if line.starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        
+        // A heading or a bold question each starts a section
+        let is_question = match line.strip_prefix('#') {
+            Some(heading) => heading.trim_start_matches('#').trim().starts_with("q:"),
+            None => line.starts_with("**q:") || line.starts_with("**q**:"),
+        };
+        if line.starts_with('#') || is_question {
+            sections += 1;
+        }
+        if is_question {
+            questions += 1;
+        }
+    }
+    
+    questions >= FAQ_MIN_QUESTIONS && questions as f64 >= sections as f64 * FAQ_MIN_QUESTION_SHARE
+}
+
+/// Whether a lowercased filename has `api` or `reference` as a whole word
+fn has_api_reference_name(filename_lower: &str) -> bool {
+    filename_lower
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .any(|word| matches!(word, "api" | "apis" | "reference" | "apiref"))
+}
+
+/// Fewest language-tagged code fences, and the most lines of content per
+/// fence, for code to count as dense enough to be a reference
+const API_MIN_TAGGED_FENCES: usize = 3;
+const API_MAX_LINES_PER_FENCE: usize = 15;
+
+/// Whether lowercased markdown is mostly example code: many fences opened
+/// with a language tag (```` ```json ````), close together
+fn has_dense_tagged_code(content_lower: &str) -> bool {
+    let mut in_fence = false;
+    let mut tagged_fences = 0;
+    let mut lines = 0;
+    for line in content_lower.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        lines += 1;
+        if let Some(tag) = line.strip_prefix("```") {
+            if !in_fence && !tag.trim().is_empty() {
+                tagged_fences += 1;
+            }
+            in_fence = !in_fence;
+        }
+    }
+    
+    tagged_fences >= API_MIN_TAGGED_FENCES && lines <= tagged_fences * API_MAX_LINES_PER_FENCE
+}
+
+/// Table headers naming the thing a row describes, and headers describing it
+const FIELD_TABLE_NAME_HEADERS: &[&str] = &["field", "column", "variable"];
+const FIELD_TABLE_DETAIL_HEADERS: &[&str] = &["description", "type", "meaning", "definition"];
+
+/// Whether lowercased markdown has a table describing fields or columns, such
+/// as `| Field | Type | Description |`
+fn has_field_description_table(content_lower: &str) -> bool {
+    let lines: Vec<&str> = content_lower.lines().map(str::trim).collect();
+    lines.windows(2).any(|pair| {
+        let (header, separator) = (pair[0], pair[1]);
+        if !header.starts_with('|')
+            || !separator.starts_with('|')
+            || !separator.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+        {
+            return false;
+        }
+        
+        let cells: Vec<&str> = header.split('|').map(str::trim).collect();
+        cells.iter().any(|cell| FIELD_TABLE_NAME_HEADERS.contains(cell))
+            && cells.iter().any(|cell| FIELD_TABLE_DETAIL_HEADERS.contains(cell))
+    })
+}
+
+/// Whether a lowercased filename or content has any of a kind's built-in or
+/// configured keywords
+fn has_kind_keywords(
+    kind: DocumentKind,
+    filename_lower: &str,
+    content_lower: &str,
+    extra: &HashMap<DocumentKind, KindKeywords>,
+) -> bool {
+    keyword_confidence(kind, filename_lower, content_lower, extra) > 0.0
+}
+
+/// Confidence a kind's keywords give: some for a filename keyword, and a
+/// little for each content keyword found (uncapped)
+fn keyword_confidence(
+    kind: DocumentKind,
+    filename_lower: &str,
+    content_lower: &str,
+    extra: &HashMap<DocumentKind, KindKeywords>,
+) -> f32 {
+    let (filename_keywords, content_keywords) = kind_keywords(kind, extra);
+    
+    let filename_match = filename_keywords
+        .iter()
+        .any(|keyword| filename_lower.contains(&keyword.to_lowercase()));
+    let content_matches = content_keywords
+        .iter()
+        .filter(|keyword| content_lower.contains(&keyword.to_lowercase()))
+        .count();
+    
+    let filename_confidence = if filename_match { FILENAME_KEYWORD_CONFIDENCE } else { 0.0 };
+    filename_confidence + content_matches as f32 * CONTENT_KEYWORD_CONFIDENCE
+}
+
+/// The filename and content keywords of a kind: the built-in ones followed by
+/// any from `maid.toml`
+fn kind_keywords<'a>(
+    kind: DocumentKind,
+    extra: &'a HashMap<DocumentKind, KindKeywords>,
+) -> (Vec<&'a str>, Vec<&'a str>) {
+    let (builtin_filename, builtin_content): (&[&str], &[&str]) = BUILTIN_KEYWORDS
+        .iter()
+        .find(|(builtin_kind, _, _)| *builtin_kind == kind)
+        .map_or((&[], &[]), |(_, filename, content)| (*filename, *content));
+    let extra = extra.get(&kind);
+    
+    // Repeating a built-in keyword (as a `maid config init` file does) adds nothing
+    let merge = |builtin: &[&'a str], extra: Option<&'a Vec<String>>| {
+        let mut keywords = builtin.to_vec();
+        for keyword in extra.into_iter().flatten() {
+            if !keywords.iter().any(|known| known.eq_ignore_ascii_case(keyword)) {
+                keywords.push(keyword);
+            }
+        }
+        keywords
+    };
+    (
+        merge(builtin_filename, extra.map(|e| &e.filename)),
+        merge(builtin_content, extra.map(|e| &e.content)),
+    )
+}
+
+/// One check made while classifying a file, for --classify-trace
+#[derive(Debug, Serialize)]
+struct TraceCheck {
+    stage: &'static str,
+    pattern: String,
+    kind: DocumentKind,
+    matched: bool,
+}
+
+/// Every check made while classifying a file and the kind it ended up with
+#[derive(Debug, Serialize)]
+struct ClassificationTrace {
+    file: PathBuf,
+    checks: Vec<TraceCheck>,
+    decision: DocumentKind,
+    confidence: f32,
+}
+
+impl ClassificationTrace {
+    /// Run every check in the order classification makes them, without
+    /// stopping at the first match, and record the kind the file really gets
+    fn new(mut info: FileInfo, config: &Config, by_structure: bool) -> Self {
+        let filename_lower = info.name.to_lowercase();
+        let content_lower = info.content.to_lowercase();
+        let mut checks = Vec::new();
+        let mut check = |stage, pattern: &str, kind, matched| {
+            checks.push(TraceCheck {
+                stage,
+                pattern: pattern.to_string(),
+                kind,
+                matched,
+            });
+        };
+        
+        if let Some(kind) = info.frontmatter_kind {
+            check("frontmatter", "type/kind field", kind, true);
+        }
+        
+        for kind in BUILTIN_KEYWORDS.map(|(kind, _, _)| kind).into_iter().chain([DocumentKind::Script]) {
+            // Only markdown is checked for being an API reference
+            if kind == DocumentKind::ApiReference && info.file_type != FileType::Markdown {
+                continue;
+            }
+            if kind == DocumentKind::Script {
+                check("task checklist", "checkbox share", DocumentKind::TaskList, has_task_checklist(&content_lower));
+                check("extension", ".sh", kind, filename_lower.ends_with(".sh"));
+            }
+            
+            let (filename_keywords, content_keywords) = kind_keywords(kind, &config.keywords);
+            for keyword in filename_keywords {
+                check("filename keyword", keyword, kind, filename_lower.contains(&keyword.to_lowercase()));
+            }
+            for keyword in content_keywords {
+                check("content keyword", keyword, kind, content_lower.contains(&keyword.to_lowercase()));
+            }
+            
+            if kind == DocumentKind::Data {
+                check("content", "field description table", kind, has_field_description_table(&content_lower));
+            }
+            if kind == DocumentKind::Faq {
+                check("content", "question/answer blocks", kind, has_qa_structure(&content_lower));
+            }
+            if kind == DocumentKind::ApiReference {
+                check("filename", "api/reference word", kind, has_api_reference_name(&filename_lower));
+                check("content", "dense tagged code blocks", kind, has_dense_tagged_code(&content_lower));
+            }
+        }
+        
+        if let Some(file_name) = info.path.file_name() {
+            for rule in &config.rules {
+                let matched = Glob::new(&rule.pattern)
+                    .map(|glob| glob.compile_matcher().is_match(file_name))
+                    .unwrap_or(false);
+                check("rule", &rule.pattern, rule.kind, matched);
+            }
+        }
+        
+        info.apply_rules(config);
+        if by_structure {
+            let before = info.doc_kind;
+            info.classify_report_vs_summary_by_structure(config);
+            check("structure", "report vs summary sections", info.doc_kind, info.doc_kind != before);
+        }
+        
+        ClassificationTrace {
+            file: info.path,
+            checks,
+            decision: info.doc_kind,
+            confidence: info.confidence,
+        }
+    }
+}
+
+/// Write a classification trace of each file to `trace_path`, one JSON object per line
+fn write_classify_trace(trace_path: &Path, file_paths: &[PathBuf], options: &CleanOptions, config: &Config) -> Result<()> {
+    let mut trace = String::new();
+    
+    for file_path in file_paths {
+        let Ok(info) = FileInfo::new(file_path.clone()) else {
+            continue;
+        };
+        let entry = ClassificationTrace::new(info, config, options.classify_report_vs_summary_by_structure);
+        trace.push_str(&serde_json::to_string(&entry)?);
+        trace.push('\n');
+    }
+    
+    fs::write(trace_path, trace)
+        .with_context(|| format!("Failed to write classification trace to {}", trace_path.display()))
+}
+
+/// Sections (headings below the title) at which a document reads as a report
+const REPORT_MIN_SECTIONS: usize = 4;
+
+/// Word count at which a document reads as a report whatever its sections
+const REPORT_MIN_WORDS: usize = 500;
+
+/// Tell a report from a summary by its shape: many sections or a long body
+/// make a report, a short document with few sections a summary
+fn report_or_summary_by_structure(content: &str) -> DocumentKind {
+    let mut in_fence = false;
+    let sections = content
+        .lines()
+        .filter(|line| {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+            }
+            !in_fence && line.starts_with("##")
+        })
+        .count();
+    
+    if sections >= REPORT_MIN_SECTIONS || content.split_whitespace().count() >= REPORT_MIN_WORDS {
+        DocumentKind::Report
+    } else {
+        DocumentKind::Summary
+    }
+}
+
+/// Process a single file, returning the operation planned for it
+fn process_file(
+    file_path: &Path,
+    base_dir: &Path,
+    options: &CleanOptions,
+    config: &Config,
+    cache: Option<&Mutex<ClassificationCache>>,
+    claimed: &ClaimedTargets,
+) -> Result<Option<PlannedOperation>> {
+    let CleanOptions {
+        restructure,
+        dry_run,
+        verbose,
+        move_files,
+        link,
+        preserve_timestamps,
+        verify_shell_syntax,
+        on_conflict,
+        ..
+    } = *options;
+    
+    // Skip if not a file or if hidden
+    if !file_path.is_file() || is_hidden(file_path) {
+        return Ok(None);
+    }
+
+    // Process only the file types we know about, plus any --include-ext ones
+    // (or everything with --include-other)
+    if !is_included(
+        file_path,
+        &options.include_ext,
+        options.include_other,
+        options.include_extensionless,
+        options.strict_extensions,
+    ) {
+        return Ok(None);
+    }
+
+    // Handle symlinks
+    let real_path = if file_path.is_symlink() {
+        fs::canonicalize(file_path).unwrap_or_else(|_| file_path.to_path_buf())
+    } else {
+        file_path.to_path_buf()
+    };
+
+    // Analyze the file
+    let mut file_info = match FileInfo::new_with_cache(real_path.clone(), cache) {
+        Ok(info) => info,
+        Err(e) if e.is::<NotText>() => {
+            if verbose {
+                println!(
+                    "{} {} - {}",
+                    "Skipping:".yellow().bold(),
+                    file_path.display().to_string().yellow(),
+                    e
+                );
+            }
+            return Ok(Some(PlannedOperation {
+                source: file_path.to_path_buf(),
+                target: file_path.to_path_buf(),
+                action: PlanAction::Skip,
+                doc_kind: DocumentKind::Unknown,
+                conflict: None,
+            }));
+        }
+        Err(e) => return Err(e.context("could not be analyzed")),
+    };
+    if options.strict_extensions {
+        file_info.drop_trailing_extension();
+    }
+    file_info.apply_rules(config);
+    if options.classify_report_vs_summary_by_structure {
+        file_info.classify_report_vs_summary_by_structure(config);
+    }
+    
+    // READMEs, licenses and task lists are protected: never renamed or moved
+    let protected = match file_info.doc_kind {
+        _ if is_readme(file_path) => Some("README"),
+        DocumentKind::License => Some("license file"),
+        DocumentKind::TaskList => Some("task list"),
+        _ => None,
+    };
+    if let Some(protected) = protected {
+        if verbose {
+            println!(
+                "{} {} - protected {}",
+                "Skipping:".yellow().bold(),
+                file_path.display().to_string().yellow(),
+                protected
+            );
+        }
+        return Ok(Some(PlannedOperation {
+            source: file_path.to_path_buf(),
+            target: file_path.to_path_buf(),
+            action: PlanAction::Skip,
+            doc_kind: file_info.doc_kind,
+            conflict: None,
+        }));
+    }
+    
+    if let Some(pattern) = excluded_by_content(&options.exclude_content, &file_info.content) {
+        if verbose {
+            println!(
+                "{} {} - content matches excluded pattern {}",
+                "Skipping:".yellow().bold(),
+                file_path.display().to_string().yellow(),
+                pattern.as_str().bright_black()
+            );
+        }
+        return Ok(Some(PlannedOperation {
+            source: file_path.to_path_buf(),
+            target: file_path.to_path_buf(),
+            action: PlanAction::Skip,
+            doc_kind: file_info.doc_kind,
+            conflict: None,
+        }));
+    }
+    
+    if options.skip_error_output && config.is_error_output(&file_info.content) {
+        if verbose {
+            println!(
+                "{} {} - non-content/error output",
+                "Skipping:".yellow().bold(),
+                file_path.display().to_string().yellow()
+            );
+        }
+        return Ok(Some(PlannedOperation {
+            source: file_path.to_path_buf(),
+            target: file_path.to_path_buf(),
+            action: PlanAction::Skip,
+            doc_kind: file_info.doc_kind,
+            conflict: None,
+        }));
+    }
+    
+    // Unclassified files are never skipped, but are marked for review with the rest
+    let low_confidence = file_info.confidence < options.min_confidence;
+    if low_confidence && file_info.doc_kind != DocumentKind::Unknown && !options.annotate_confidence_in_name {
+        if verbose {
+            println!(
+                "{} {} - {:?} with confidence {:.2}, below --min-confidence",
+                "Skipping:".yellow().bold(),
+                file_path.display().to_string().yellow(),
+                file_info.doc_kind,
+                file_info.confidence
+            );
+        }
+        return Ok(Some(PlannedOperation {
+            source: file_path.to_path_buf(),
+            target: file_path.to_path_buf(),
+            action: PlanAction::Skip,
+            doc_kind: DocumentKind::Unknown,
+            conflict: None,
+        }));
+    }
+    
+    // Generate new filename
+    let mut new_filename = file_info.generate_new_filename();
+    if options.add_extension && file_path.extension().is_none() {
+        if let Some(ext) = file_info.file_type.extension() {
+            new_filename = format!("{}.{}", new_filename, ext);
+        }
+    }
+    if low_confidence && options.annotate_confidence_in_name {
+        new_filename = with_review_marker(&new_filename);
+    }
+    
+    // Scripts that fail a syntax check are set aside for review
+    let needs_review = verify_shell_syntax
+        && file_info.file_type == FileType::Shell
+        && check_shell_syntax(&real_path) == Some(false);
+    
+    if needs_review {
+        log::warn!("{}: failed the shell syntax check", file_path.display());
+    }
+    if needs_review && verbose {
+        println!(
+            "  {} {}",
+            "Warning:".yellow(),
+            "Script failed the shell syntax check".bright_black()
+        );
+    }
+    
+    // Determine target location, organizing into --target-dir if one was given
+    let target_base = options.target_dir.as_deref().unwrap_or(base_dir);
+    let roots = TargetRoots::new(target_base, options);
+    let subpath = if options.preserve_tree {
+        file_path
+            .parent()
+            .and_then(|parent| parent.strip_prefix(base_dir).ok())
+            .unwrap_or(Path::new(""))
+    } else {
+        Path::new("")
+    };
+    let target_dir = if restructure && options.flat {
+        target_base.to_path_buf()
+    } else if restructure && needs_review {
+        roots.scripts.join("needs-review").join(subpath)
+    } else if restructure {
+        file_info.suggest_target_directory(target_base, &roots, subpath)
+    } else {
+        file_path.parent().unwrap_or(Path::new(".")).to_path_buf()
+    };
+    
+    let mut target_path = target_dir.join(&new_filename);
+    
+    // Print what we're doing
+    if verbose {
+        println!(
+            "{} {} -> {}",
+            "Processing:".cyan().bold(),
+            file_path.display().to_string().yellow(),
+            target_path.display().to_string().green()
+        );
+        println!(
+            "  {} {} {}",
+            "Type:".cyan(),
+            format!("{:?}", file_info.doc_kind).color(config.kind_color(file_info.doc_kind)),
+            format!("(confidence {:.2})", file_info.confidence).bright_black()
+        );
+    } else if options.format == OutputFormat::Text && !options.no_progress {
+        print!(".");
+        io::stdout().flush()?;
+    }
+    
+    // Targets are claimed while they're decided on, so when another file in
+    // this run already maps to the same name, both survive
+    let mut claimed = claimed.lock().unwrap_or_else(|e| e.into_inner());
+    let claimed_in_run = claimed.contains(&target_path);
+    if claimed_in_run {
+        target_path = next_free_path(&target_path, &claimed);
+    }
+    
+    // Check if source and target are the same
+    let source_canonical = fs::canonicalize(file_path).ok();
+    let target_canonical = fs::canonicalize(&target_path).ok();
+    
+    let conflict = (target_canonical.is_none() || source_canonical != target_canonical)
+        && fs::symlink_metadata(&target_path).is_ok();
+    let conflict = conflict.then_some(on_conflict);
+    
+    match conflict {
+        Some(ConflictStrategy::Rename) => target_path = next_free_path(&target_path, &claimed),
+        // Symlinks can't be created over an existing file, and copying onto
+        // a symlink would write through to whatever it points at
+        Some(ConflictStrategy::Overwrite)
+            if !dry_run && (link || file_path.is_symlink() || target_path.is_symlink()) =>
+        {
+            fs::remove_file(&target_path)?;
+        }
+        _ => {}
+    }
+    
+    if verbose && claimed_in_run {
+        println!(
+            "  {} Another file in this run has the same name, renaming to {}",
+            "Conflict:".yellow(),
+            target_path.display().to_string().green()
+        );
+    }
+    if verbose {
+        match conflict {
+            Some(ConflictStrategy::Skip) => println!(
+                "  {} {}",
+                "Skip:".yellow(),
+                "Target file already exists".bright_black()
+            ),
+            Some(ConflictStrategy::Rename) => println!(
+                "  {} Target file already exists, renaming to {}",
+                "Conflict:".yellow(),
+                target_path.display().to_string().green()
+            ),
+            Some(ConflictStrategy::Overwrite) => println!(
+                "  {} Target file already exists, overwriting it",
+                "Conflict:".yellow()
+            ),
+            None => {}
+        }
+    }
+    
+    let action = if target_canonical.is_some() && source_canonical == target_canonical {
+        if verbose {
+            println!(
+                "  {} {}",
+                "Skip:".yellow(),
+                "Source and target are the same file".bright_black()
+            );
+        }
+        PlanAction::Skip
+    } else if conflict == Some(ConflictStrategy::Skip) {
+        PlanAction::Skip
+    } else if link || file_path.is_symlink() {
+        PlanAction::Symlink
+    } else if move_files {
+        PlanAction::Move
+    } else {
+        PlanAction::Copy
+    };
+    
+    // A file left out because of an existing one doesn't take its name
+    if conflict != Some(ConflictStrategy::Skip) {
+        claimed.insert(target_path.clone());
+    }
+    drop(claimed);
+    
+    // Restructuring also tidies markdown content, not just its location
+    let restructured = (restructure
+        && file_info.file_type == FileType::Markdown
+        && matches!(action, PlanAction::Copy | PlanAction::Move))
+        .then(|| restructure_markdown(&file_info.content, options.normalize_headings_to_sentence_case))
+        .filter(|content| *content != file_info.content);
+    
+    if let (true, Some(content), OutputFormat::Text) = (dry_run, &restructured, options.format) {
+        print_unified_diff(&file_info.content, content, file_path, &target_path);
+    }
+    
+    if dry_run && verbose && matches!(action, PlanAction::Move) {
+        println!(
+            "  {} {} -> {}",
+            "Would move:".blue(),
+            file_path.display().to_string().yellow(),
+            target_path.display().to_string().green()
+        );
+    }
+    
+    // If not dry run, perform the operation
+    if !dry_run && !matches!(action, PlanAction::Skip) {
+        let _permit = IO_LIMIT.acquire();
+        
+        // Create target directory if it doesn't exist
+        if !target_dir.exists() {
+            fs::create_dir_all(&target_dir)?;
+        }
+        
+        // Copy or move the file - use a symlink for symlinks
+        if let PlanAction::Symlink = action {
+            if verbose {
+                println!("  {} Creating symlink", "Info:".blue());
+            }
+            
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::symlink;
+                // Link to the resolved file, since a relative link target
+                // wouldn't resolve from the new location
+                let original_target = fs::canonicalize(file_path)?;
+                symlink(original_target, &target_path)?;
+            }
+            
+            #[cfg(windows)]
+            {
+                use std::os::windows::fs::{symlink_file, symlink_dir};
+                let original_target = fs::canonicalize(file_path)?;
+                let linked = if original_target.is_file() {
+                    symlink_file(original_target, &target_path)
+                } else {
+                    symlink_dir(original_target, &target_path)
+                };
+                
+                // Creating symlinks needs a privilege (or developer mode) on
+                // Windows, so --link copies regular files without it
+                match linked {
+                    Err(e) if link && !file_path.is_symlink() => {
+                        println!(
+                            "{} Couldn't link {} ({}); copying it instead",
+                            "Warning:".yellow().bold(),
+                            file_path.display(),
+                            e
+                        );
+                        fs::copy(file_path, &target_path)?;
+                    }
+                    linked => linked?,
+                }
+            }
+            
+            // The new link replaces the old one when moving
+            if move_files {
+                fs::remove_file(file_path)?;
+            }
+        } else if let PlanAction::Move = action {
+            // Rewriting the moved file resets its times, so they're read first
+            let times = match (&restructured, preserve_timestamps) {
+                (Some(_), true) => Some(read_timestamps(file_path)?),
+                _ => None,
+            };
+            move_file(file_path, &target_path)?;
+            if let Some(content) = &restructured {
+                fs::write(&target_path, content)?;
+            }
+            if let Some(times) = times {
+                set_timestamps(&target_path, times)?;
+            }
+            
+            if verbose {
+                println!(
+                    "  {} {} -> {}",
+                    "Moved:".green(),
+                    file_path.display().to_string().yellow(),
+                    target_path.display().to_string().green()
+                );
+            }
+        } else {
+            // Regular file copy
+            match &restructured {
+                Some(content) => fs::write(&target_path, content)?,
+                None => {
+                    fs::copy(file_path, &target_path)?;
+                }
+            }
+            if preserve_timestamps {
+                copy_timestamps(file_path, &target_path)?;
+            }
+        }
+        
+        if verbose {
+            println!(
+                "  {} {}",
+                "Success:".green(),
+                "File processed".bright_green()
+            );
+        }
+    }
+    
+    Ok(Some(PlannedOperation {
+        source: file_path.to_path_buf(),
+        target: target_path,
+        action,
+        doc_kind: file_info.doc_kind,
+        conflict,
+    }))
+}
+
+/// Normalize markdown structure: keep the first H1 and demote later ones to H2,
+/// collapse runs of three or more blank lines into one, and end with a newline.
+/// Headings are put in sentence case if asked. Fenced code blocks are left alone.
+fn restructure_markdown(content: &str, sentence_case_headings: bool) -> String {
+    let mut lines = Vec::new();
+    let mut seen_h1 = false;
+    let mut in_fence = false;
+    let mut blank_run = 0;
+    
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        
+        if !in_fence && line.trim().is_empty() {
+            blank_run += 1;
+            continue;
+        }
+        
+        // Flush the blank lines before this one, collapsing long runs
+        let blanks = if blank_run >= 3 { 1 } else { blank_run };
+        lines.extend(std::iter::repeat_n(String::new(), blanks));
+        blank_run = 0;
+        
+        let line = match heading_text(line) {
+            Some((hashes, text)) if !in_fence && sentence_case_headings => {
+                format!("{} {}", hashes, sentence_case(text))
+            }
+            _ => line.to_string(),
+        };
+        
+        if !in_fence && (line.starts_with("# ") || line == "#") {
+            if seen_h1 {
+                lines.push(format!("#{}", line));
+                continue;
+            }
+            seen_h1 = true;
+        }
+        lines.push(line);
+    }
+    
+    let mut restructured = lines.join("\n");
+    restructured.push('\n');
+    restructured
+}
+
+/// Acronyms kept in capitals when headings are put in sentence case
+const ACRONYMS: &[&str] = &[
+    "AI", "API", "AWS", "CI", "CD", "CLI", "CPU", "CSS", "CSV", "DNS", "FAQ", "GPU", "HTML", "HTTP",
+    "HTTPS", "ID", "JSON", "JWT", "LLM", "MVP", "OS", "PDF", "PR", "QA", "README", "REST", "SDK",
+    "SQL", "SSH", "SSL", "TLS", "TODO", "TOML", "UI", "URL", "UX", "XML", "YAML",
+];
+
+/// The `#` markers and text of an ATX heading line (`## Text`)
+fn heading_text(line: &str) -> Option<(&str, &str)> {
+    let hashes = line.len() - line.trim_start_matches('#').len();
+    if !(1..=6).contains(&hashes) {
+        return None;
+    }
+    let text = line[hashes..].strip_prefix(' ')?;
+    Some((&line[..hashes], text))
+}
+
+/// Heading text in sentence case: the first word capitalized and the rest
+/// lowercased, except known acronyms and mixed-case names like "GitHub".
+/// Inline code spans and link targets are left as they are.
+fn sentence_case(text: &str) -> String {
+    let word = Regex::new(r"[A-Za-z][A-Za-z0-9]*").unwrap();
+    let mut first = true;
+    
+    text.split('`')
+        .enumerate()
+        .map(|(index, segment)| {
+            // Odd segments sit between backticks
+            if index % 2 == 1 {
+                first = false;
+                return segment.to_string();
+            }
+            
+            segment
+                .split_inclusive(char::is_whitespace)
+                .map(|token| {
+                    if token.contains("](") || token.contains("://") {
+                        first = false;
+                        return token.to_string();
+                    }
+                    word.replace_all(token, |caps: &regex::Captures| {
+                        let word = &caps[0];
+                        let is_first = std::mem::replace(&mut first, false);
+                        let upper = word.to_uppercase();
+                        let rest_lower = word.chars().skip(1).all(|c| !c.is_uppercase());
+                        
+                        if ACRONYMS.contains(&upper.as_str()) {
+                            upper
+                        } else if word != upper && !rest_lower {
+                            // Mixed case like "GitHub" or "macOS" is a name
+                            word.to_string()
+                        } else if is_first {
+                            let mut chars = word.chars();
+                            let initial = chars.next().map(|c| c.to_uppercase().to_string()).unwrap_or_default();
+                            initial + &chars.as_str().to_lowercase()
+                        } else {
+                            word.to_lowercase()
+                        }
+                    })
+                    .to_string()
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("`")
+}
+
+/// Print a colored unified diff between a file's content and what it would become
+fn print_unified_diff(old: &str, new: &str, old_path: &Path, new_path: &Path) {
+    let old_label = old_path.display().to_string();
+    let new_label = new_path.display().to_string();
+    let diff = similar::TextDiff::from_lines(old, new)
+        .unified_diff()
+        .header(&old_label, &new_label)
+        .to_string();
+    
+    // Print in one go, since files are processed in parallel
+    let colored_diff: Vec<String> = diff
+        .lines()
+        .map(|line| {
+            if line.starts_with("+++") || line.starts_with("---") {
+                line.bold().to_string()
+            } else if line.starts_with('+') {
+                line.green().to_string()
+            } else if line.starts_with('-') {
+                line.red().to_string()
+            } else if line.starts_with("@@") {
+                line.cyan().to_string()
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    println!("\n{}", colored_diff.join("\n"));
+}
+
+/// Check a script's syntax with `bash -n` (or `sh -n`). Returns `None` when no
+/// shell interpreter is available to run the check.
+fn check_shell_syntax(path: &Path) -> Option<bool> {
+    use std::process::{Command, Stdio};
+    
+    for shell in ["bash", "sh"] {
+        let status = Command::new(shell)
+            .arg("-n")
+            .arg(path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        
+        if let Ok(status) = status {
+            return Some(status.success());
+        }
+    }
+    
+    None
+}
+
+/// Move a file, falling back to copy and delete when a rename isn't possible
+/// (e.g. across filesystems)
+fn move_file(source: &Path, target: &Path) -> Result<()> {
+    if fs::rename(source, target).is_err() {
+        fs::copy(source, target).context("Failed to copy file to its new location")?;
+        copy_timestamps(source, target)?;
+        fs::remove_file(source).context("Failed to remove original file after copying")?;
+    }
+    
+    Ok(())
+}
+
+/// Targets already given to files earlier in a Clean run
+type ClaimedTargets = Mutex<HashSet<PathBuf>>;
+
+/// The first of `name (2).ext`, `name (3).ext`, ... that doesn't exist yet
+/// and isn't claimed by another file in the run
+fn next_free_path(path: &Path, claimed: &HashSet<PathBuf>) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    
+    (2..)
+        .map(|counter| path.with_file_name(format!("{} ({}){}", stem, counter, extension)))
+        .find(|candidate| fs::symlink_metadata(candidate).is_err() && !claimed.contains(candidate))
+        .unwrap()
+}
+
+/// Give `target` the access, modification and (where supported) creation
+/// times of `source`, since `fs::copy` stamps the copy with the current time
+fn copy_timestamps(source: &Path, target: &Path) -> Result<()> {
+    set_timestamps(target, read_timestamps(source)?)
+}
+
+/// A file's access, modification and (where settable) creation times
+fn read_timestamps(source: &Path) -> Result<fs::FileTimes> {
+    let metadata = fs::metadata(source).context("Failed to read original timestamps")?;
+    
+    let mut times = fs::FileTimes::new();
+    if let Ok(accessed) = metadata.accessed() {
+        times = times.set_accessed(accessed);
+    }
+    if let Ok(modified) = metadata.modified() {
+        times = times.set_modified(modified);
+    }
+    
+    #[cfg(any(windows, target_os = "macos"))]
+    if let Ok(created) = metadata.created() {
+        #[cfg(windows)]
+        use std::os::windows::fs::FileTimesExt;
+        #[cfg(target_os = "macos")]
+        use std::os::macos::fs::FileTimesExt;
+        times = times.set_created(created);
+    }
+    
+    Ok(times)
+}
+
+/// Apply times from `read_timestamps` to a file
+fn set_timestamps(target: &Path, times: fs::FileTimes) -> Result<()> {
+    // Windows needs write access to change file times; elsewhere owning the
+    // file is enough, which also covers read-only copies
+    let file = if cfg!(windows) {
+        File::options().write(true).open(target)
+    } else {
+        File::open(target)
+    };
+    file.and_then(|file| file.set_times(times))
+        .context("Failed to copy timestamps to the new file")
+}
+
+/// Files found for processing. Symlinks resolving to a file that was already
+/// collected are recorded as aliases instead, so each real file is considered once.
+struct CollectedFiles {
+    paths: Vec<PathBuf>,
+    /// (alias, file it resolves to)
+    aliases: Vec<(PathBuf, PathBuf)>,
+}
+
+/// Collect the files of a supported type to consider, skipping anything
+/// matched by a `.maidignore` file in the directory or its subdirectories.
+/// `max_depth` only applies when recursive; otherwise the depth is 1.
+fn collect_files(
+    dir_path: &Path,
+    recursive: bool,
+    max_depth: Option<usize>,
+    include_ext: &[String],
+    include_other: bool,
+    include_extensionless: bool,
+    strict_extensions: bool,
+) -> CollectedFiles {
+    let candidates: Vec<PathBuf> = WalkBuilder::new(dir_path)
+        .standard_filters(false)
+        .add_custom_ignore_filename(".maidignore")
+        .max_depth(if recursive { max_depth } else { Some(1) })
+        .build()
+        .filter_map(|entry| entry.ok())
+        // `Path::is_file` follows symlinks, so links to files are included
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| is_included(path, include_ext, include_other, include_extensionless, strict_extensions))
+        .collect();
+    
+    collapse_aliases(candidates)
+}
+
+/// Collect the files of a supported type listed on stdin, one per line.
+/// Paths that don't exist (like deletions in `git diff --name-only`) are skipped.
+fn collect_stdin_files(
+    include_ext: &[String],
+    include_other: bool,
+    include_extensionless: bool,
+    strict_extensions: bool,
+) -> Result<CollectedFiles> {
+    let mut candidates = Vec::new();
+    for line in io::stdin().lock().lines() {
+        let line = line.context("Failed to read paths from stdin")?;
+        let path = PathBuf::from(line.trim());
+        if !line.trim().is_empty()
+            && path.is_file()
+            && is_included(&path, include_ext, include_other, include_extensionless, strict_extensions)
+        {
+            candidates.push(path);
+        }
+    }
+    
+    Ok(collapse_aliases(candidates))
+}
+
+/// Keep one path per file, recording the others (symlinks and the like) as aliases
+fn collapse_aliases(mut candidates: Vec<PathBuf>) -> CollectedFiles {
+    // Prefer real files over symlinks so a link never stands in for its target
+    candidates.sort_by_key(|path| path.is_symlink());
+    
+    let mut collected = CollectedFiles {
+        paths: Vec::new(),
+        aliases: Vec::new(),
+    };
+    let mut seen: HashMap<PathBuf, PathBuf> = HashMap::new();
+    
+    for path in candidates {
+        let canonical = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        match seen.get(&canonical) {
+            Some(original) => collected.aliases.push((path, original.clone())),
+            None => {
+                seen.insert(canonical, path.clone());
+                collected.paths.push(path);
+            }
+        }
+    }
+    
+    collected
+}
+
+/// Whether a file has one of the extensions maid handles, or one added with
+/// `--include-ext`. With `--include-other` every file is included, and with
+/// `--strict-extensions` so is one like `report.md.txt`.
+fn is_included(
+    path: &Path,
+    include_ext: &[String],
+    include_other: bool,
+    include_extensionless: bool,
+    strict_extensions: bool,
+) -> bool {
+    include_other
+        || FileType::from_path(path) != FileType::Other
+        || path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| include_ext.iter().any(|included| included.eq_ignore_ascii_case(ext)))
+        || (include_extensionless && FileType::detect(path) != FileType::Other)
+        || (strict_extensions && FileType::from_double_extension(path).is_some())
+}
+
+/// Whether a file's name matches one of the `--name` globs or `--name-regex`
+/// patterns. With neither given every file matches.
+fn matches_name(path: &Path, globs: &[GlobMatcher], regexes: &[Regex]) -> bool {
+    if globs.is_empty() && regexes.is_empty() {
+        return true;
+    }
+    
+    let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    globs.iter().any(|glob| glob.is_match(file_name)) || regexes.iter().any(|regex| regex.is_match(file_name))
+}
+
+/// Print the symlinks that were not processed because they alias another file
+fn report_aliases(aliases: &[(PathBuf, PathBuf)]) {
+    for (alias, original) in aliases {
+        println!(
+            "{} {} (same file as {})",
+            "Alias:".bright_black().bold(),
+            alias.display().to_string().yellow(),
+            original.display().to_string().green()
+        );
+    }
+}
+
+/// Whether a file is a README (`README.md`, `readme.md`, ...), whatever it says
+fn is_readme(path: &Path) -> bool {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| stem.eq_ignore_ascii_case("readme"))
+}
+
+/// Check if a file is hidden
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.starts_with('.'))
+        .unwrap_or(false)
+}
+
+/// The first `--exclude-content` pattern matching a file's content, if any
+fn excluded_by_content<'a>(patterns: &'a [Regex], content: &str) -> Option<&'a Regex> {
+    patterns.iter().find(|pattern| pattern.is_match(content))
+}
+
+/// Clean up files in a directory
+pub fn clean_directory(dir_path: &Path, options: &CleanOptions) -> Result<RunOutcome> {
+    let CleanOptions {
+        recursive,
+        dry_run,
+        verbose,
+        prune_empty,
+        ..
+    } = *options;
+    
+    // Count all files
+    let mut processed_files = 0;
+    let mut skipped_files = 0;
+    let mut md_files = 0;
+    let mut sh_files = 0;
+    let mut py_files = 0;
+    let mut yaml_files = 0;
+    let mut other_files = 0;
+    let mut failed_files = 0;
+    
+    // The confirmation prompt would read the rest of the piped paths as its answer
+    if options.stdin && !dry_run && !options.yes {
+        return Err(MaidError::Usage(
+            "--stdin needs --yes or --dry-run, since stdin can't also answer the confirmation prompt".to_string(),
+        )
+        .into());
+    }
+    
+    // Count files first for progress bar
+    let CollectedFiles {
+        paths: mut file_paths,
+        aliases,
+    } = if options.stdin {
+        collect_stdin_files(
+            &options.include_ext,
+            options.include_other,
+            options.include_extensionless,
+            options.strict_extensions,
+        )?
+    } else {
+        collect_files(
+            dir_path,
+            recursive,
+            options.max_depth,
+            &options.include_ext,
+            options.include_other,
+            options.include_extensionless,
+            options.strict_extensions,
+        )
+    };
+    file_paths.retain(|path| matches_name(path, &options.name, &options.name_regex));
+    
+    // Leave out what an interrupted run already did, sources and results alike
+    let journal = Journal::open(dir_path, options.resume.as_deref())?;
+    let found_files = file_paths.len();
+    file_paths.retain(|path| !journal.is_done(path));
+    let resumed_files = found_files - file_paths.len();
+    let journal = Mutex::new(journal);
+    
+    // Taken before processing, so files changed during this run are picked up next time
+    let run_started = chrono::Local::now();
+    let last_run = if options.since_last_run {
+        read_last_run(dir_path)?
+    } else {
+        None
+    };
+    if let Some(last_run) = last_run {
+        file_paths.retain(|path| changed_since(path, last_run));
+    }
+    let unchanged_files = found_files - resumed_files - file_paths.len();
+    
+    if let Some(git_ref) = &options.since_commit {
+        let changed = git_changed_files(dir_path, git_ref)?;
+        file_paths.retain(|path| fs::canonicalize(path).is_ok_and(|path| changed.contains(&path)));
+    }
+    let uncommitted_files = found_files - resumed_files - unchanged_files - file_paths.len();
+    
+    let date_window = options.newer_than.is_some() || options.older_than.is_some();
+    if date_window {
+        file_paths.retain(|path| in_date_window(path, options.newer_than, options.older_than, verbose));
+    }
+    let out_of_window_files = found_files - resumed_files - unchanged_files - uncommitted_files - file_paths.len();
+    
+    let total_files = file_paths.len();
+    let json_output = options.format == OutputFormat::Json;
+    
+    if !json_output {
+        println!(
+            "{} {} files in {}{}",
+            "Found".cyan().bold(),
+            found_files.to_string().yellow().bold(),
+            dir_path.display().to_string().green(),
+            if dry_run {
+                " (DRY RUN)".bright_red().bold().to_string()
+            } else {
+                "".to_string()
+            }
+        );
+    }
+    
+    if options.resume.is_some() && !json_output {
+        println!(
+            "{} {} files already done in the resumed run",
+            "Skipping".cyan().bold(),
+            resumed_files.to_string().yellow().bold()
+        );
+    }
+    
+    if let (Some(last_run), false) = (last_run, json_output) {
+        println!(
+            "{} {} files unchanged since the last run ({})",
+            "Skipping".cyan().bold(),
+            unchanged_files.to_string().yellow().bold(),
+            last_run.format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+    
+    if let (Some(git_ref), false) = (&options.since_commit, json_output) {
+        println!(
+            "{} {} files not changed since {}",
+            "Skipping".cyan().bold(),
+            uncommitted_files.to_string().yellow().bold(),
+            git_ref
+        );
+    }
+    
+    if date_window && !json_output {
+        println!(
+            "{} {} files outside the --newer-than/--older-than window",
+            "Skipping".cyan().bold(),
+            out_of_window_files.to_string().yellow().bold()
+        );
+    }
+    
+    if verbose {
+        report_aliases(&aliases);
+    }
+    
+    let mut config = Config::load(dir_path)?;
+    config.colors.extend(options.kind_color.iter().cloned());
+    if options.interactive_classify && quiet() {
+        return Err(MaidError::Usage("--interactive-classify asks questions, so it can't be used with --quiet".to_string()).into());
+    }
+    if options.interactive && quiet() {
+        return Err(MaidError::Usage("--interactive asks about each file, so it can't be used with --quiet".to_string()).into());
+    }
+    if options.interactive_classify {
+        interactive_classify(&file_paths, dir_path, &mut config)?;
+    }
+    if let Some(trace_path) = &options.classify_trace {
+        write_classify_trace(trace_path, &file_paths, options, &config)?;
+    }
+    
+    // Files are independent, so analyze and process them in parallel
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.jobs.unwrap_or(0))
+        .build()
+        .context("Failed to start worker threads")?;
+    
+    if options.interactive && !dry_run {
+        let Some(approved) = approve_each_file(&file_paths, dir_path, options, &config)? else {
+            println!("{} Operation cancelled", "Info:".blue().bold());
+            return Ok(RunOutcome::default());
+        };
+        skipped_files += file_paths.len() - approved.len();
+        file_paths = approved;
+    } else if !dry_run && !options.yes {
+        let planned = pool.install(|| count_planned_operations(&file_paths, dir_path, options, &config));
+        if !confirm_clean(planned, json_output)? {
+            return Ok(RunOutcome::default());
+        }
+    }
+    
+    // Files past --preview-limit are processed as if --verbose wasn't given
+    let to_process = file_paths.len();
+    let preview_limit = options.preview_limit.filter(|_| verbose).unwrap_or(to_process);
+    let unpreviewed = to_process.saturating_sub(preview_limit);
+    let unpreviewed_options = CleanOptions {
+        verbose: false,
+        ..options.clone()
+    };
+
+    // The bar covers every file unless verbose output already reports them,
+    // in which case it only counts those past --preview-limit. Verbose lines
+    // are printed above it either way.
+    let bar_counts_all = !verbose || options.progress;
+    let show_progress = (bar_counts_all || unpreviewed > 0) && !options.no_progress;
+    let progress_bar = if show_progress && !json_output && !quiet() {
+        let pb = ProgressBar::new(if bar_counts_all { to_process } else { unpreviewed } as u64);
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+        Some(pb)
+    } else {
+        None
+    };
+    let active_progress = progress_bar.as_ref().map(show_progress_bar);
+    
+    let cache = match &options.cache_file {
+        Some(cache_path) => Some(Mutex::new(ClassificationCache::load(cache_path)?)),
+        None => None,
+    };
+    let claimed = ClaimedTargets::default();
+    
+    let results: Vec<(PathBuf, u64, Result<Option<PlannedOperation>>)> = pool.install(|| {
+        file_paths
+            .into_par_iter()
+            .enumerate()
+            .map(|(index, file_path)| {
+                let previewed = index < preview_limit;
+                let file_options = if previewed { options } else { &unpreviewed_options };
+                // Sized first, since a moved file is gone afterwards
+                let size = fs::metadata(&file_path).map(|metadata| metadata.len()).unwrap_or(0);
+                let mut result = process_file(&file_path, dir_path, file_options, &config, cache.as_ref(), &claimed);
+                // Record each operation as soon as it's done, so an interruption loses nothing
+                if let (false, Ok(Some(operation))) = (dry_run, &result) {
+                    let recorded = journal.lock().unwrap_or_else(|e| e.into_inner()).record(operation);
+                    if let Err(e) = recorded {
+                        result = Err(e);
+                    }
+                }
+                if let (Some(pb), true) = (&progress_bar, bar_counts_all || !previewed) {
+                    pb.inc(1);
+                }
+                (file_path, size, result)
+            })
+            .collect()
+    });
+    
+    let mut plan = Vec::new();
+    let mut vacated_dirs = Vec::new();
+    let mut processed_bytes = 0;
+    
+    for (file_path, size, result) in results {
+        // Update file type counts
+        match FileType::detect(&file_path) {
+            FileType::Markdown => md_files += 1,
+            FileType::Shell => sh_files += 1,
+            FileType::Python => py_files += 1,
+            FileType::Yaml => yaml_files += 1,
+            FileType::Other => other_files += 1,
+        }
+        
+        match result {
+            Ok(operation) => {
+                if operation.as_ref().is_some_and(|op| matches!(op.action, PlanAction::Skip)) {
+                    skipped_files += 1;
+                } else {
+                    processed_files += 1;
+                    processed_bytes += size;
+                }
+                if let Some(op) = &operation {
+                    log_operation(op, dry_run);
+                }
+                plan.extend(operation);
+                
+                // Remember directories we took files out of
+                if !dry_run && !file_path.exists() {
+                    if let Some(parent) = file_path.parent() {
+                        vacated_dirs.push(parent.to_path_buf());
+                    }
+                }
+            }
+            Err(e) => {
+                skipped_files += 1;
+                failed_files += 1;
+                log::error!("{}: {:#}", file_path.display(), e);
+                if quiet() {
+                    eprintln!("{} {}: {:#}", "Error:".red().bold(), file_path.display(), e);
+                } else if verbose {
+                    println!(
+                        "{} {} - Error: {:#}",
+                        "Error:".red().bold(),
+                        file_path.display().to_string().yellow(),
+                        e
+                    );
+                }
+            }
+        }
+    }
+    
+    drop(active_progress);
+    if let Some(pb) = progress_bar {
+        pb.finish_with_message("Done!");
+        println!(); // Add a blank line after the progress bar
+    }
+    if verbose && unpreviewed > 0 && !json_output {
+        println!("... and {} more files", unpreviewed);
+    }
+    
+    let cache = cache.map(|cache| cache.into_inner().unwrap_or_else(|e| e.into_inner()));
+    if let (Some(cache), Some(cache_path)) = (&cache, &options.cache_file) {
+        cache.save(cache_path)?;
+    }
+    
+    if options.since_last_run && !dry_run {
+        write_last_run(dir_path, run_started)?;
+    }
+    
+    let pruned_dirs = if prune_empty && !dry_run {
+        Some(prune_empty_dirs(&vacated_dirs, dir_path, verbose)?)
+    } else {
+        None
+    };
+    
+    let plan: Vec<PlannedOperation> = plan
+        .into_iter()
+        .map(|op| op.with_report_paths(dir_path, options.report_paths))
+        .collect();
+    
+    // Persist the plan for review
+    if let Some(plan_path) = &options.dry_run_json_to {
+        let json = serde_json::to_string_pretty(&plan).context("Failed to serialize plan")?;
+        fs::write(plan_path, json)
+            .with_context(|| format!("Failed to write plan to {}", plan_path.display()))?;
+        if !json_output {
+            println!(
+                "{} {}",
+                "Plan written to:".cyan().bold(),
+                plan_path.display().to_string().green()
+            );
+        }
+    }
+    
+    if let Some(index_path) = &options.keep_originals_index {
+        write_originals_index(index_path, &plan)?;
+        if !json_output {
+            println!(
+                "{} {}",
+                "Originals index written to:".cyan().bold(),
+                index_path.display().to_string().green()
+            );
+        }
+    }
+    
+    let kind_transitions = match &options.report_kind_transitions {
+        Some(previous) => Some(kind_transitions(previous, &plan)?),
+        None => None,
+    };
+    
+    let conflicts = plan.iter().filter(|op| op.conflict.is_some()).count();
+    let summary = CleanSummary {
+        total_files,
+        processed: processed_files,
+        skipped: skipped_files,
+        bytes_processed: processed_bytes,
+        markdown: md_files,
+        shell: sh_files,
+        python: py_files,
+        yaml: yaml_files,
+        other: other_files,
+        conflicts,
+        on_conflict: options.on_conflict,
+        files: plan,
+        pruned_dirs,
+        kind_transitions,
+    };
+    
+    if let Some(report_path) = &options.report {
+        let template = match &options.output_report_template {
+            Some(template_path) => fs::read_to_string(template_path).with_context(|| {
+                format!("Failed to read report template {}", template_path.display())
+            })?,
+            None => DEFAULT_REPORT_TEMPLATE.to_string(),
+        };
+        let report = render_report(&template, dir_path, &summary, dry_run)?;
+        fs::write(report_path, report)
+            .with_context(|| format!("Failed to write report to {}", report_path.display()))?;
+        if !json_output {
+            println!(
+                "{} {}",
+                "Report written to:".cyan().bold(),
+                report_path.display().to_string().green()
+            );
+        }
+    }
+    
+    // Quiet runs have no summary to check, so failures show in the exit code
+    if quiet() && failed_files > 0 {
+        anyhow::bail!("{} of {} files could not be processed", failed_files, total_files);
+    }
+    
+    let outcome = RunOutcome {
+        processed: processed_files,
+        skipped: skipped_files,
+        failed: failed_files,
+    };
+    
+    if json_output {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&summary).context("Failed to serialize summary")?
+        );
+        return Ok(outcome);
+    }
+    
+    // Print a summary
+    println!("\n{}", "📊 Summary".cyan().bold());
+    println!("  {} {}", "Total files found:".bright_white(), total_files);
+    println!("  {} {}", "Files processed:".green(), processed_files);
+    println!("  {} {}", "Files skipped:".yellow(), skipped_files);
+    println!("  {} {}", "Bytes processed:".green(), format_size(processed_bytes));
+    println!("  {} {}", "Markdown files:".magenta(), md_files);
+    println!("  {} {}", "Shell scripts:".magenta(), sh_files);
+    println!("  {} {}", "Python scripts:".magenta(), py_files);
+    println!("  {} {}", "YAML files:".magenta(), yaml_files);
+    if other_files > 0 {
+        println!("  {} {}", "Other files:".magenta(), other_files);
+    }
+    if conflicts > 0 {
+        println!(
+            "  {} {} ({:?})",
+            "Target conflicts:".yellow(),
+            conflicts,
+            options.on_conflict
+        );
+    }
+    if let Some(pruned_dirs) = pruned_dirs {
+        println!("  {} {}", "Empty directories pruned:".bright_black(), pruned_dirs);
+    }
+    if let Some(cache) = &cache {
+        println!("  {} {}", "Cache hits:".bright_black(), cache.hits);
+    }
+    
+    if let Some(transitions) = &summary.kind_transitions {
+        println!("\n{}", "🔀 Kind changes".cyan().bold());
+        if transitions.is_empty() {
+            println!("  No files changed kind since the previous plan");
+        }
+        for transition in transitions {
+            println!(
+                "  {}: {} -> {}",
+                transition.source.display().to_string().yellow(),
+                format!("{:?}", transition.from).red(),
+                format!("{:?}", transition.to).green()
+            );
+        }
+    }
+    
+    Ok(outcome)
+}
+
+/// Record a Clean operation in the log file
+fn log_operation(op: &PlannedOperation, dry_run: bool) {
+    let action = format!("{:?}", op.action).to_lowercase();
+    let planned = if dry_run { " (planned)" } else { "" };
+    if let PlanAction::Skip = op.action {
+        log::info!("skip {} ({:?}){}", op.source.display(), op.doc_kind, planned);
+    } else {
+        log::info!(
+            "{} {} -> {} ({:?}){}",
+            action,
+            op.source.display(),
+            op.target.display(),
+            op.doc_kind,
+            planned
+        );
+    }
+}
+
+/// Clean one file without scanning its siblings, routing it relative to
+/// `base_dir` (the file's own directory by default). Shared by `--path <file>`
+/// and the watcher, which only hands over files that changed.
+pub fn process_single_file(
+    file_path: &Path,
+    base_dir: Option<&Path>,
+    options: &CleanOptions,
+) -> Result<Option<PlannedOperation>> {
+    let base_dir = match base_dir {
+        Some(base_dir) => base_dir.to_path_buf(),
+        None => file_path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_path_buf(),
+    };
+    
+    let mut config = Config::load(&base_dir)?;
+    config.colors.extend(options.kind_color.iter().cloned());
+    if let Some(trace_path) = &options.classify_trace {
+        write_classify_trace(trace_path, &[file_path.to_path_buf()], options, &config)?;
+    }
+    let cache = match &options.cache_file {
+        Some(cache_path) => Some(Mutex::new(ClassificationCache::load(cache_path)?)),
+        None => None,
+    };
+    
+    let mut journal = Journal::open(&base_dir, options.resume.as_deref())?;
+    if journal.is_done(file_path) {
+        println!(
+            "{} {} is already done in {}",
+            "Skipping:".yellow().bold(),
+            file_path.display(),
+            journal.path.display()
+        );
+        return Ok(None);
+    }
+    
+    if !options.dry_run && !options.yes {
+        let planned = count_planned_operations(&[file_path.to_path_buf()], &base_dir, options, &config);
+        if !confirm_clean(planned, options.format == OutputFormat::Json)? {
+            return Ok(None);
+        }
+    }
+    
+    let claimed = ClaimedTargets::default();
+    let operation = process_file(file_path, &base_dir, options, &config, cache.as_ref(), &claimed)?;
+    
+    if let (Some(cache), Some(cache_path)) = (cache, &options.cache_file) {
+        cache.into_inner().unwrap_or_else(|e| e.into_inner()).save(cache_path)?;
+    }
+    
+    if !options.dry_run {
+        if let Some(operation) = &operation {
+            journal.record(operation)?;
+        }
+    }
+    
+    if options.format == OutputFormat::Json {
+        let operation = operation.map(|op| op.with_report_paths(&base_dir, options.report_paths));
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&operation).context("Failed to serialize operation")?
+        );
+        return Ok(operation);
+    }
+    
+    if let Some(operation) = &operation {
+        log_operation(operation, options.dry_run);
+    }
+    match &operation {
+        Some(operation) => println!(
+            "\n{} {} -> {} ({:?})",
+            if options.dry_run { "Planned:" } else { "Done:" }.cyan().bold(),
+            operation.source.display().to_string().yellow(),
+            operation.target.display().to_string().green(),
+            operation.action
+        ),
+        None => println!(
+            "{} {} - not a file maid handles",
+            "Skipping:".yellow().bold(),
+            file_path.display().to_string().yellow()
+        ),
+    }
+    
+    Ok(operation)
+}
+
+/// Built-in layout for `--report`
+const DEFAULT_REPORT_TEMPLATE: &str = include_str!("../assets/report_template.md");
+
+/// Fill a report template's `{{ placeholder }}`s with data from a Clean run
+fn render_report(
+    template: &str,
+    dir_path: &Path,
+    summary: &CleanSummary,
+    dry_run: bool,
+) -> Result<String> {
+    let files = if summary.files.is_empty() {
+        "_No files were changed._".to_string()
+    } else {
+        summary
+            .files
+            .iter()
+            .map(|op| {
+                format!(
+                    "- `{}` -> `{}` ({:?}, {:?})",
+                    op.source.display(),
+                    op.target.display(),
+                    op.action,
+                    op.doc_kind
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    
+    let values: HashMap<&str, String> = HashMap::from([
+        ("date", chrono::Local::now().format("%Y-%m-%d %H:%M").to_string()),
+        ("directory", dir_path.display().to_string()),
+        ("mode", if dry_run { "dry run" } else { "applied" }.to_string()),
+        ("total_files", summary.total_files.to_string()),
+        ("processed", summary.processed.to_string()),
+        ("skipped", summary.skipped.to_string()),
+        ("bytes_processed", format_size(summary.bytes_processed)),
+        ("markdown", summary.markdown.to_string()),
+        ("shell", summary.shell.to_string()),
+        ("python", summary.python.to_string()),
+        ("yaml", summary.yaml.to_string()),
+        ("other", summary.other.to_string()),
+        ("files", files),
+    ]);
+    
+    let placeholder = Regex::new(r"\{\{\s*(\w+)\s*\}\}").unwrap();
+    let mut rendered = String::with_capacity(template.len());
+    let mut last = 0;
+    
+    for captures in placeholder.captures_iter(template) {
+        let whole = captures.get(0).unwrap();
+        let name = &captures[1];
+        let value = values
+            .get(name)
+            .with_context(|| format!("Unknown placeholder in report template: {{{{ {} }}}}", name))?;
+        
+        rendered.push_str(&template[last..whole.start()]);
+        rendered.push_str(value);
+        last = whole.end();
+    }
+    rendered.push_str(&template[last..]);
+    
+    Ok(rendered)
+}
+
+/// Kinds a user can assign when correcting a classification
+const ASSIGNABLE_KINDS: [DocumentKind; 11] = [
+    DocumentKind::Rubric,
+    DocumentKind::Report,
+    DocumentKind::Guide,
+    DocumentKind::Summary,
+    DocumentKind::Script,
+    DocumentKind::Changelog,
+    DocumentKind::License,
+    DocumentKind::Data,
+    DocumentKind::TaskList,
+    DocumentKind::Faq,
+    DocumentKind::ApiReference,
+];
+
+/// An answer to `--interactive`'s prompt for one file
+enum Approval {
+    Yes,
+    No,
+    All,
+    Quit,
+}
+
+/// Show each file's proposed rename or move, as `--verbose` prints it, and ask
+/// whether to make it (--interactive). Files with nothing to do aren't asked
+/// about. Returns the files to process, or None if the user quit.
+fn approve_each_file(
+    file_paths: &[PathBuf],
+    base_dir: &Path,
+    options: &CleanOptions,
+    config: &Config,
+) -> Result<Option<Vec<PathBuf>>> {
+    let proposing = CleanOptions {
+        dry_run: true,
+        verbose: true,
+        ..options.clone()
+    };
+    
+    // Ask in path order, not the order the walk happened to find files in
+    let mut file_paths = file_paths.to_vec();
+    file_paths.sort();
+    
+    let claimed = ClaimedTargets::default();
+    let mut approved = Vec::new();
+    let mut approve_rest = false;
+    for file_path in &file_paths {
+        if approve_rest {
+            approved.push(file_path.clone());
+            continue;
+        }
+        
+        // Files that fail are left to the real run, which reports them
+        let planned = process_file(file_path, base_dir, &proposing, config, None, &claimed).unwrap_or(None);
+        let Some(planned) = planned.filter(|op| !matches!(op.action, PlanAction::Skip)) else {
+            approved.push(file_path.clone());
+            continue;
+        };
+        
+        let approval = loop {
+            print!("Apply? [y]es / [n]o / [a]ll / [q]uit: ");
+            io::stdout().flush()?;
+            
+            let mut input = String::new();
+            // A closed stdin can't approve anything more
+            if io::stdin().read_line(&mut input)? == 0 {
+                println!();
+                break Approval::Quit;
+            }
+            match input.trim().to_lowercase().as_str() {
+                "y" | "yes" => break Approval::Yes,
+                "n" | "no" | "" => break Approval::No,
+                "a" | "all" => break Approval::All,
+                "q" | "quit" => break Approval::Quit,
+                _ => continue,
+            }
+        };
+        
+        match approval {
+            Approval::Yes => approved.push(file_path.clone()),
+            // A declined file stays put, so its proposed name is free again
+            Approval::No => {
+                claimed.lock().unwrap_or_else(|e| e.into_inner()).remove(&planned.target);
+            }
+            Approval::All => {
+                approved.push(file_path.clone());
+                approve_rest = true;
+            }
+            Approval::Quit => return Ok(None),
+        }
+    }
+    
+    Ok(Some(approved))
+}
+
+/// Ask the user to classify files maid couldn't, remembering each answer as a
+/// rule in `maid.toml` so future runs get it right
+fn interactive_classify(file_paths: &[PathBuf], dir_path: &Path, config: &mut Config) -> Result<()> {
+    for file_path in file_paths {
+        let Ok(mut info) = FileInfo::new(file_path.clone()) else {
+            continue;
+        };
+        info.apply_rules(config);
+        
+        if !matches!(info.doc_kind, DocumentKind::Unknown) {
+            continue;
+        }
+        
+        println!(
+            "\n{} {} (guessed {:?})",
+            "Classify:".cyan().bold(),
+            file_path.display().to_string().yellow(),
+            info.doc_kind
+        );
+        for (index, kind) in ASSIGNABLE_KINDS.iter().enumerate() {
+            println!("  {}) {:?}", index + 1, kind);
+        }
+        print!("Choose a kind (Enter to leave as is): ");
+        io::stdout().flush()?;
+        
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let Some(kind) = input
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .and_then(|choice| ASSIGNABLE_KINDS.get(choice.wrapping_sub(1)))
+        else {
+            continue;
+        };
+        
+        let default_pattern = file_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        print!("Remember for files matching [{}]: ", default_pattern);
+        io::stdout().flush()?;
+        
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let pattern = match input.trim() {
+            "" => default_pattern,
+            pattern => pattern.to_string(),
+        };
+        
+        if let Err(e) = Glob::new(&pattern) {
+            println!("{} Invalid pattern: {}", "Warning:".yellow().bold(), e);
+            continue;
+        }
+        
+        config.append_rule(dir_path, ClassificationRule { pattern, kind: *kind })?;
+        println!(
+            "{} {}",
+            "Saved rule to".green(),
+            Config::path(dir_path).display().to_string().green()
+        );
+    }
+    
+    Ok(())
+}
+
+/// File holding when `--since-last-run` last completed a Clean run
+fn last_run_path(dir_path: &Path) -> PathBuf {
+    dir_path.join(".maid/last-run")
+}
+
+/// When the last `--since-last-run` Clean started, if it has run before
+fn read_last_run(dir_path: &Path) -> Result<Option<chrono::DateTime<chrono::Local>>> {
+    let path = last_run_path(dir_path);
+    if !path.exists() {
+        return Ok(None);
+    }
+    
+    let timestamp = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let last_run = chrono::DateTime::parse_from_rfc3339(timestamp.trim())
+        .with_context(|| format!("Invalid timestamp in {}", path.display()))?;
+    
+    Ok(Some(last_run.into()))
+}
+
+/// Record the start of a completed `--since-last-run` Clean
+fn write_last_run(dir_path: &Path, started: chrono::DateTime<chrono::Local>) -> Result<()> {
+    let path = last_run_path(dir_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, format!("{}\n", started.to_rfc3339()))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// When a file was created, falling back to when it was last modified where
+/// creation times aren't available
+fn file_date(path: &Path) -> Option<(chrono::DateTime<chrono::Local>, DateSource)> {
+    let meta = fs::metadata(path).ok()?;
+    meta.created()
+        .map(|time| (time.into(), DateSource::Created))
+        .or_else(|_| meta.modified().map(|time| (time.into(), DateSource::Modified)))
+        .ok()
+}
+
+/// Whether a file is dated within `--newer-than`/`--older-than`, taking its
+/// date the way Keep ranks it (version header, frontmatter and filename before
+/// filesystem times). Files without a date are left out, since they can't be
+/// placed in the window.
+fn in_date_window(
+    path: &Path,
+    newer_than: Option<chrono::DateTime<chrono::Local>>,
+    older_than: Option<chrono::DateTime<chrono::Local>>,
+    verbose: bool,
+) -> bool {
+    // Files that can't be read are still dated by the filesystem
+    let date = match FileInfo::new(path.to_path_buf()) {
+        Ok(info) => info.created_date,
+        Err(_) => file_date(path).map(|(date, _)| date),
+    };
+    let Some(date) = date else {
+        if verbose {
+            println!(
+                "{} {} - no date to compare with --newer-than/--older-than",
+                "Skipping:".yellow().bold(),
+                path.display().to_string().yellow()
+            );
+        }
+        return false;
+    };
+    
+    newer_than.is_none_or(|bound| date >= bound) && older_than.is_none_or(|bound| date < bound)
+}
+
+/// Files changed in commits since `git_ref`, as `git diff --name-only
+/// <ref>...HEAD` lists them, with their canonical paths (--since-commit)
+fn git_changed_files(dir_path: &Path, git_ref: &str) -> Result<HashSet<PathBuf>> {
+    use std::process::Command;
+    
+    // git would take it for an option
+    if git_ref.starts_with('-') {
+        return Err(MaidError::Usage(format!("invalid --since-commit ref '{}'", git_ref)).into());
+    }
+    
+    let git = |args: &[&str]| -> Result<String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir_path)
+            .args(args)
+            .output()
+            .context("Failed to run git (needed for --since-commit)")?;
+        if !output.status.success() {
+            anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    
+    let toplevel = git(&["rev-parse", "--show-toplevel"]).map_err(|_| {
+        anyhow::anyhow!("{} is not in a git repository, so --since-commit can't be used", dir_path.display())
+    })?;
+    let changed = git(&["diff", "--name-only", &format!("{}...HEAD", git_ref), "--"])
+        .with_context(|| format!("Failed to list files changed since {}", git_ref))?;
+    
+    // Paths are relative to the top of the repository; deleted files are gone
+    let toplevel = PathBuf::from(toplevel.trim_end());
+    Ok(changed
+        .lines()
+        .filter_map(|path| fs::canonicalize(toplevel.join(path)).ok())
+        .collect())
+}
+
+/// Whether a file was written after `since` (creating a file sets its
+/// modification time too). Files whose time can't be read count as changed.
+fn changed_since(path: &Path, since: chrono::DateTime<chrono::Local>) -> bool {
+    match fs::metadata(path).and_then(|metadata| metadata.modified()) {
+        Ok(modified) => chrono::DateTime::<chrono::Local>::from(modified) > since,
+        Err(_) => true,
+    }
+}
+
+/// Directory holding the journals of previous Clean runs
+fn history_dir(dir_path: &Path) -> PathBuf {
+    dir_path.join(".maid/history")
+}
+
+/// Read a journal: one JSON entry per line, or a JSON array as older runs wrote
+fn read_journal(path: &Path) -> Result<Vec<JournalEntry>> {
+    let json = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read journal {}", path.display()))?;
+    
+    if json.trim_start().starts_with('[') {
+        return serde_json::from_str(&json).context("Failed to parse journal");
+    }
+    
+    json.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).context("Failed to parse journal"))
+        .collect()
+}
+
+/// The journal of a Clean run, written as each operation completes so an
+/// interrupted run can be undone or resumed
+struct Journal {
+    path: PathBuf,
+    /// Opened on the first entry, so runs that change nothing leave no journal
+    file: Option<File>,
+    /// Absolute sources and targets of the operations already recorded
+    done: HashSet<PathBuf>,
+}
+
+impl Journal {
+    /// A journal for a new run at `.maid/history/<timestamp>.jsonl`, or the
+    /// given journal of an earlier run to carry on with
+    fn open(dir_path: &Path, resume: Option<&Path>) -> Result<Self> {
+        let Some(path) = resume else {
+            let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S_%3f").to_string();
+            return Ok(Journal {
+                path: history_dir(dir_path).join(format!("{}.jsonl", timestamp)),
+                file: None,
+                done: HashSet::new(),
+            });
+        };
+        
+        let done = read_journal(path)?
+            .into_iter()
+            .flat_map(|entry| [entry.source, entry.target])
+            .collect();
+        
+        Ok(Journal {
+            path: path.to_path_buf(),
+            file: None,
+            done,
+        })
+    }
+    
+    /// Whether an earlier pass of this run already handled a file
+    fn is_done(&self, path: &Path) -> bool {
+        self.done
+            .contains(&std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()))
+    }
+    
+    /// Append a completed operation
+    fn record(&mut self, op: &PlannedOperation) -> Result<()> {
+        if matches!(op.action, PlanAction::Skip) {
+            return Ok(());
+        }
+        
+        let entry = JournalEntry {
+            source: std::path::absolute(&op.source).unwrap_or_else(|_| op.source.clone()),
+            target: std::path::absolute(&op.target).unwrap_or_else(|_| op.target.clone()),
+            action: op.action,
+            target_hash: fs::read(&op.target).ok().map(|data| sha256_hex(&data)),
+        };
+        
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => {
+                if let Some(parent) = self.path.parent() {
+                    fs::create_dir_all(parent).context("Failed to create history directory")?;
+                }
+                let file = fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)
+                    .with_context(|| format!("Failed to open journal {}", self.path.display()))?;
+                self.file.insert(file)
+            }
+        };
+        
+        let line = serde_json::to_string(&entry).context("Failed to serialize journal")?;
+        writeln!(file, "{}", line)
+            .with_context(|| format!("Failed to write journal to {}", self.path.display()))?;
+        
+        self.done.insert(entry.source);
+        self.done.insert(entry.target);
+        Ok(())
+    }
+}
+
+/// How many files a Clean run would copy, move or link, found with a quiet dry run
+fn count_planned_operations(
+    file_paths: &[PathBuf],
+    base_dir: &Path,
+    options: &CleanOptions,
+    config: &Config,
+) -> usize {
+    let planning = CleanOptions {
+        dry_run: true,
+        verbose: false,
+        format: OutputFormat::Json,
+        ..options.clone()
+    };
+    let claimed = ClaimedTargets::default();
+    
+    file_paths
+        .par_iter()
+        .filter_map(|file_path| process_file(file_path, base_dir, &planning, config, None, &claimed).ok().flatten())
+        .filter(|op| !matches!(op.action, PlanAction::Skip))
+        .count()
+}
+
+/// Read a y/N answer from stdin. An unreadable stdin (closed, or not text)
+/// counts as "no", with a warning (on stderr with JSON output).
+fn read_confirmation(json_output: bool) -> bool {
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(_) => matches!(input.trim().to_lowercase().as_str(), "y" | "yes"),
+        Err(e) => {
+            let message = format!(
+                "\n{} Couldn't read an answer from stdin ({}), so nothing will be changed",
+                "Warning:".yellow().bold(),
+                e
+            );
+            if json_output {
+                eprintln!("{}", message);
+            } else {
+                println!("{}", message);
+            }
+            log::warn!("couldn't read confirmation from stdin: {}", e);
+            false
+        }
+    }
+}
+
+/// Ask before Clean changes anything, like Keep does. Nothing to do needs no
+/// answer. With JSON output the prompt goes to stderr to keep stdout parseable.
+fn confirm_clean(planned: usize, json_output: bool) -> Result<bool> {
+    if planned == 0 {
+        return Ok(true);
+    }
+    
+    let prompt = format!(
+        "\n{} This will carry out {} planned operations. Continue? (y/N) ",
+        "⚠️".yellow().bold(),
+        planned.to_string().yellow().bold()
+    );
+    if json_output || quiet() {
+        eprint!("{}", prompt);
+        io::stderr().flush()?;
+    } else {
+        print!("{}", prompt);
+        io::stdout().flush()?;
+    }
+    
+    if !read_confirmation(json_output) {
+        let message = format!("{} Operation cancelled", "Info:".blue().bold());
+        if json_output {
+            eprintln!("{}", message);
+        } else {
+            println!("{}", message);
+        }
+        return Ok(false);
+    }
+    
+    Ok(true)
+}
+
+/// Remove directories that were emptied by this run, walking up towards (but
+/// never removing) the root directory being cleaned. Returns how many were removed.
+fn prune_empty_dirs(dirs: &[PathBuf], root: &Path, verbose: bool) -> Result<usize> {
+    let root = fs::canonicalize(root)?;
+    let mut pruned = 0;
+    
+    for dir in dirs {
+        let mut current = fs::canonicalize(dir).ok();
+        
+        while let Some(candidate) = current {
+            if candidate == root || !candidate.starts_with(&root) {
+                break;
+            }
+            
+            let is_empty = fs::read_dir(&candidate)
+                .map(|mut entries| entries.next().is_none())
+                .unwrap_or(false);
+            if !is_empty {
+                break;
+            }
+            
+            fs::remove_dir(&candidate)?;
+            pruned += 1;
+            
+            if verbose {
+                println!(
+                    "{} {}",
+                    "Pruned:".yellow().bold(),
+                    candidate.display().to_string().bright_black()
+                );
+            }
+            
+            current = candidate.parent().map(Path::to_path_buf);
+        }
+    }
+    
+    Ok(pruned)
+}
+
+/// Options controlling a Keep run
+pub struct KeepOptions {
+    pub recursive: bool,
+    pub max_depth: Option<usize>,
+    pub verbose: bool,
+    pub dedupe_ignore_trailing_report_sections: bool,
+    pub classify_report_vs_summary_by_structure: bool,
+    pub recency_weight: Option<f64>,
+    pub size_weight: Option<f64>,
+    pub quality_weight: Option<f64>,
+    pub trash_dir: Option<PathBuf>,
+    pub auto_purge: bool,
+    pub trash_to_system: bool,
+    pub keep_recent: Option<chrono::Duration>,
+    pub keep_last: usize,
+    pub keep_all: Vec<DocumentKind>,
+    pub thin_by: ThinGranularity,
+    pub dedup_only: bool,
+    pub exclude_content: Vec<Regex>,
+    pub name: Vec<GlobMatcher>,
+    pub name_regex: Vec<Regex>,
+    pub dedupe_cross_extension: Option<CrossExtensionKeep>,
+    pub dedupe_keep: DedupeKeep,
+    pub dedupe_keep_annotated: Option<String>,
+    pub max_content_bytes_for_keywords: usize,
+    pub keyword_weighting: KeywordWeighting,
+    pub max_keywords_per_file: usize,
+    pub rubric_format: RubricFormat,
+    pub stopwords_file: Option<PathBuf>,
+}
+
+/// Relative weight of recency, size and content quality when ranking files
+/// within a keep-group
+#[derive(Debug, Clone, Copy)]
+struct RankWeights {
+    recency: f64,
+    size: f64,
+    quality: f64,
+}
+
+impl RankWeights {
+    const RECENCY_ONLY: Self = RankWeights { recency: 1.0, size: 0.0, quality: 0.0 };
+    const QUALITY_ONLY: Self = RankWeights { recency: 0.0, size: 0.0, quality: 1.0 };
+    const RECENCY_THEN_QUALITY: Self = RankWeights { recency: 1.0, size: 0.0, quality: 0.5 };
+}
+
+impl KeepOptions {
+    /// Most times one file's use of a word counts towards the key terms, if capped
+    fn keyword_cap(&self) -> Option<usize> {
+        match self.keyword_weighting {
+            KeywordWeighting::Count => None,
+            KeywordWeighting::Presence => Some(1),
+            KeywordWeighting::Capped => Some(self.max_keywords_per_file),
+        }
+    }
+    
+    /// Weights for a group, with any weight not given on the command line
+    /// falling back to the group's default
+    fn weights(&self, default: RankWeights) -> RankWeights {
+        RankWeights {
+            recency: self.recency_weight.unwrap_or(default.recency),
+            size: self.size_weight.unwrap_or(default.size),
+            quality: self.quality_weight.unwrap_or(default.quality),
+        }
+    }
+}
+
+/// How much useful structure a document has: word count on a log scale, plus
+/// credit for headings, tables, code blocks and links, scaled down for text
+/// that keeps repeating the same words. Each structural bonus is capped so
+/// piling on more of one thing stops helping.
+fn quality_score(info: &FileInfo) -> f64 {
+    let content = info.body();
+    let words: Vec<String> = content
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect();
+    if words.is_empty() {
+        return 0.0;
+    }
+    
+    let mut in_fence = false;
+    let mut headings = 0;
+    let mut table_rows = 0;
+    let mut code_blocks = 0;
+    for line in content.lines() {
+        let line = line.trim_start();
+        if line.starts_with("```") {
+            if !in_fence {
+                code_blocks += 1;
+            }
+            in_fence = !in_fence;
+        } else if !in_fence && line.starts_with('#') {
+            headings += 1;
+        } else if !in_fence && line.starts_with('|') && line.contains("---") {
+            // Count tables by their header separator row
+            table_rows += 1;
+        }
+    }
+    let links = content.matches("](").count();
+    
+    let unique: HashSet<&String> = words.iter().collect();
+    let unique_ratio = unique.len() as f64 / words.len() as f64;
+    let link_density = links as f64 * 100.0 / words.len() as f64;
+    
+    let structure = 0.5 * headings.min(10) as f64
+        + table_rows.min(3) as f64
+        + 0.75 * code_blocks.min(4) as f64
+        + 0.5 * link_density.min(5.0);
+    ((words.len() as f64).ln_1p() + structure) * (0.5 + 0.5 * unique_ratio)
+}
+
+/// Sort files best first by a weighted score of recency, word count and
+/// content quality, each normalized to 0..=1 within the group. Files without
+/// a date score 0 for recency. Equal scores (such as between undated files)
+/// go by path, so the same files always rank the same way.
+fn rank_files(files: &mut Vec<(PathBuf, FileInfo)>, weights: RankWeights) {
+    let word_counts: Vec<usize> = files
+        .iter()
+        .map(|(_, info)| info.word_count())
+        .collect();
+    let qualities: Vec<f64> = files.iter().map(|(_, info)| quality_score(info)).collect();
+    let timestamps: Vec<Option<i64>> = files
+        .iter()
+        .map(|(_, info)| info.created_date.map(|date| date.timestamp_millis()))
+        .collect();
+    
+    let max_words = word_counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+    let oldest = timestamps.iter().flatten().copied().min().unwrap_or(0);
+    let newest = timestamps.iter().flatten().copied().max().unwrap_or(0);
+    let span = (newest - oldest).max(1) as f64;
+    let max_quality = qualities.iter().copied().fold(0.0, f64::max).max(f64::EPSILON);
+    
+    let scores: Vec<f64> = word_counts
+        .iter()
+        .zip(&timestamps)
+        .zip(&qualities)
+        .map(|((words, timestamp), quality)| {
+            let size = *words as f64 / max_words;
+            let recency = match timestamp {
+                Some(_) if newest == oldest => 1.0,
+                Some(timestamp) => (timestamp - oldest) as f64 / span,
+                None => 0.0,
+            };
+            weights.recency * recency + weights.size * size + weights.quality * quality / max_quality
+        })
+        .collect();
+    
+    let mut scored: Vec<(f64, (PathBuf, FileInfo))> = scores.into_iter().zip(files.drain(..)).collect();
+    scored.sort_by(|(a, (a_path, _)), (b, (b_path, _))| b.total_cmp(a).then_with(|| a_path.cmp(b_path)));
+    files.extend(scored.into_iter().map(|(_, file)| file));
+}
+
+/// Hash file content after normalizing line endings and trailing whitespace,
+/// so copies that only differ in those still match. An annotation marker is
+/// left out too, with its line when it's on one of its own.
+fn normalized_content_hash(content: &str, annotation: Option<&str>) -> u64 {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+    
+    let unannotated;
+    let content = match annotation {
+        Some(marker) => {
+            unannotated = content
+                .lines()
+                .filter(|line| line.trim() != marker)
+                .map(|line| line.replace(marker, ""))
+                .collect::<Vec<_>>()
+                .join("\n");
+            unannotated.trim_start()
+        }
+        None => content,
+    };
+    
+    let mut hasher = DefaultHasher::new();
+    for line in content.trim_end().lines() {
+        line.trim_end().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// How descriptive a filename is: the number of words in its stem, then its length
+fn name_descriptiveness(path: &Path) -> (usize, usize) {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let words = stem
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .count();
+    
+    (words, stem.len())
+}
+
+/// Where a file sits: its number of path components, then the path's length.
+/// Shallower, shorter paths tend to be the canonical copy.
+fn path_depth(path: &Path) -> (usize, usize) {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    (path.components().count(), path.as_os_str().len())
+}
+
+/// Which of several identical files survives deduplication
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum DedupeKeep {
+    /// The most descriptive name, then the shortest path
+    DescriptiveName,
+    /// The fewest path components / shortest path, then the most descriptive name
+    ShortestPath,
+}
+
+/// How a file's occurrences of a word add up in the rubric's key terms
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum KeywordWeighting {
+    /// Every occurrence counts
+    Count,
+    /// Each file counts once per word, so terms reflect breadth across files
+    Presence,
+    /// Each file counts at most --max-keywords-per-file times per word
+    Capped,
+}
+
+/// How the generated rubric is written
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum RubricFormat {
+    /// COMPREHENSIVE_PROJECT_RUBRIC.md
+    Md,
+    /// COMPREHENSIVE_PROJECT_RUBRIC.html, e.g. for embedding in a wiki
+    Html,
+}
+
+/// Which copy survives when a markdown file just wraps a shell script
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum CrossExtensionKeep {
+    /// Keep the .sh file and discard the markdown wrapper
+    Sh,
+    /// Keep the markdown file and discard the .sh script
+    Md,
+}
+
+/// Shell code from a markdown file's fenced code blocks (untagged or tagged as a shell)
+fn extract_shell_fences(markdown: &str) -> Option<String> {
+    let mut body = Vec::new();
+    let mut in_shell_fence = None;
+    
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some(info) = trimmed.strip_prefix("```") {
+            in_shell_fence = match in_shell_fence {
+                Some(_) => None,
+                None => Some(matches!(info.trim(), "" | "sh" | "bash" | "shell" | "zsh")),
+            };
+            continue;
+        }
+        
+        if in_shell_fence == Some(true) {
+            body.push(line);
+        }
+    }
+    
+    (!body.is_empty()).then(|| body.join("\n"))
+}
+
+/// Script lines without the shebang, blank lines or trailing whitespace,
+/// so a script and its markdown copy compare equal
+fn normalize_script(script: &str) -> String {
+    script
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty() && !line.starts_with("#!"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Granularity used to thin older files under a rolling retention policy
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ThinGranularity {
+    Week,
+    Month,
+}
+
+/// Parse a duration like `24h`, `10d`, `2w` or `3m` (months are 30 days)
+pub fn parse_duration(value: &str) -> std::result::Result<chrono::Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("missing unit in '{}' (use h, d, w or m)", value))?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("invalid duration '{}'", value))?;
+    
+    match unit {
+        "h" => Ok(chrono::Duration::hours(amount)),
+        "d" => Ok(chrono::Duration::days(amount)),
+        "w" => Ok(chrono::Duration::weeks(amount)),
+        "m" => Ok(chrono::Duration::days(amount * 30)),
+        _ => Err(format!("unknown unit '{}' (use h, d, w or m)", unit)),
+    }
+}
+
+/// Parse a `--newer-than`/`--older-than` bound: an ISO date (`2025-01-01`,
+/// meaning its local midnight) or a duration back from now (`7d`, `24h`)
+pub fn parse_date_bound(value: &str) -> std::result::Result<chrono::DateTime<chrono::Local>, String> {
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d") {
+        return local_midnight(date).ok_or_else(|| format!("'{}' has no local midnight", value));
+    }
+    
+    parse_duration(value)
+        .map(|duration| chrono::Local::now() - duration)
+        .map_err(|e| format!("{} (or give a date like 2025-01-01)", e))
+}
+
+/// Parse a `--min-confidence` threshold between 0 and 1
+pub fn parse_confidence(value: &str) -> std::result::Result<f32, String> {
+    let confidence: f32 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid confidence '{}'", value))?;
+    if !(0.0..=1.0).contains(&confidence) {
+        return Err(format!("confidence must be between 0 and 1, got {}", confidence));
+    }
+    Ok(confidence)
+}
+
+/// Header line that marks a rubric as written by `maid keep`
+const GENERATED_RUBRIC_SIGNATURE: &str = "*Generated by Maid";
+
+/// A file's type and the leading part of its content used for rubric key terms
+struct RubricInput {
+    file_type: FileType,
+    excerpt: String,
+}
+
+/// Name of the audit report Keep writes into the directory it processed
+const KEEP_REPORT: &str = "maid-keep-report.json";
+
+/// What Keep decided to do with a file
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum KeepVerdict {
+    Kept,
+    Discarded,
+    /// A rubric from an earlier run, replaced by the one generated now
+    Regenerated,
+}
+
+/// One file's entry in the keep report
+#[derive(Debug, Serialize)]
+struct KeepDecision {
+    path: PathBuf,
+    /// Missing for files that couldn't be read
+    doc_kind: Option<DocumentKind>,
+    word_count: Option<usize>,
+    decision: KeepVerdict,
+    reason: String,
+    /// Which timestamp ranked the file by recency
+    date_source: Option<DateSource>,
+}
+
+impl KeepDecision {
+    fn new(path: &Path, info: Option<&FileInfo>, decision: KeepVerdict, reason: String) -> Self {
+        KeepDecision {
+            path: path.to_path_buf(),
+            doc_kind: info.map(|info| info.doc_kind),
+            word_count: info.map(FileInfo::word_count),
+            decision,
+            reason,
+            date_source: info.and_then(|info| info.date_source),
+        }
+    }
+}
+
+/// Structure to track file analysis results for keep command
+pub struct KeepAnalysis {
+    pub important_files: Vec<PathBuf>,
+    pub redundant_files: Vec<PathBuf>,
+    /// Every decision made, in order, for the keep report
+    decisions: Vec<KeepDecision>,
+    /// Each group of files found to be copies of one another, so a group is
+    /// never trashed down to its last copy
+    duplicate_groups: Vec<Vec<PathBuf>>,
+    /// What rubric generation needs from each file, captured when it was first
+    /// read so it isn't read again
+    rubric_inputs: HashMap<PathBuf, RubricInput>,
+    trash_dir: PathBuf,
+    /// Whether the trash bin is the default temporary one
+    temporary: bool,
+    /// Whether a terminal is opened that deletes the temporary bin when it's
+    /// closed (--auto-purge)
+    self_destruct: bool,
+    /// Redundant files that went to the platform trash instead of the bin
+    system_trashed: Vec<PathBuf>,
+    /// Total size of the files evaluated
+    processed_bytes: u64,
+    /// Files kept because they couldn't be read
+    unreadable: usize,
+}
+
+impl KeepAnalysis {
+    pub fn new(trash_dir: Option<PathBuf>, auto_purge: bool) -> Self {
+        let (trash_dir, temporary) = match trash_dir {
+            Some(trash_dir) => (trash_dir, false),
+            None => (Self::default_trash_dir(), true),
+        };
+        
+        KeepAnalysis {
+            important_files: Vec::new(),
+            redundant_files: Vec::new(),
+            decisions: Vec::new(),
+            duplicate_groups: Vec::new(),
+            rubric_inputs: HashMap::new(),
+            trash_dir,
+            temporary,
+            self_destruct: auto_purge,
+            system_trashed: Vec::new(),
+            processed_bytes: 0,
+            unreadable: 0,
+        }
+    }
+    
+    /// A fresh, timestamped trash bin under the system temporary directory
+    fn default_trash_dir() -> PathBuf {
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+        std::env::temp_dir().join(format!("maid-trash-bin-{}", timestamp))
+    }
+    
+    /// Make sure the trash bin can be written to, falling back to the default
+    /// temporary bin if a custom one isn't usable
+    fn prepare_trash_dir(&mut self) -> Result<()> {
+        let writable = fs::create_dir_all(&self.trash_dir).is_ok() && {
+            let probe = self.trash_dir.join(".maid-write-test");
+            let ok = fs::write(&probe, b"").is_ok();
+            let _ = fs::remove_file(&probe);
+            ok
+        };
+        
+        if !writable {
+            if self.temporary {
+                anyhow::bail!("Cannot write to trash directory: {}", self.trash_dir.display());
+            }
+            
+            let fallback = Self::default_trash_dir();
+            log::warn!(
+                "trash directory {} isn't writable, using {}",
+                self.trash_dir.display(),
+                fallback.display()
+            );
+            println!(
+                "{} Trash directory {} isn't writable, using {} instead",
+                "Warning:".yellow().bold(),
+                self.trash_dir.display(),
+                fallback.display()
+            );
+            self.trash_dir = fallback;
+            self.temporary = true;
+            fs::create_dir_all(&self.trash_dir)?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Evaluate files to determine which ones should be kept
+    pub fn evaluate_files(
+        &mut self,
+        file_paths: &[PathBuf],
+        options: &KeepOptions,
+        config: &Config,
+    ) -> Result<()> {
+        let verbose = options.verbose;
+        
+        let mut analyzed = Vec::new();
+        self.processed_bytes += total_size(file_paths);
+        
+        for file_path in file_paths {
+            let info = FileInfo::new(file_path.clone());
+            if let Ok(info) = &info {
+                // Frontmatter is metadata, not what the file is about
+                let body = info.body();
+                let mut excerpt_len = body.floor_char_boundary(options.max_content_bytes_for_keywords);
+                // Don't count a word cut off by the limit
+                if excerpt_len < body.len() {
+                    excerpt_len = body[..excerpt_len]
+                        .rfind(char::is_whitespace)
+                        .unwrap_or(0);
+                }
+                self.rubric_inputs.insert(
+                    file_path.clone(),
+                    RubricInput {
+                        file_type: info.file_type,
+                        excerpt: body[..excerpt_len].to_string(),
+                    },
+                );
+            }
+            
+            match info {
+                Ok(info) if excluded_by_content(&options.exclude_content, &info.content).is_some() => {
+                    // Authors can opt a file out of cleanup from inside it
+                    self.keep(file_path.clone(), Some(&info), "content matches excluded pattern", verbose);
+                },
+                Ok(mut info) => {
+                    info.apply_rules(config);
+                    if options.classify_report_vs_summary_by_structure {
+                        info.classify_report_vs_summary_by_structure(config);
+                    }
+                    
+                    // A rubric from an earlier run is regenerated, not ranked
+                    // against the project's own rubrics
+                    if info.doc_kind == DocumentKind::Rubric
+                        && info.content.contains(GENERATED_RUBRIC_SIGNATURE)
+                    {
+                        log::info!("regenerate {} (generated by a previous run)", file_path.display());
+                        if verbose {
+                            println!(
+                                "{} {} (generated by a previous run)",
+                                "Regenerating:".blue().bold(),
+                                file_path.display().to_string().blue()
+                            );
+                        }
+                        self.decisions.push(KeepDecision::new(
+                            file_path,
+                            Some(&info),
+                            KeepVerdict::Regenerated,
+                            "generated by a previous run".to_string(),
+                        ));
+                        continue;
+                    }
+                    
+                    // READMEs, changelogs, licenses and task lists are never trashed,
+                    // even as duplicates
+                    if is_readme(file_path) {
+                        self.keep(file_path.clone(), Some(&info), "protected README", verbose);
+                    } else if matches!(
+                        info.doc_kind,
+                        DocumentKind::Changelog | DocumentKind::License | DocumentKind::TaskList
+                    ) {
+                        let reason = format!("protected {:?}", info.doc_kind);
+                        self.keep(file_path.clone(), Some(&info), reason, verbose);
+                    } else {
+                        analyzed.push((file_path.clone(), info));
+                    }
+                },
+                Err(e) => {
+                    // If we can't analyze the file, keep it by default
+                    log::error!("{}: {:#}", file_path.display(), e);
+                    self.unreadable += 1;
+                    self.keep(file_path.clone(), None, "could not be analyzed", verbose);
+                }
+            }
+        }
+        
+        // Drop near-identical copies before looking at document kinds
+        let mut analyzed = self.remove_content_duplicates(
+            analyzed,
+            options.dedupe_keep,
+            options.dedupe_keep_annotated.as_deref(),
+            verbose,
+        );
+        
+        if let Some(keep) = options.dedupe_cross_extension {
+            analyzed = self.remove_cross_extension_duplicates(analyzed, keep, verbose);
+        }
+        
+        if options.dedup_only {
+            for (path, info) in analyzed {
+                self.keep(path, Some(&info), "unique content", verbose);
+            }
+            self.protect_last_copies();
+            return Ok(());
+        }
+        
+        // Failed generations aren't worth keeping whatever their kind
+        let (error_output, analyzed): (Vec<_>, Vec<_>) = analyzed
+            .into_iter()
+            .partition(|(_, info)| config.is_error_output(&info.content));
+        
+        for (path, info) in error_output {
+            self.discard(path, Some(&info), "non-content/error output", verbose);
+        }
+        
+        // Group files by document kind
+        let mut rubrics = Vec::new();
+        let mut reports = Vec::new();
+        let mut guides = Vec::new();
+        let mut summaries = Vec::new();
+        let mut faqs = Vec::new();
+        let mut scripts = Vec::new();
+        
+        for (file_path, info) in analyzed {
+            if options.keep_all.contains(&info.doc_kind) {
+                let reason = format!("--keep-all {:?}", info.doc_kind).to_lowercase();
+                self.keep(file_path, Some(&info), reason, verbose);
+                continue;
+            }
+            
+            match info.doc_kind {
+                DocumentKind::Rubric => rubrics.push((file_path, info)),
+                DocumentKind::Report => reports.push((file_path, info)),
+                DocumentKind::Guide => guides.push((file_path, info)),
+                DocumentKind::Summary => summaries.push((file_path, info)),
+                DocumentKind::Faq => faqs.push((file_path, info)),
+                DocumentKind::Script => scripts.push((file_path, info)),
+                // Each data description documents a different dataset
+                DocumentKind::Data => self.keep(file_path, Some(&info), "data description", verbose),
+                // Reference docs each cover their own API, so they rarely duplicate
+                DocumentKind::ApiReference => self.keep(file_path, Some(&info), "API reference", verbose),
+                DocumentKind::TaskList
+                | DocumentKind::Changelog
+                | DocumentKind::License
+                | DocumentKind::Unknown => {
+                    // For unknown types, keep them by default
+                    self.keep(file_path, Some(&info), "unknown kind", verbose);
+                }
+            }
+        }
+        
+        // Keep the most comprehensive rubric, discard others
+        if !rubrics.is_empty() {
+            // Find the most comprehensive rubric (highest quality score by default)
+            let weights = options.weights(RankWeights::QUALITY_ONLY);
+            rank_files(&mut rubrics, weights);
+            
+            // Keep the most comprehensive one and mark others as redundant
+            self.keep_first(rubrics, 1, weights, "most comprehensive rubric", "redundant rubric", verbose);
+        }
+        
+        // Reports contained in a longer report (e.g. one that only appends
+        // "Update:" sections) are superseded by it, regardless of date
+        if options.dedupe_ignore_trailing_report_sections {
+            let superseded_by: Vec<Option<PathBuf>> = reports
+                .iter()
+                .map(|(_, info)| {
+                    let content = info.content.trim();
+                    reports
+                        .iter()
+                        .find(|(_, other)| {
+                            let other = other.content.trim();
+                            other.len() > content.len() && other.contains(content)
+                        })
+                        .map(|(path, _)| path.clone())
+                })
+                .collect();
+            
+            let mut remaining = Vec::new();
+            for ((path, info), superset) in reports.into_iter().zip(superseded_by) {
+                match superset {
+                    Some(superset) => {
+                        let reason = format!("superseded by {}", superset.display());
+                        self.discard(path, Some(&info), reason, verbose);
+                    }
+                    None => remaining.push((path, info)),
+                }
+            }
+            reports = remaining;
+        }
+        
+        let most_recent = |singular: &str, plural: &str| match options.keep_last {
+            1 => format!("most recent {}", singular),
+            count => format!("one of the {} most recent {}", count, plural),
+        };
+        
+        // For reports, keep the most recent ones (or thin them over time)
+        if let Some(keep_recent) = options.keep_recent {
+            self.apply_retention(reports, keep_recent, options.thin_by, "report", verbose);
+        } else if !reports.is_empty() {
+            // Rank by creation date if available, newest first by default
+            let weights = options.weights(RankWeights::RECENCY_ONLY);
+            rank_files(&mut reports, weights);
+            
+            // Keep the newest reports and mark older ones as redundant
+            let kept_reason = most_recent("report", "reports");
+            self.keep_first(reports, options.keep_last, weights, &kept_reason, "older report", verbose);
+        }
+        
+        // Keep all guides
+        for (path, info) in guides {
+            self.keep(path, Some(&info), "guide", verbose);
+        }
+        
+        // Keep the most recent summary, discard others (or thin them over time)
+        if let Some(keep_recent) = options.keep_recent {
+            self.apply_retention(summaries, keep_recent, options.thin_by, "summary", verbose);
+        } else if !summaries.is_empty() {
+            // Rank by creation date if available, newest first by default,
+            // with quality breaking ties between summaries of the same age
+            let weights = options.weights(RankWeights::RECENCY_THEN_QUALITY);
+            rank_files(&mut summaries, weights);
+            
+            // Keep the newest summaries and mark older ones as redundant
+            let kept_reason = most_recent("summary", "summaries");
+            self.keep_first(summaries, options.keep_last, weights, &kept_reason, "older summary", verbose);
+        }
+        
+        // Keep the newest FAQ, discard older ones
+        if !faqs.is_empty() {
+            let weights = options.weights(RankWeights::RECENCY_ONLY);
+            rank_files(&mut faqs, weights);
+            self.keep_first(faqs, 1, weights, "most recent FAQ", "older FAQ", verbose);
+        }
+        
+        // Keep all scripts (duplicates were already removed by content)
+        for (path, info) in scripts {
+            self.keep(path, Some(&info), "unique script", verbose);
+        }
+        
+        self.protect_last_copies();
+        Ok(())
+    }
+    
+    /// Safety check before anything is trashed: a duplicate group with no
+    /// member left to keep is an error, and the whole group is kept instead
+    /// of losing every copy
+    fn protect_last_copies(&mut self) {
+        for group in std::mem::take(&mut self.duplicate_groups) {
+            if group.iter().any(|path| self.important_files.contains(path)) {
+                continue;
+            }
+            
+            log::error!("every copy of {} was marked for the trash", group[0].display());
+            eprintln!(
+                "{} Every copy of {} was marked for the trash; keeping all {} of them",
+                "Error:".red().bold(),
+                group[0].display(),
+                group.len()
+            );
+            
+            for path in group {
+                let Some(position) = self.redundant_files.iter().position(|file| *file == path) else {
+                    continue;
+                };
+                self.redundant_files.remove(position);
+                
+                if let Some(decision) = self
+                    .decisions
+                    .iter_mut()
+                    .rev()
+                    .find(|decision| decision.path == path)
+                {
+                    decision.decision = KeepVerdict::Kept;
+                    decision.reason = format!("last copy of a duplicate group (was: {})", decision.reason);
+                }
+                self.important_files.push(path);
+            }
+        }
+    }
+    
+    /// Keep a file, noting why in verbose output and the keep report
+    fn keep(&mut self, path: PathBuf, info: Option<&FileInfo>, reason: impl Into<String>, verbose: bool) {
+        let reason = reason.into();
+        log::info!("keep {} ({})", path.display(), reason);
+        if verbose {
+            println!(
+                "{} {} ({})",
+                "Keeping:".green().bold(),
+                path.display().to_string().green(),
+                reason
+            );
+        }
+        
+        self.decisions.push(KeepDecision::new(&path, info, KeepVerdict::Kept, reason));
+        self.important_files.push(path);
+    }
+    
+    /// Mark a file as redundant, noting why in verbose output and the keep report
+    fn discard(&mut self, path: PathBuf, info: Option<&FileInfo>, reason: impl Into<String>, verbose: bool) {
+        let reason = reason.into();
+        log::info!("discard {} ({})", path.display(), reason);
+        if verbose {
+            println!(
+                "{} {} ({})",
+                "Discarding:".yellow().bold(),
+                path.display().to_string().yellow(),
+                reason
+            );
+        }
+        
+        self.decisions.push(KeepDecision::new(&path, info, KeepVerdict::Discarded, reason));
+        self.redundant_files.push(path);
+    }
+    
+    /// Keep the first `count` of some ranked files and discard the rest. When
+    /// quality counted towards the ranking, each reason includes the file's score.
+    fn keep_first(
+        &mut self,
+        files: Vec<(PathBuf, FileInfo)>,
+        count: usize,
+        weights: RankWeights,
+        kept_reason: &str,
+        discarded_reason: &str,
+        verbose: bool,
+    ) {
+        let with_quality = |reason: &str, info: &FileInfo| {
+            if weights.quality > 0.0 {
+                format!("{}, quality {:.1}", reason, quality_score(info))
+            } else {
+                reason.to_string()
+            }
+        };
+        
+        let mut files = files.into_iter();
+        for (path, info) in files.by_ref().take(count) {
+            let reason = with_quality(kept_reason, &info);
+            self.keep(path, Some(&info), reason, verbose);
+        }
+        for (path, info) in files {
+            let reason = with_quality(discarded_reason, &info);
+            self.discard(path, Some(&info), reason, verbose);
+        }
+    }
+    
+    /// Drop one of each markdown/shell pair where the markdown's fenced code is
+    /// the same script as the `.sh` file
+    fn remove_cross_extension_duplicates(
+        &mut self,
+        files: Vec<(PathBuf, FileInfo)>,
+        keep: CrossExtensionKeep,
+        verbose: bool,
+    ) -> Vec<(PathBuf, FileInfo)> {
+        let scripts: HashMap<String, (&PathBuf, &FileInfo)> = files
+            .iter()
+            .filter(|(_, info)| info.file_type == FileType::Shell)
+            .map(|(path, info)| (normalize_script(&info.content), (path, info)))
+            .filter(|(script, _)| !script.is_empty())
+            .collect();
+        
+        let mut redundant = HashSet::new();
+        for (path, info) in &files {
+            if info.file_type != FileType::Markdown {
+                continue;
+            }
+            
+            let Some(&script) = extract_shell_fences(&info.content)
+                .and_then(|fenced| scripts.get(&normalize_script(&fenced)))
+            else {
+                continue;
+            };
+            
+            let ((discard, discard_info), (kept, _)) = match keep {
+                CrossExtensionKeep::Sh => ((path, info), script),
+                CrossExtensionKeep::Md => (script, (path, info)),
+            };
+            if !redundant.insert(discard.clone()) {
+                continue;
+            }
+            
+            let reason = format!("same script as {}", kept.display());
+            self.discard(discard.clone(), Some(discard_info), reason, verbose);
+            self.duplicate_groups.push(vec![kept.clone(), discard.clone()]);
+        }
+        
+        files
+            .into_iter()
+            .filter(|(path, _)| !redundant.contains(path))
+            .collect()
+    }
+    
+    /// Group files by a hash of their normalized content and keep only one file
+    /// from each group of duplicates, chosen by `keep`
+    fn remove_content_duplicates(
+        &mut self,
+        files: Vec<(PathBuf, FileInfo)>,
+        keep: DedupeKeep,
+        annotation: Option<&str>,
+        verbose: bool,
+    ) -> Vec<(PathBuf, FileInfo)> {
+        let mut groups: HashMap<u64, Vec<(PathBuf, FileInfo)>> = HashMap::new();
+        let mut order = Vec::new();
+        
+        // Hashing is the expensive part, so it runs in parallel; grouping
+        // stays in file order so the same file always survives
+        let hashes: Vec<u64> = files
+            .par_iter()
+            .map(|(_, info)| normalized_content_hash(&info.content, annotation))
+            .collect();
+        
+        for ((path, info), hash) in files.into_iter().zip(hashes) {
+            let group = groups.entry(hash).or_default();
+            if group.is_empty() {
+                order.push(hash);
+            }
+            group.push((path, info));
+        }
+        
+        let mut unique = Vec::new();
+        
+        for hash in order {
+            let mut group = groups.remove(&hash).unwrap_or_default();
+            
+            // Stable sort, so the first file found wins a full tie
+            match keep {
+                DedupeKeep::DescriptiveName => group.sort_by_cached_key(|(path, _)| {
+                    (std::cmp::Reverse(name_descriptiveness(path)), path_depth(path))
+                }),
+                DedupeKeep::ShortestPath => group.sort_by_cached_key(|(path, _)| {
+                    (path_depth(path), std::cmp::Reverse(name_descriptiveness(path)))
+                }),
+            }
+            // A copy someone marked by hand beats any other rule
+            if let Some(marker) = annotation {
+                group.sort_by_key(|(_, info)| !info.content.contains(marker));
+            }
+            let mut group = group.into_iter();
+            let Some((kept_path, kept_info)) = group.next() else {
+                continue;
+            };
+            
+            let mut members = vec![kept_path.clone()];
+            for (path, info) in group {
+                let reason = format!("duplicate of {}", kept_path.display());
+                members.push(path.clone());
+                self.discard(path, Some(&info), reason, verbose);
+            }
+            if members.len() > 1 {
+                self.duplicate_groups.push(members);
+            }
+            
+            unique.push((kept_path, kept_info));
+        }
+        
+        unique
+    }
+    
+    /// Rolling retention: keep every file from the recent window, and only the
+    /// newest file per week or month before it. Files without a date are kept.
+    fn apply_retention(
+        &mut self,
+        mut files: Vec<(PathBuf, FileInfo)>,
+        keep_recent: chrono::Duration,
+        granularity: ThinGranularity,
+        label: &str,
+        verbose: bool,
+    ) {
+        let cutoff = chrono::Local::now() - keep_recent;
+        let period = format!("{:?}", granularity).to_lowercase();
+        rank_files(&mut files, RankWeights::RECENCY_ONLY);
+        
+        let mut seen_buckets = HashSet::new();
+        
+        for (path, info) in files {
+            let reason = match info.created_date {
+                None => format!("undated {}", label),
+                Some(date) if date >= cutoff => format!("recent {}", label),
+                Some(date) => {
+                    let bucket = match granularity {
+                        ThinGranularity::Week => (date.iso_week().year(), date.iso_week().week()),
+                        ThinGranularity::Month => (date.year(), date.month()),
+                    };
+                    
+                    if !seen_buckets.insert(bucket) {
+                        let reason = format!("older {} in the same {}", label, period);
+                        self.discard(path, Some(&info), reason, verbose);
+                        continue;
+                    }
+                    
+                    format!("newest {} of its {}", label, period)
+                }
+            };
+            
+            self.keep(path, Some(&info), reason, verbose);
+        }
+    }
+    
+    /// Total size of the files marked redundant, while they're still in place
+    fn redundant_bytes(&self) -> u64 {
+        total_size(&self.redundant_files)
+    }
+    
+    /// Move redundant files to the trash bin
+    fn move_to_trash(&mut self, to_system: bool, verbose: bool) -> Result<()> {
+        if to_system {
+            self.move_to_system_trash(verbose);
+        }
+        
+        if self.redundant_files.is_empty() {
+            return Ok(());
+        }
+        
+        // Create trash directory
+        self.prepare_trash_dir()?;
+        
+        // A reused trash bin already has a manifest to add to
+        let mut manifest = read_trash_manifest(&self.trash_dir)?;
+        
+        // Move redundant files to trash
+        for file_path in &self.redundant_files {
+            let file_name = file_path
+                .file_name()
+                .unwrap_or_else(|| std::ffi::OsStr::new("unknown"))
+                .to_string_lossy();
+                
+            let target_path = self.trash_dir.join(file_name.to_string());
+            
+            // Handle duplicate file names in trash
+            let mut actual_target_path = target_path.clone();
+            let mut counter = 1;
+            
+            while actual_target_path.exists() {
+                let new_name = format!(
+                    "{}-{}.{}",
+                    target_path.file_stem().unwrap().to_string_lossy(),
+                    counter,
+                    target_path.extension().unwrap_or_default().to_string_lossy()
+                );
+                actual_target_path = self.trash_dir.join(new_name);
+                counter += 1;
+            }
+            
+            // Record what already made it to the bin before giving up, so
+            // `maid restore` can still bring those files back
+            if let Err(e) = move_file(file_path, &actual_target_path) {
+                write_trash_manifest(&self.trash_dir, &manifest)?;
+                return Err(e);
+            }
+            log::info!("trash {} -> {}", file_path.display(), actual_target_path.display());
+            
+            manifest.push(TrashEntry {
+                trashed: std::path::absolute(&actual_target_path)
+                    .unwrap_or_else(|_| actual_target_path.clone()),
+                original: std::path::absolute(file_path).unwrap_or_else(|_| file_path.clone()),
+            });
+            
+            if verbose {
+                println!(
+                    "{} {} -> {}",
+                    "Moved:".yellow().bold(),
+                    file_path.display().to_string().yellow(),
+                    actual_target_path.display().to_string().bright_black()
+                );
+            }
+        }
+        
+        write_trash_manifest(&self.trash_dir, &manifest)?;
+        
+        // A user-chosen trash bin is meant to be kept, and the temporary one
+        // only goes away by itself with --auto-purge
+        if !self.temporary || !self.self_destruct {
+            return Ok(());
+        }
+        
+        // Set up self-destruct on terminal close
+        // We'll create a script that deletes the trash bin
+        let script_path = self.trash_dir.join("self_destruct.sh");
+        let script_content = format!(
+            r#"#!/bin/bash
+# This script will delete the maid trash bin when the terminal session ends
+trap "rm -rf {}" EXIT
+# Keep the terminal session open until explicit termination
+cat <(echo "Maid trash bin will be deleted when this terminal is closed.")
+# Execute the trap even if the script is killed
+exec bash"#,
+            self.trash_dir.display()
+        );
+        
+        let mut file = File::create(&script_path)?;
+        file.write_all(script_content.as_bytes())?;
+        
+        // Make the script executable
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = fs::metadata(&script_path)?.permissions();
+            perms.set_mode(0o755);
+            fs::set_permissions(&script_path, perms)?;
+        }
+        
+        // Launch the self-destruct script in a new terminal. Without one (e.g.
+        // on a headless server) the bin is simply left in place.
+        let launched = match terminal_command(&script_path).map(|mut command| command.spawn()) {
+            Some(Ok(_)) => Ok(()),
+            Some(Err(e)) => Err(e.to_string()),
+            None => Err("no terminal emulator for this platform".to_string()),
+        };
+        
+        if let Err(e) = launched {
+            log::warn!("couldn't open a terminal for {}: {}", script_path.display(), e);
+            println!(
+                "{} Couldn't open a terminal to purge the trash bin ({}), so it's left in place",
+                "Warning:".yellow().bold(),
+                e
+            );
+            self.self_destruct = false;
+        }
+        
+        Ok(())
+    }
+    
+    /// Send redundant files to the platform trash. Files it can't take are
+    /// left in `redundant_files` for the custom bin.
+    #[cfg(feature = "system-trash")]
+    fn move_to_system_trash(&mut self, verbose: bool) {
+        let mut remaining = Vec::new();
+        
+        for file_path in std::mem::take(&mut self.redundant_files) {
+            match trash::delete(&file_path) {
+                Ok(()) => {
+                    if verbose {
+                        println!(
+                            "{} {} -> {}",
+                            "Moved:".yellow().bold(),
+                            file_path.display().to_string().yellow(),
+                            "system trash".bright_black()
+                        );
+                    }
+                    self.system_trashed.push(file_path);
+                }
+                Err(e) => {
+                    log::warn!("couldn't send {} to the system trash: {}", file_path.display(), e);
+                    println!(
+                        "{} Couldn't send {} to the system trash ({}), using the trash bin instead",
+                        "Warning:".yellow().bold(),
+                        file_path.display(),
+                        e
+                    );
+                    remaining.push(file_path);
+                }
+            }
+        }
+        
+        self.redundant_files = remaining;
+    }
+    
+    #[cfg(not(feature = "system-trash"))]
+    fn move_to_system_trash(&mut self, _verbose: bool) {
+        if !self.redundant_files.is_empty() {
+            println!(
+                "{} This build of maid has no system trash support, using the trash bin instead",
+                "Warning:".yellow().bold()
+            );
+        }
+    }
+    
+    /// Write what was kept or discarded, and why, to the keep report
+    fn write_report(&self, dir_path: &Path, verbose: bool) -> Result<()> {
+        let report_path = dir_path.join(KEEP_REPORT);
+        let json = serde_json::to_string_pretty(&self.decisions).context("Failed to serialize keep report")?;
+        fs::write(&report_path, json)
+            .with_context(|| format!("Failed to write keep report to {}", report_path.display()))?;
+        
+        if verbose {
+            println!(
+                "{} {}",
+                "Created:".green().bold(),
+                report_path.display().to_string().green()
+            );
+        }
+        
+        Ok(())
+    }
+    
+    /// Generate a comprehensive rubric based on kept files, counting each
+    /// file's use of a word at most `keyword_cap` times
+    fn generate_comprehensive_rubric(
+        &self,
+        base_dir: &Path,
+        keyword_cap: Option<usize>,
+        format: RubricFormat,
+        extra_stopwords: &HashSet<String>,
+        verbose: bool,
+    ) -> Result<()> {
+        if self.important_files.is_empty() {
+            if verbose {
+                println!("{} No files to analyze for rubric generation", "Warning:".yellow().bold());
+            }
+            return Ok(());
+        }
+        
+        if verbose {
+            println!("{} Generating comprehensive rubric...", "Info:".blue().bold());
+        }
+        
+        let mut rubric_content = String::new();
+        rubric_content.push_str("# Comprehensive Project Rubric\n\n");
+        rubric_content.push_str(&format!("{} - AI-generated file organizer*\n\n", GENERATED_RUBRIC_SIGNATURE));
+        rubric_content.push_str("## Overview\n\n");
+        rubric_content.push_str("This rubric is automatically generated based on the analysis of project documentation and scripts.\n\n");
+        
+        // Add current date
+        let current_date = chrono::Local::now().format("%Y-%m-%d").to_string();
+        rubric_content.push_str(&format!("Generated on: {}\n\n", current_date));
+        
+        // Extract key concepts from important files
+        rubric_content.push_str("## Key Project Components\n\n");
+        
+        let mut keywords: HashMap<String, usize> = HashMap::new();
+        
+        // Sections depend on the file types we've kept
+        let mut has_documentation = false;
+        let mut has_scripts = false;
+        
+        for file_path in &self.important_files {
+            let Some(input) = self.rubric_inputs.get(file_path) else {
+                continue;
+            };
+            
+            // Extract keywords from content, leaving out stopwords and numbers
+            let content_words = input
+                .excerpt
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .filter(|s| !s.is_empty() && s.len() > 3)
+                .map(str::to_lowercase)
+                .filter(|word| {
+                    !RUBRIC_STOPWORDS.contains(&word.as_str())
+                        && !extra_stopwords.contains(word)
+                        && !word.chars().all(|c| c.is_ascii_digit())
+                });
+            
+            let mut file_counts: HashMap<String, usize> = HashMap::new();
+            for word in content_words {
+                *file_counts.entry(word).or_insert(0) += 1;
+            }
+            for (word, count) in file_counts {
+                *keywords.entry(word).or_insert(0) += keyword_cap.map_or(count, |cap| count.min(cap));
+            }
+            
+            match input.file_type {
+                FileType::Markdown => has_documentation = true,
+                FileType::Shell | FileType::Python => has_scripts = true,
+                _ => {}
+            }
+        }
+        
+        // Get top keywords
+        let mut keyword_counts: Vec<(String, usize)> = keywords.into_iter().collect();
+        // Alphabetical among equal counts, so ties come out the same every run
+        keyword_counts.sort_by(|(a_word, a_count), (b_word, b_count)| {
+            b_count.cmp(a_count).then_with(|| a_word.cmp(b_word))
+        });
+        
+        let top_keywords: Vec<String> = keyword_counts
+            .iter()
+            .take(15)
+            .map(|(word, _)| word.clone())
+            .collect();
+            
+        rubric_content.push_str("### Key Terms\n\n");
+        for keyword in &top_keywords {
+            rubric_content.push_str(&format!("- {}\n", keyword));
+        }
+        
+        rubric_content.push_str("\n## Evaluation Criteria\n\n");
+        
+        if has_documentation {
+            rubric_content.push_str("### Documentation Quality\n\n");
+            rubric_content.push_str("| Criterion | Poor | Satisfactory | Excellent |\n");
+            rubric_content.push_str("|-----------|------|--------------|----------|\n");
+            rubric_content.push_str("| Completeness | Documentation missing key components | Most features documented | Comprehensive documentation of all features |\n");
+            rubric_content.push_str("| Clarity | Confusing or unclear | Generally clear with some issues | Clear, concise, and well-organized |\n");
+            rubric_content.push_str("| Examples | Few or no examples | Some examples provided | Rich examples covering typical use cases |\n");
+            rubric_content.push('\n');
+        }
+        
+        if has_scripts {
+            rubric_content.push_str("### Script Quality\n\n");
+            rubric_content.push_str("| Criterion | Poor | Satisfactory | Excellent |\n");
+            rubric_content.push_str("|-----------|------|--------------|----------|\n");
+            rubric_content.push_str("| Functionality | Scripts fail to accomplish tasks | Scripts work but have limitations | Scripts work flawlessly for all use cases |\n");
+            rubric_content.push_str("| Readability | Poorly commented and structured | Adequate comments and structure | Well-commented, clear structure |\n");
+            rubric_content.push_str("| Error Handling | Little or no error handling | Basic error handling | Comprehensive error handling with helpful messages |\n");
+            rubric_content.push('\n');
+        }
+        
+        // Add references to hallucination issues
+        rubric_content.push_str("## Note on Documentation Management\n\n");
+        rubric_content.push_str("Research has shown that having too many redundant documentation files can lead to confusion and AI hallucinations when used as reference material. This rubric is generated as part of an effort to consolidate and organize project documentation.\n\n");
+        rubric_content.push_str("### References\n\n");
+        rubric_content.push_str("- Hallucination in Large Language Models: [https://arxiv.org/abs/2309.01219](https://arxiv.org/abs/2309.01219)\n");
+        rubric_content.push_str("- The Impact of Contradictory Data on AI Training: [https://www.nature.com/articles/s41467-023-42879-y](https://www.nature.com/articles/s41467-023-42879-y)\n");
+        
+        // Save rubric to file
+        let (file_name, rubric_content) = match format {
+            RubricFormat::Md => ("COMPREHENSIVE_PROJECT_RUBRIC.md", rubric_content),
+            RubricFormat::Html => ("COMPREHENSIVE_PROJECT_RUBRIC.html", markdown_to_html(&rubric_content)),
+        };
+        let rubric_path = base_dir.join(file_name);
+        let mut file = File::create(&rubric_path)?;
+        file.write_all(rubric_content.as_bytes())?;
+        
+        if verbose {
+            println!(
+                "{} {}",
+                "Created:".green().bold(),
+                rubric_path.display().to_string().green()
+            );
+        }
+        
+        Ok(())
+    }
+}
+
+/// Common words, and bits of links and markup, that say nothing about a
+/// project and are left out of the rubric's key terms
+const RUBRIC_STOPWORDS: &[&str] = &[
+    "about", "above", "after", "again", "also", "because", "been", "before", "being", "below", "between",
+    "both", "could", "does", "doing", "down", "during", "each", "every", "file", "files", "first", "from",
+    "further", "have", "having", "here", "into", "just", "like", "make", "many", "more", "most", "much",
+    "must", "need", "needs", "only", "other", "over", "same", "should", "some", "such", "than", "that",
+    "their", "them", "then", "there", "these", "they", "this", "those", "through", "under", "until",
+    "upon", "used", "uses", "using", "very", "want", "were", "what", "when", "where", "which", "while",
+    "will", "with", "within", "without", "would", "your", "yours",
+    // Markdown and link noise
+    "http", "https", "www", "com", "org", "html", "href", "nbsp",
+];
+
+/// Extra stopwords from a `--stopwords-file`: whitespace-separated words,
+/// with `#` starting a comment
+fn read_stopwords(path: Option<&Path>) -> Result<HashSet<String>> {
+    let Some(path) = path else {
+        return Ok(HashSet::new());
+    };
+    
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read stopword file {}", path.display()))?;
+    Ok(content
+        .lines()
+        .flat_map(|line| line.split('#').next().unwrap_or_default().split_whitespace())
+        .map(str::to_lowercase)
+        .collect())
+}
+
+/// Render markdown (with its tables) as an HTML fragment
+fn markdown_to_html(markdown: &str) -> String {
+    let parser = pulldown_cmark::Parser::new_ext(markdown, pulldown_cmark::Options::ENABLE_TABLES);
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, parser);
+    html
+}
+
+/// Keep important files and move others to trash
+pub fn keep_important_files(dir_path: &Path, options: &KeepOptions) -> Result<RunOutcome> {
+    let KeepOptions {
+        recursive,
+        max_depth,
+        verbose,
+        ..
+    } = *options;
+    
+    // Read extra stopwords first, so a bad path fails before anything is moved
+    let stopwords = read_stopwords(options.stopwords_file.as_deref())?;
+    
+    // Find all files of a supported type
+    let CollectedFiles {
+        paths: mut file_paths,
+        aliases,
+    } = collect_files(dir_path, recursive, max_depth, &[], false, false, false);
+    file_paths.retain(|path| matches_name(path, &options.name, &options.name_regex));
+    
+    let total_files = file_paths.len();
+    
+    println!(
+        "{} {} files in {}",
+        "Found".cyan().bold(),
+        total_files.to_string().yellow().bold(),
+        dir_path.display().to_string().green(),
+    );
+    
+    if verbose {
+        report_aliases(&aliases);
+    }
+    
+    if total_files == 0 {
+        println!("{} No files to process", "Warning:".yellow().bold());
+        return Ok(RunOutcome::default());
+    }
+    
+    // Create and run the analysis
+    let mut analysis = KeepAnalysis::new(options.trash_dir.clone(), options.auto_purge);
+    let config = Config::load(dir_path)?;
+    analysis.evaluate_files(&file_paths, options, &config)?;
+    
+    // Generate statistics
+    let important_count = analysis.important_files.len();
+    let redundant_count = analysis.redundant_files.len();
+    
+    println!("\n{}", "📊 Analysis Results".cyan().bold());
+    println!("  {} {}", "Files to keep:".green(), important_count);
+    println!("  {} {}", "Files to move to trash:".yellow(), redundant_count);
+    let redundant_bytes = analysis.redundant_bytes();
+    println!("  {} {}", "Bytes to move to trash:".yellow(), format_size(redundant_bytes));
+    
+    // Confirm with the user (on stderr when --quiet, so the question is still asked)
+    let prompt = format!(
+        "\n{} This will move {} files to the trash bin. Continue? (y/N) ",
+        "⚠️".yellow().bold(),
+        redundant_count.to_string().yellow().bold()
+    );
+    if quiet() {
+        eprint!("{}", prompt);
+        io::stderr().flush()?;
+    } else {
+        print!("{}", prompt);
+        io::stdout().flush()?;
+    }
+    
+    if !read_confirmation(false) {
+        println!("{} Operation cancelled", "Info:".blue().bold());
+        return Ok(RunOutcome::default());
+    }
+    
+    let outcome = RunOutcome {
+        processed: redundant_count,
+        skipped: important_count,
+        failed: analysis.unreadable,
+    };
+    
+    // Move redundant files to trash
+    analysis.move_to_trash(options.trash_to_system, verbose)?;
+    
+    // Generate comprehensive rubric
+    analysis.generate_comprehensive_rubric(
+        dir_path,
+        options.keyword_cap(),
+        options.rubric_format,
+        &stopwords,
+        verbose,
+    )?;
+    
+    // Leave an audit trail of every decision
+    analysis.write_report(dir_path, verbose)?;
+    
+    // Print summary
+    println!("\n{}", "📊 Summary".cyan().bold());
+    println!("  {} {}", "Files kept:".green(), important_count);
+    println!("  {} {}", "Files moved to trash:".yellow(), redundant_count);
+    println!("  {} {}", "Bytes processed:".green(), format_size(analysis.processed_bytes));
+    println!("  {} {}", "Bytes moved to trash:".yellow(), format_size(redundant_bytes));
+    if !analysis.system_trashed.is_empty() {
+        println!(
+            "  {} {}",
+            "Sent to system trash:".bright_black(),
+            analysis.system_trashed.len().to_string().bright_black()
+        );
+        if analysis.redundant_files.is_empty() {
+            return Ok(outcome);
+        }
+    }
+    println!(
+        "  {} {}",
+        "Trash location:".bright_black(),
+        analysis.trash_dir.display().to_string().bright_black()
+    );
+    if analysis.self_destruct {
+        println!("  {} The trash bin will be automatically deleted when you close its terminal window", 
+            "Note:".blue().bold()
+        );
+    } else if analysis.temporary {
+        println!("  {} The trash bin stays until you delete it (--auto-purge deletes it with its terminal window)",
+            "Note:".blue().bold()
+        );
+    }
+    
+    Ok(outcome)
+}
+
+/// The command that runs a script in a new terminal window, if this platform has one
+fn terminal_command(script: &Path) -> Option<std::process::Command> {
+    let script = script.to_string_lossy().into_owned();
+    
+    if cfg!(target_os = "macos") {
+        let mut command = std::process::Command::new("open");
+        command.args(["-a", "Terminal", &script]);
+        Some(command)
+    } else if cfg!(target_os = "windows") {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/c", "start", "cmd", "/k", &script]);
+        Some(command)
+    } else if cfg!(unix) {
+        let mut command = std::process::Command::new("x-terminal-emulator");
+        command.args(["-e", &script]);
+        Some(command)
+    } else {
+        None
+    }
+}
+
+/// Print what maid thinks each file is and what it would be renamed to.
+/// Only reads files; nothing on disk is changed.
+pub fn print_stats(dir_path: &Path, recursive: bool) -> Result<()> {
+    let CollectedFiles { paths: file_paths, .. } = collect_files(dir_path, recursive, None, &[], false, false, false);
+    let config = Config::load(dir_path)?;
+    
+    let mut kind_counts: HashMap<DocumentKind, usize> = HashMap::new();
+    let mut type_counts: HashMap<FileType, usize> = HashMap::new();
+    let mut listing = Vec::new();
+    
+    for file_path in &file_paths {
+        let Ok(mut info) = FileInfo::new(file_path.clone()) else {
+            continue;
+        };
+        info.apply_rules(&config);
+        
+        *kind_counts.entry(info.doc_kind).or_default() += 1;
+        *type_counts.entry(info.file_type).or_default() += 1;
+        
+        let display_path = file_path.strip_prefix(dir_path).unwrap_or(file_path);
+        listing.push((display_path.display().to_string(), info.doc_kind, info.generate_new_filename()));
+    }
+    
+    println!(
+        "{} {} files in {}\n",
+        "Found".cyan().bold(),
+        file_paths.len().to_string().yellow().bold(),
+        dir_path.display().to_string().green()
+    );
+    
+    println!("{}", "📊 By document kind".cyan().bold());
+    for kind in DocumentKind::ALL {
+        let count = kind_counts.get(&kind).copied().unwrap_or(0);
+        println!(
+            "  {:<12} {}",
+            format!("{:?}", kind).color(config.kind_color(kind)),
+            count
+        );
+    }
+    
+    println!("\n{}", "📊 By file type".cyan().bold());
+    for file_type in [FileType::Markdown, FileType::Shell, FileType::Python, FileType::Yaml] {
+        let count = type_counts.get(&file_type).copied().unwrap_or(0);
+        println!("  {:<12} {}", format!("{:?}", file_type), count);
+    }
+    
+    println!("\n{}", "📄 Files".cyan().bold());
+    let path_width = listing.iter().map(|(path, _, _)| path.len()).max().unwrap_or(0);
+    for (path, kind, new_name) in &listing {
+        println!(
+            "  {:<path_width$}  {:<10}  {}",
+            path.yellow(),
+            format!("{:?}", kind).color(config.kind_color(*kind)),
+            new_name.green(),
+            path_width = path_width
+        );
+    }
+    
+    Ok(())
+}
+
+/// Reverse the operations recorded in the most recent Clean journal
+pub fn undo_last_run(dir_path: &Path, dry_run: bool, verbose: bool) -> Result<()> {
+    let history_dir = history_dir(dir_path);
+    let latest_journal = fs::read_dir(&history_dir)
+        .ok()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| matches!(path.extension().and_then(|ext| ext.to_str()), Some("json" | "jsonl")))
+        .max();
+    
+    let Some(journal_path) = latest_journal else {
+        println!("{} No Clean runs to undo in {}", "Info:".blue().bold(), dir_path.display());
+        return Ok(());
+    };
+    
+    let entries = read_journal(&journal_path)?;
+    
+    println!(
+        "{} {} operations from {}{}",
+        "Reversing".cyan().bold(),
+        entries.len().to_string().yellow().bold(),
+        journal_path.display().to_string().green(),
+        if dry_run {
+            " (DRY RUN)".bright_red().bold().to_string()
+        } else {
+            "".to_string()
+        }
+    );
+    
+    let mut reversed = 0;
+    let mut skipped = 0;
+    
+    for entry in entries.iter().rev() {
+        let skip_reason = if fs::symlink_metadata(&entry.target).is_err() {
+            Some("target no longer exists")
+        } else if entry.target_hash.is_some()
+            && fs::read(&entry.target).ok().map(|data| sha256_hex(&data)) != entry.target_hash
+        {
+            Some("target was modified after Clean")
+        } else if matches!(entry.action, PlanAction::Move) && entry.source.exists() {
+            Some("original location is occupied")
+        } else {
+            None
+        };
+        
+        if let Some(reason) = skip_reason {
+            skipped += 1;
+            log::warn!("can't undo {}: {}", entry.target.display(), reason);
+            println!(
+                "{} {} - {}",
+                "Warning:".yellow().bold(),
+                entry.target.display().to_string().yellow(),
+                reason
+            );
+            continue;
+        }
+        
+        if verbose || dry_run {
+            if let PlanAction::Move = entry.action {
+                println!(
+                    "{} {} -> {}",
+                    "Restore:".cyan().bold(),
+                    entry.target.display().to_string().yellow(),
+                    entry.source.display().to_string().green()
+                );
+            } else {
+                println!(
+                    "{} {} (copy of {})",
+                    "Remove:".cyan().bold(),
+                    entry.target.display().to_string().yellow(),
+                    entry.source.display().to_string().green()
+                );
+            }
+        }
+        
+        if !dry_run {
+            match entry.action {
+                PlanAction::Move => {
+                    if let Some(parent) = entry.source.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    move_file(&entry.target, &entry.source)?;
+                }
+                PlanAction::Symlink => {
+                    // Put the original link back if --move removed it
+                    #[cfg(unix)]
+                    if fs::symlink_metadata(&entry.source).is_err() {
+                        std::os::unix::fs::symlink(fs::read_link(&entry.target)?, &entry.source)?;
+                    }
+                    fs::remove_file(&entry.target)?;
+                }
+                PlanAction::Copy | PlanAction::Skip => fs::remove_file(&entry.target)?,
+            }
+            log::info!("undo {} -> {}", entry.target.display(), entry.source.display());
+        }
+        
+        reversed += 1;
+    }
+    
+    // A fully reversed journal is done with; keep it around if anything was left behind
+    if !dry_run && skipped == 0 {
+        fs::remove_file(&journal_path)?;
+    }
+    
+    println!("\n{}", "📊 Summary".cyan().bold());
+    println!("  {} {}", "Operations reversed:".green(), reversed);
+    println!("  {} {}", "Operations skipped:".yellow(), skipped);
+    
+    Ok(())
+}
+
+/// The `maid-trash-bin-*` directories in the temporary directory, oldest first
+fn trash_bins() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(std::env::temp_dir()) else {
+        return Vec::new();
+    };
+    let mut bins: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_dir()
+                && path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with("maid-trash-bin-"))
+        })
+        .collect();
+    bins.sort();
+    bins
+}
+
+/// The most recently created `maid-trash-bin-*` directory in the temporary directory
+fn latest_trash_dir() -> Option<PathBuf> {
+    trash_bins().pop()
+}
+
+/// When a trash bin was created, from the timestamp in its name or else its
+/// modification time
+fn trash_bin_created(bin: &Path) -> Option<chrono::DateTime<chrono::Local>> {
+    let from_name = bin
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_prefix("maid-trash-bin-"))
+        .and_then(|timestamp| chrono::NaiveDateTime::parse_from_str(timestamp, "%Y%m%d_%H%M%S").ok())
+        .and_then(|timestamp| timestamp.and_local_timezone(chrono::Local).earliest());
+    
+    from_name.or_else(|| {
+        let modified = fs::metadata(bin).and_then(|metadata| metadata.modified()).ok()?;
+        Some(modified.into())
+    })
+}
+
+/// Number and total size of the trashed files in a bin, leaving out its manifest
+fn trash_bin_contents(bin: &Path) -> (usize, u64) {
+    WalkBuilder::new(bin)
+        .standard_filters(false)
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_some_and(|file_type| file_type.is_file()))
+        .filter(|entry| entry.path() != bin.join(TRASH_MANIFEST))
+        .fold((0, 0), |(count, size), entry| {
+            let len = entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+            (count + 1, size + len)
+        })
+}
+
+/// Combined size of the files that can still be read
+fn total_size(paths: &[PathBuf]) -> u64 {
+    paths
+        .iter()
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// A byte count in the largest binary unit that keeps it at least 1
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Print each trash bin with its age, file count and size, deleting those
+/// older than `purge_older_than`
+pub fn list_trash(purge_older_than: Option<chrono::Duration>) -> Result<()> {
+    let bins = trash_bins();
+    if bins.is_empty() {
+        println!("{} No maid trash bins in {}", "Info:".blue().bold(), std::env::temp_dir().display());
+        return Ok(());
+    }
+    
+    let cutoff = purge_older_than.map(|age| chrono::Local::now() - age);
+    let mut purged = 0;
+    let mut reclaimed = 0;
+    
+    for bin in &bins {
+        let created = trash_bin_created(bin);
+        let (files, size) = trash_bin_contents(bin);
+        let stale = cutoff.is_some_and(|cutoff| created.is_some_and(|created| created < cutoff));
+        
+        println!(
+            "{} {} ({} files, {}){}",
+            created
+                .map(|created| created.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| "unknown date".to_string())
+                .bright_black(),
+            bin.display().to_string().green(),
+            files,
+            format_size(size),
+            if stale { " - purged".red().to_string() } else { String::new() }
+        );
+        
+        if stale {
+            fs::remove_dir_all(bin)
+                .with_context(|| format!("Failed to delete trash bin {}", bin.display()))?;
+            log::info!("purge {} ({} files, {})", bin.display(), files, format_size(size));
+            purged += 1;
+            reclaimed += size;
+        }
+    }
+    
+    println!("\n{}", "📊 Summary".cyan().bold());
+    println!("  {} {}", "Trash bins:".bright_white(), bins.len());
+    if purge_older_than.is_some() {
+        println!("  {} {}", "Bins purged:".green(), purged);
+        println!("  {} {}", "Space reclaimed:".green(), format_size(reclaimed));
+    }
+    
+    Ok(())
+}
+
+/// Move trashed files back to their original locations using the bin's manifest
+pub fn restore_from_trash(from: Option<PathBuf>, file: Option<&str>) -> Result<()> {
+    let Some(trash_dir) = from.or_else(latest_trash_dir) else {
+        println!("{} No maid trash bin found", "Info:".blue().bold());
+        return Ok(());
+    };
+    
+    let entries = read_trash_manifest(&trash_dir)?;
+    if entries.is_empty() {
+        println!(
+            "{} Nothing to restore in {}",
+            "Info:".blue().bold(),
+            trash_dir.display()
+        );
+        return Ok(());
+    }
+    
+    println!(
+        "{} {} trashed files in {}",
+        "Found".cyan().bold(),
+        entries.len().to_string().yellow().bold(),
+        trash_dir.display().to_string().green()
+    );
+    
+    let mut restored = 0;
+    let mut skipped = 0;
+    let mut remaining = Vec::new();
+    
+    for entry in entries {
+        let selected = file.is_none_or(|name| {
+            entry.trashed.file_name().is_some_and(|trashed| trashed == name)
+                || entry.original.file_name().is_some_and(|original| original == name)
+        });
+        
+        if !selected {
+            remaining.push(entry);
+            continue;
+        }
+        
+        let skip_reason = if !entry.trashed.exists() {
+            Some("no longer in the trash bin")
+        } else if entry.original.exists() {
+            Some("original location is occupied")
+        } else {
+            None
+        };
+        
+        if let Some(reason) = skip_reason {
+            skipped += 1;
+            log::warn!("can't restore {}: {}", entry.original.display(), reason);
+            println!(
+                "{} {} - {}",
+                "Warning:".yellow().bold(),
+                entry.original.display().to_string().yellow(),
+                reason
+            );
+            if entry.trashed.exists() {
+                remaining.push(entry);
+            }
+            continue;
+        }
+        
+        if let Some(parent) = entry.original.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        move_file(&entry.trashed, &entry.original)?;
+        log::info!("restore {} -> {}", entry.trashed.display(), entry.original.display());
+        restored += 1;
+        
+        println!(
+            "{} {} -> {}",
+            "Restored:".green().bold(),
+            entry.trashed.display().to_string().bright_black(),
+            entry.original.display().to_string().green()
+        );
+    }
+    
+    if let Some(name) = file {
+        if restored + skipped == 0 {
+            anyhow::bail!("No file named {} in {}", name, trash_dir.display());
+        }
+    }
+    
+    write_trash_manifest(&trash_dir, &remaining)?;
+    
+    println!("\n{}", "📊 Summary".cyan().bold());
+    println!("  {} {}", "Files restored:".green(), restored);
+    println!("  {} {}", "Files skipped:".yellow(), skipped);
+    
+    Ok(())
+}
@@ -14,15 +14,25 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
 use colored::*;
-use indicatif::{ProgressBar, ProgressStyle};
+use maid::{
+    clean_directory, init_config, keep_important_files, list_trash, parse_confidence, parse_date_bound, parse_duration, parse_extension, parse_kind,
+    parse_kind_color, parse_name_glob, print_stats, println, process_single_file, restore_from_trash, set_io_concurrency, set_quiet, undo_last_run,
+    CleanOptions, ConflictStrategy, CrossExtensionKeep, DedupeKeep, DocumentKind, KeepOptions, KeywordWeighting,
+    MaidError, OutputFormat, ReportPaths, RubricFormat, RunOutcome, ThinGranularity,
+};
+use globset::GlobMatcher;
 use regex::Regex;
 use std::fs::{self, File};
-use std::io::{self, Read, Write};
+use std::io::{self, Write};
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+use std::process::ExitCode;
+use std::sync::Mutex;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -30,29 +40,150 @@ use walkdir::WalkDir;
     author = "Realvonmakeheat <dev@shrowd.org>",
     version = "0.1.0",
     about = "Clean up and restructure AI-generated files",
-    long_about = "Maid helps clean up AI-generated .md and .sh files by renaming, reorganizing, and making them more human-readable."
+    long_about = "Maid helps clean up AI-generated .md, .sh, .py and .yml files by renaming, reorganizing, and making them more human-readable.",
+    after_help = "Exit codes: 0 success, 1 some files failed (or the run stopped on an error), 2 usage error, 3 directory missing"
 )]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Disable colored output
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Print nothing but errors
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Most files read or copied at once, whatever --jobs is; lower it if
+    /// large runs hit the open file limit
+    #[arg(long, global = true, value_name = "N", default_value = "64")]
+    io_concurrency: NonZeroUsize,
+
+    /// Also write a log of what maid does to this file, one JSON object per line
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
+    /// Least severe messages written to --log-file (error, warn, info, debug or trace)
+    #[arg(long, global = true, value_name = "LEVEL", default_value_t = log::LevelFilter::Info)]
+    log_level: log::LevelFilter,
+}
+
+/// Appends log records to `--log-file` as JSON Lines, alongside the
+/// colored terminal output
+struct FileLogger {
+    file: Mutex<File>,
+    level: log::LevelFilter,
+}
+
+impl FileLogger {
+    /// Route the `log` macros to `path`. Without a log file they stay no-ops.
+    fn init(path: Option<&Path>, level: log::LevelFilter) -> Result<()> {
+        let Some(path) = path else {
+            return Ok(());
+        };
+        
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open log file {}", path.display()))?;
+        log::set_boxed_logger(Box::new(FileLogger {
+            file: Mutex::new(file),
+            level,
+        }))
+        .context("Failed to set up logging")?;
+        log::set_max_level(level);
+        
+        Ok(())
+    }
+}
+
+impl log::Log for FileLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+    
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        
+        let entry = serde_json::json!({
+            "time": chrono::Local::now().to_rfc3339(),
+            "level": record.level().to_string(),
+            "message": record.args().to_string(),
+        });
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        // A failed log write shouldn't stop the run it's describing
+        let _ = writeln!(file, "{}", entry);
+    }
+    
+    fn flush(&self) {
+        let _ = self.file.lock().unwrap_or_else(|e| e.into_inner()).flush();
+    }
 }
 
+// Parsed once per run, so Clean's many flags aren't worth boxing
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 enum Commands {
-    /// Clean up AI-generated .md and .sh files
+    /// Clean up AI-generated .md, .sh, .py and .yml files
     Clean {
-        /// Path to the directory to clean
+        /// Path to the directory (or single file) to clean
         #[arg(short, long)]
         path: Option<PathBuf>,
 
+        /// Directory a single-file --path is routed relative to (defaults to its parent)
+        #[arg(long, value_name = "DIR")]
+        base_dir: Option<PathBuf>,
+
         /// Recursively clean subdirectories
         #[arg(short, long)]
         recursive: bool,
 
+        /// Descend at most this many directory levels with --recursive
+        /// (0 is the directory itself only, 1 its files)
+        #[arg(long, value_name = "N")]
+        max_depth: Option<usize>,
+
+        /// Read the files to clean from stdin, one path per line, instead of
+        /// walking --path (which files are still routed relative to)
+        #[arg(long, conflicts_with_all = ["interactive_classify", "base_dir"])]
+        stdin: bool,
+
         /// Restructure files (don't just rename)
-        #[arg(short, long)]
+        #[arg(short = 'R', long)]
         restructure: bool,
 
+        /// With --restructure, put every file straight into --path instead of
+        /// the docs/ and scripts/ tree
+        #[arg(long, requires = "restructure")]
+        flat: bool,
+
+        /// With --restructure, keep each file's subdirectory beneath the kind
+        /// folder (sub/report.md goes to docs/reports/sub/)
+        #[arg(long, requires = "restructure", conflicts_with = "flat")]
+        preserve_tree: bool,
+
+        /// With --restructure, put markdown under this directory instead of `docs/`
+        #[arg(long, value_name = "PATH", requires = "restructure", conflicts_with = "flat")]
+        docs_root: Option<PathBuf>,
+
+        /// With --restructure, put scripts under this directory instead of `scripts/`
+        #[arg(long, value_name = "PATH", requires = "restructure", conflicts_with = "flat")]
+        scripts_root: Option<PathBuf>,
+
+        /// With --restructure, build the organized tree under this directory
+        /// instead of --path (which stays untouched unless --move is given)
+        #[arg(long, value_name = "PATH", requires = "restructure")]
+        target_dir: Option<PathBuf>,
+
+        /// With --restructure, rewrite markdown headings in sentence case
+        /// ("## Getting started"), keeping known acronyms and inline code
+        #[arg(long, requires = "restructure")]
+        normalize_headings_to_sentence_case: bool,
+
         /// Dry run (don't actually change anything)
         #[arg(short, long)]
         dry_run: bool,
@@ -60,6 +191,185 @@ enum Commands {
         /// Verbose output
         #[arg(short, long)]
         verbose: bool,
+
+        /// Move files instead of copying them
+        #[arg(short, long = "move")]
+        move_files: bool,
+
+        /// Symlink files into their new location instead of copying them,
+        /// leaving the originals in place
+        #[arg(long, conflicts_with = "move_files")]
+        link: bool,
+
+        /// What to do when a file's target already exists
+        #[arg(long, value_enum, default_value_t = ConflictStrategy::Skip)]
+        on_conflict: ConflictStrategy,
+
+        /// Give copies the original file's access and modification times
+        /// (pass `--preserve-timestamps false` to use the current time)
+        #[arg(long, default_value_t = true, action = clap::ArgAction::Set, value_name = "BOOL")]
+        preserve_timestamps: bool,
+
+        /// Route scripts that fail `bash -n` to scripts/needs-review
+        #[arg(long)]
+        verify_shell_syntax: bool,
+
+        /// Write the planned operations as JSON to this path (requires --dry-run)
+        #[arg(long, value_name = "PATH", requires = "dry_run")]
+        dry_run_json_to: Option<PathBuf>,
+
+        /// Write a list of renamed files, mapping each original name and path
+        /// to its new one, as JSON (or CSV if PATH ends in .csv)
+        #[arg(long, value_name = "PATH")]
+        keep_originals_index: Option<PathBuf>,
+
+        /// List files whose document kind changed since a plan written by
+        /// --dry-run-json-to (with the same --report-paths)
+        #[arg(long, value_name = "PLAN")]
+        report_kind_transitions: Option<PathBuf>,
+
+        /// Remove source directories left empty once their files are moved out
+        #[arg(long)]
+        prune_empty: bool,
+
+        /// Share classification results across runs through this cache file
+        #[arg(long, value_name = "PATH")]
+        cache_file: Option<PathBuf>,
+
+        /// Write every classification check made on each file, matched or not,
+        /// and the kind it ended up with to this path as JSON Lines
+        #[arg(long, value_name = "PATH")]
+        classify_trace: Option<PathBuf>,
+
+        /// Carry on with an interrupted run: skip what its journal (under
+        /// .maid/history) records as done and add new operations to it
+        #[arg(long, value_name = "JOURNAL")]
+        resume: Option<PathBuf>,
+
+        /// Only process files created or modified since the last run with
+        /// this flag (everything on the first run)
+        #[arg(long)]
+        since_last_run: bool,
+
+        /// In a git repository, only process files changed in commits since
+        /// this ref (as `git diff --name-only REF...HEAD` lists them)
+        #[arg(long, value_name = "REF")]
+        since_commit: Option<String>,
+
+        /// Only process files dated after this date (2025-01-01) or within
+        /// this long (7d, 24h); files are dated as Keep ranks them (version
+        /// header, frontmatter, filename, then filesystem times), and files
+        /// with no date are skipped
+        #[arg(long, value_name = "DATE|DURATION", value_parser = parse_date_bound)]
+        newer_than: Option<chrono::DateTime<chrono::Local>>,
+
+        /// Only process files dated before this date or longer ago than this
+        #[arg(long, value_name = "DATE|DURATION", value_parser = parse_date_bound)]
+        older_than: Option<chrono::DateTime<chrono::Local>>,
+
+        /// Leave files classified with less than this confidence (0 to 1)
+        /// untouched; --verbose prints each file's confidence
+        #[arg(long, value_name = "CONFIDENCE", default_value = "0", value_parser = parse_confidence)]
+        min_confidence: f32,
+
+        /// Process files below --min-confidence anyway, unclassified ones
+        /// included, marking their names with " (review)" before the extension
+        #[arg(long, requires = "min_confidence")]
+        annotate_confidence_in_name: bool,
+
+        /// Number of files to process in parallel (defaults to the number of CPUs)
+        #[arg(short, long, value_name = "N")]
+        jobs: Option<usize>,
+
+        /// With --verbose, only print details for the first N files, then show
+        /// a progress bar for the rest
+        #[arg(long, value_name = "N", requires = "verbose")]
+        preview_limit: Option<usize>,
+
+        /// Show the progress bar even with --verbose
+        #[arg(long, conflicts_with = "no_progress")]
+        progress: bool,
+
+        /// Never show the progress bar (or the dots printed per file)
+        #[arg(long)]
+        no_progress: bool,
+
+        /// Ask how to classify files maid can't, and remember the answers in maid.toml
+        #[arg(long, conflicts_with = "format")]
+        interactive_classify: bool,
+
+        /// Show each file's proposed rename or move and ask before making it:
+        /// [y]es, [n]o, [a]ll (stop asking), or [q]uit (change nothing)
+        #[arg(short, long, conflicts_with_all = ["dry_run", "yes", "stdin", "format"])]
+        interactive: bool,
+
+        /// Leave files that only contain an AI refusal or error message untouched
+        #[arg(long)]
+        skip_error_output: bool,
+
+        /// When a file has both report and summary keywords, call it a report
+        /// if it has many sections or is long, otherwise a summary
+        #[arg(long)]
+        classify_report_vs_summary_by_structure: bool,
+
+        /// Leave files whose content matches this regex untouched (repeatable)
+        #[arg(long, value_name = "REGEX", value_parser = Regex::new)]
+        exclude_content: Vec<Regex>,
+
+        /// Print a document kind in another color, e.g. report=red (repeatable)
+        #[arg(long, value_name = "KIND=COLOR", value_parser = parse_kind_color)]
+        kind_color: Vec<(DocumentKind, String)>,
+
+        /// Also process files with this extension, e.g. txt (repeatable)
+        #[arg(long, value_name = "EXT", value_parser = parse_extension)]
+        include_ext: Vec<String>,
+
+        /// Also process files of every other extension, routed to misc/ with --restructure
+        #[arg(long)]
+        include_other: bool,
+
+        /// Also process files without an extension that start with a `#!` line,
+        /// as shell (or Python) scripts
+        #[arg(long)]
+        include_extensionless: bool,
+
+        /// Give extensionless scripts a `.sh` (or `.py`) extension when renaming them
+        #[arg(long, requires = "include_extensionless")]
+        add_extension: bool,
+
+        /// Also process files with a stray extension after a known one, like
+        /// `report.md.txt`, dropping the trailing extension when renaming
+        #[arg(long)]
+        strict_extensions: bool,
+
+        /// Only process files whose name matches this glob, e.g. `*_report_*.md`
+        /// (repeatable; a file matching any --name or --name-regex is processed)
+        #[arg(long, value_name = "GLOB", value_parser = parse_name_glob)]
+        name: Vec<GlobMatcher>,
+
+        /// Only process files whose name matches this regex (repeatable)
+        #[arg(long, value_name = "REGEX", value_parser = Regex::new)]
+        name_regex: Vec<Regex>,
+
+        /// How paths are written in JSON output, plan files and reports
+        #[arg(long, value_enum, default_value_t = ReportPaths::Relative)]
+        report_paths: ReportPaths,
+
+        /// Write a Markdown report of the run to this path
+        #[arg(long, value_name = "PATH")]
+        report: Option<PathBuf>,
+
+        /// Render the report with this template instead of the built-in one
+        #[arg(long, value_name = "PATH", requires = "report")]
+        output_report_template: Option<PathBuf>,
+
+        /// Output format for the run summary
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text, conflicts_with = "verbose")]
+        format: OutputFormat,
+
+        /// Don't ask for confirmation before changing anything
+        #[arg(short, long)]
+        yes: bool,
     },
 
     /// Keep important files and discard others to a temporary trash bin
@@ -72,1153 +382,473 @@ enum Commands {
         #[arg(short, long)]
         recursive: bool,
 
+        /// Descend at most this many directory levels with --recursive
+        /// (0 is the directory itself only, 1 its files)
+        #[arg(long, value_name = "N")]
+        max_depth: Option<usize>,
+
         /// Verbose output
         #[arg(short, long)]
         verbose: bool,
-    },
-}
-
-/// File types that we handle
-#[derive(Debug, PartialEq)]
-enum FileType {
-    Markdown,
-    Shell,
-    Other,
-}
-
-/// The kind of document based on content analysis
-#[derive(Debug)]
-enum DocumentKind {
-    Rubric,
-    Report,
-    Guide,
-    Summary,
-    Script,
-    Unknown,
-}
 
-/// Represents a file with its metadata and classification
-#[derive(Debug)]
-struct FileInfo {
-    path: PathBuf,
-    file_type: FileType,
-    doc_kind: DocumentKind,
-    name: String,
-    content: String,
-    created_date: Option<chrono::DateTime<chrono::Local>>,
-}
+        /// Treat a report contained in a longer report as redundant, keeping the superset
+        #[arg(long)]
+        dedupe_ignore_trailing_report_sections: bool,
+
+        /// When a file has both report and summary keywords, call it a report
+        /// if it has many sections or is long, otherwise a summary
+        #[arg(long)]
+        classify_report_vs_summary_by_structure: bool,
+
+        /// Weight of recency when ranking files within a group (default: 1 for reports and summaries, 0 for rubrics)
+        #[arg(long, value_name = "WEIGHT")]
+        recency_weight: Option<f64>,
+
+        /// Weight of word count when ranking files within a group (default: 0)
+        #[arg(long, value_name = "WEIGHT")]
+        size_weight: Option<f64>,
+
+        /// Weight of content quality (headings, tables, code blocks, links, word count) when ranking files within a group (default: 1 for rubrics, 0.5 for summaries, 0 for reports)
+        #[arg(long, value_name = "WEIGHT")]
+        quality_weight: Option<f64>,
+
+        /// Move discarded files here instead of a temporary bin
+        #[arg(long, value_name = "PATH")]
+        trash_dir: Option<PathBuf>,
+
+        /// Open a terminal that deletes the temporary trash bin when it's closed
+        #[arg(long, conflicts_with = "trash_dir")]
+        auto_purge: bool,
+
+        /// Send discarded files to the platform Trash/Recycle Bin, falling back
+        /// to the trash bin if it's unavailable
+        #[arg(long)]
+        #[cfg_attr(not(feature = "system-trash"), arg(hide = true))]
+        trash_to_system: bool,
+
+        /// Keep every report and summary newer than this (e.g. 14d, 2w, 3m) and thin older ones
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        keep_recent: Option<chrono::Duration>,
+
+        /// Keep this many of the most recent reports and summaries
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 1,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+            conflicts_with = "keep_recent"
+        )]
+        keep_last: usize,
+
+        /// Keep every file of this kind instead of only the best one, e.g. `reports` (repeatable)
+        #[arg(long, value_name = "KIND", value_parser = parse_kind)]
+        keep_all: Vec<DocumentKind>,
+
+        /// Only remove files whose content duplicates another file
+        #[arg(long)]
+        dedup_only: bool,
+
+        /// Always keep files whose content matches this regex (repeatable)
+        #[arg(long, value_name = "REGEX", value_parser = Regex::new)]
+        exclude_content: Vec<Regex>,
+
+        /// Only process files whose name matches this glob, e.g. `*_report_*.md`
+        /// (repeatable; a file matching any --name or --name-regex is processed)
+        #[arg(long, value_name = "GLOB", value_parser = parse_name_glob)]
+        name: Vec<GlobMatcher>,
+
+        /// Only process files whose name matches this regex (repeatable)
+        #[arg(long, value_name = "REGEX", value_parser = Regex::new)]
+        name_regex: Vec<Regex>,
+
+        /// Treat a markdown file wrapping the same script as a .sh file as a
+        /// duplicate, keeping the .sh (or the markdown with `md`)
+        #[arg(long, value_enum, value_name = "KEEP", num_args = 0..=1, default_missing_value = "sh")]
+        dedupe_cross_extension: Option<CrossExtensionKeep>,
+
+        /// Which of several identical files survives
+        #[arg(long, value_enum, value_name = "RULE", default_value_t = DedupeKeep::DescriptiveName)]
+        dedupe_keep: DedupeKeep,
+
+        /// Of identical files, keep one containing this marker (e.g. `<!-- reviewed -->`)
+        /// over every other rule; copies that only differ by it count as identical
+        #[arg(long, value_name = "MARKER")]
+        dedupe_keep_annotated: Option<String>,
+
+        /// Only scan this many bytes of each kept file for the rubric's key terms
+        #[arg(long, value_name = "BYTES", default_value_t = 64 * 1024)]
+        max_content_bytes_for_keywords: usize,
+
+        /// How much each kept file's use of a word counts towards the rubric's key terms
+        #[arg(long, value_enum, value_name = "MODE", default_value_t = KeywordWeighting::Count)]
+        keyword_weighting: KeywordWeighting,
+
+        /// Occurrences of a word counted per file with --keyword-weighting capped
+        #[arg(long, value_name = "N", default_value_t = 3)]
+        max_keywords_per_file: usize,
+
+        /// Write the generated rubric as markdown or as HTML
+        #[arg(long, value_enum, value_name = "FORMAT", default_value_t = RubricFormat::Md)]
+        rubric_format: RubricFormat,
+
+        /// File of extra words (whitespace-separated, `#` comments) to leave out
+        /// of the rubric's key terms
+        #[arg(long, value_name = "PATH")]
+        stopwords_file: Option<PathBuf>,
+
+        /// Keep one older report or summary per week or month (with --keep-recent)
+        #[arg(long, value_enum, default_value_t = ThinGranularity::Week, requires = "keep_recent")]
+        thin_by: ThinGranularity,
+    },
 
-impl FileInfo {
-    fn new(path: PathBuf) -> Result<Self> {
-        let file_type = match path.extension().and_then(|ext| ext.to_str()) {
-            Some("md") => FileType::Markdown,
-            Some("sh") => FileType::Shell,
-            _ => FileType::Other,
-        };
+    /// Reverse the most recent Clean run
+    Undo {
+        /// Path to the directory that was cleaned
+        #[arg(short, long)]
+        path: Option<PathBuf>,
 
-        let name = path
-            .file_stem()
-            .and_then(|name| name.to_str())
-            .unwrap_or("unknown")
-            .to_string();
+        /// Dry run (show what would be reversed)
+        #[arg(short, long)]
+        dry_run: bool,
 
-        // Read file content
-        let mut file = File::open(&path).context("Failed to open file")?;
-        let mut content = String::new();
-        file.read_to_string(&mut content)
-            .context("Failed to read file content")?;
+        /// Verbose output
+        #[arg(short, long)]
+        verbose: bool,
+    },
 
-        // Try to determine when the file was created
-        let metadata = fs::metadata(&path).ok();
-        let created_date = metadata
-            .and_then(|meta| meta.created().ok())
-            .map(|time| chrono::DateTime::from(time));
+    /// Move files from a Keep trash bin back to where they came from
+    Restore {
+        /// Trash bin to restore from (defaults to the most recent temporary bin)
+        #[arg(long)]
+        from: Option<PathBuf>,
 
-        // Determine document kind based on content and filename
-        let doc_kind = determine_document_kind(&name, &content);
+        /// Only restore the trashed file with this name
+        #[arg(long)]
+        file: Option<String>,
+    },
 
-        Ok(FileInfo {
-            path,
-            file_type,
-            doc_kind,
-            name,
-            content,
-            created_date,
-        })
-    }
+    /// List the Keep trash bins left in the temporary directory
+    ListTrash {
+        /// Delete bins older than this (e.g. 7d, 2w, 1m)
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        purge_older_than: Option<chrono::Duration>,
+    },
 
-    /// Generate a better, more human-readable filename
-    fn generate_new_filename(&self) -> String {
-        let normalized_name = self
-            .name
-            .replace('_', " ")
-            .replace('-', " ")
-            .to_lowercase();
-
-        // Extract relevant information from AI-generated filenames
-        let re_install = Regex::new(r"(?i)(?:setup|install)[_\-\s]*(.*?)$").ok();
-        let re_test = Regex::new(r"(?i)test[_\-\s]*(.*?)$").ok();
-        let re_launch = Regex::new(r"(?i)launch[_\-\s]*(.*?)$").ok();
-        let re_verify = Regex::new(r"(?i)verify[_\-\s]*(.*?)$").ok();
-        let re_cleanup = Regex::new(r"(?i)cleanup[_\-\s]*(.*?)$").ok();
-        let re_integration = Regex::new(r"(?i)integration[_\-\s]*(.*?)$").ok();
-        let re_config = Regex::new(r"(?i)config(?:uration)?[_\-\s]*(.*?)$").ok();
-        let re_build = Regex::new(r"(?i)build[_\-\s]*(.*?)$").ok();
-        let re_deploy = Regex::new(r"(?i)deploy[_\-\s]*(.*?)$").ok();
-        
-        let mut improved_name = normalized_name.clone();
-        
-        // Apply regex patterns to extract meaningful information
-        if let Some(re) = &re_install {
-            if let Some(caps) = re.captures(&normalized_name) {
-                if let Some(m) = caps.get(1) {
-                    improved_name = format!("Install {}", m.as_str().trim());
-                }
-            }
-        }
-        
-        if let Some(re) = &re_test {
-            if let Some(caps) = re.captures(&normalized_name) {
-                if let Some(m) = caps.get(1) {
-                    improved_name = format!("Test {}", m.as_str().trim());
-                }
-            }
-        }
-        
-        if let Some(re) = &re_launch {
-            if let Some(caps) = re.captures(&normalized_name) {
-                if let Some(m) = caps.get(1) {
-                    improved_name = format!("Launch {}", m.as_str().trim());
-                }
-            }
-        }
-        
-        if let Some(re) = &re_verify {
-            if let Some(caps) = re.captures(&normalized_name) {
-                if let Some(m) = caps.get(1) {
-                    improved_name = format!("Verify {}", m.as_str().trim());
-                }
-            }
-        }
-        
-        if let Some(re) = &re_cleanup {
-            if let Some(caps) = re.captures(&normalized_name) {
-                if let Some(m) = caps.get(1) {
-                    improved_name = format!("Cleanup {}", m.as_str().trim());
-                }
-            }
-        }
-        
-        if let Some(re) = &re_integration {
-            if let Some(caps) = re.captures(&normalized_name) {
-                if let Some(m) = caps.get(1) {
-                    improved_name = format!("Integration {}", m.as_str().trim());
-                }
-            }
-        }
-        
-        if let Some(re) = &re_config {
-            if let Some(caps) = re.captures(&normalized_name) {
-                if let Some(m) = caps.get(1) {
-                    improved_name = format!("Configuration {}", m.as_str().trim());
-                }
-            }
-        }
-        
-        if let Some(re) = &re_build {
-            if let Some(caps) = re.captures(&normalized_name) {
-                if let Some(m) = caps.get(1) {
-                    improved_name = format!("Build {}", m.as_str().trim());
-                }
-            }
-        }
-        
-        if let Some(re) = &re_deploy {
-            if let Some(caps) = re.captures(&normalized_name) {
-                if let Some(m) = caps.get(1) {
-                    improved_name = format!("Deploy {}", m.as_str().trim());
-                }
-            }
-        }
+    /// Show how files would be classified and renamed, without changing anything
+    Stats {
+        /// Path to the directory to inspect
+        #[arg(short, long)]
+        path: Option<PathBuf>,
 
-        // Convert to title case
-        let title_case: String = improved_name
-            .split_whitespace()
-            .map(|word| {
-                let mut chars = word.chars();
-                match chars.next() {
-                    None => String::new(),
-                    Some(first) => {
-                        let capitalized = first.to_uppercase().collect::<String>();
-                        capitalized + chars.as_str()
-                    }
-                }
-            })
-            .collect::<Vec<String>>()
-            .join(" ");
-
-        // Add prefix based on document kind
-        let prefixed_name = match self.doc_kind {
-            DocumentKind::Rubric => format!("Rubric - {}", title_case),
-            DocumentKind::Report => format!("Report - {}", title_case),
-            DocumentKind::Guide => format!("Guide - {}", title_case),
-            DocumentKind::Summary => format!("Summary - {}", title_case),
-            DocumentKind::Script => title_case,
-            DocumentKind::Unknown => title_case,
-        };
+        /// Include subdirectories
+        #[arg(short, long)]
+        recursive: bool,
+    },
 
-        // Add extension
-        match self.file_type {
-            FileType::Markdown => format!("{}.md", prefixed_name),
-            FileType::Shell => format!("{}.sh", prefixed_name),
-            FileType::Other => self
-                .path
-                .file_name()
-                .and_then(|name| name.to_str())
-                .unwrap_or("unknown")
-                .to_string(),
-        }
-    }
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
 
-    /// Generate suggested target directory based on document kind
-    fn suggest_target_directory(&self, base_dir: &Path) -> PathBuf {
-        match (&self.file_type, &self.doc_kind) {
-            (FileType::Markdown, DocumentKind::Rubric) => base_dir.join("docs/rubrics"),
-            (FileType::Markdown, DocumentKind::Report) => base_dir.join("docs/reports"),
-            (FileType::Markdown, DocumentKind::Guide) => base_dir.join("docs/guides"),
-            (FileType::Markdown, DocumentKind::Summary) => base_dir.join("docs/summaries"),
-            (FileType::Shell, DocumentKind::Script) => {
-                // Determine script subcategory
-                let content_lower = self.content.to_lowercase();
-                if content_lower.contains("install") || content_lower.contains("setup") {
-                    base_dir.join("scripts/setup")
-                } else if content_lower.contains("test") {
-                    base_dir.join("scripts/tests")
-                } else if content_lower.contains("build") {
-                    base_dir.join("scripts/build")
-                } else {
-                    base_dir.join("scripts")
-                }
-            }
-            _ => base_dir.to_path_buf(),
-        }
-    }
+    /// Manage maid.toml
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
 }
 
-/// Determine document kind based on filename and content
-fn determine_document_kind(filename: &str, content: &str) -> DocumentKind {
-    let filename_lower = filename.to_lowercase();
-    let content_lower = content.to_lowercase();
-    
-    // Check for rubrics
-    if filename_lower.contains("rubric") 
-        || content_lower.contains("# rubric") 
-        || content_lower.contains("rubric for") 
-        || content_lower.contains("evaluation rubric") 
-        || content_lower.contains("assessment criteria") 
-        || content_lower.contains("scoring guide") {
-        return DocumentKind::Rubric;
-    }
-    
-    // Check for reports
-    if filename_lower.contains("report") 
-        || filename_lower.contains("complete") 
-        || filename_lower.contains("status") 
-        || filename_lower.contains("analysis") 
-        || filename_lower.contains("assessment") 
-        || content_lower.contains("# report") 
-        || content_lower.contains("# completion") 
-        || content_lower.contains("# status") 
-        || content_lower.contains("# analysis") 
-        || content_lower.contains("task completion") 
-        || content_lower.contains("completion report") 
-        || content_lower.contains("status update") {
-        return DocumentKind::Report;
-    }
-    
-    // Check for guides
-    if filename_lower.contains("guide") 
-        || filename_lower.contains("how_to") 
-        || filename_lower.contains("howto") 
-        || filename_lower.contains("manual") 
-        || filename_lower.contains("tutorial") 
-        || filename_lower.contains("instructions") 
-        || content_lower.contains("# guide") 
-        || content_lower.contains("# how to") 
-        || content_lower.contains("step by step") 
-        || content_lower.contains("# tutorial") 
-        || content_lower.contains("# instructions") 
-        || content_lower.contains("how to use") 
-        || content_lower.contains("usage instructions") {
-        return DocumentKind::Guide;
-    }
-    
-    // Check for summaries
-    if filename_lower.contains("summary") 
-        || filename_lower.contains("overview") 
-        || filename_lower.contains("recap") 
-        || filename_lower.contains("synopsis") 
-        || content_lower.contains("# summary") 
-        || content_lower.contains("## summary") 
-        || content_lower.contains("# overview") 
-        || content_lower.contains("# recap") 
-        || content_lower.contains("in conclusion") 
-        || content_lower.contains("executive summary") 
-        || content_lower.contains("project summary") {
-        return DocumentKind::Summary;
-    }
-    
-    // Check for scripts (shell files are automatically scripts)
-    if filename_lower.ends_with(".sh") {
-        return DocumentKind::Script;
-    }
-    
-    // Default
-    DocumentKind::Unknown
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// Write a commented maid.toml with the built-in keywords to start from
+    Init {
+        /// Write to the user config directory instead of the current directory
+        #[arg(long)]
+        global: bool,
+
+        /// Overwrite an existing maid.toml
+        #[arg(long)]
+        force: bool,
+    },
 }
 
-/// Process a single file
-fn process_file(
-    file_path: &Path,
-    base_dir: &Path,
-    restructure: bool,
-    dry_run: bool,
-    verbose: bool,
-) -> Result<()> {
-    // Skip if not a file or if hidden
-    if !file_path.is_file() || is_hidden(file_path) {
-        return Ok(());
-    }
-
-    // Process only markdown and shell files
-    let extension = file_path.extension().and_then(|ext| ext.to_str());
-    if !matches!(extension, Some("md") | Some("sh")) {
-        return Ok(());
-    }
+/// Some files couldn't be processed, or the run stopped on an error
+const EXIT_FAILURE: u8 = 1;
+/// Options that can't be used together (clap exits with this too)
+const EXIT_USAGE: u8 = 2;
+/// The directory to work on doesn't exist or isn't a directory
+const EXIT_MISSING_DIRECTORY: u8 = 3;
 
-    // Handle symlinks
-    let real_path = if file_path.is_symlink() {
-        fs::read_link(file_path).unwrap_or_else(|_| file_path.to_path_buf())
-    } else {
-        file_path.to_path_buf()
-    };
-
-    // Analyze the file
-    let file_info = match FileInfo::new(real_path.clone()) {
-        Ok(info) => info,
-        Err(e) => {
-            if verbose {
-                println!(
-                    "{} {} - Error: {}",
-                    "Skipping:".red().bold(),
-                    file_path.display().to_string().yellow(),
-                    e.to_string()
-                );
-            }
-            return Ok(());
-        }
-    };
-    
-    // Generate new filename
-    let new_filename = file_info.generate_new_filename();
-    
-    // Determine target location
-    let target_dir = if restructure {
-        file_info.suggest_target_directory(base_dir)
-    } else {
-        file_path.parent().unwrap_or(Path::new(".")).to_path_buf()
-    };
-    
-    let target_path = target_dir.join(&new_filename);
+fn main() -> ExitCode {
+    let cli = Cli::parse();
     
-    // Print what we're doing
-    if verbose {
-        println!(
-            "{} {} -> {}",
-            "Processing:".cyan().bold(),
-            file_path.display().to_string().yellow(),
-            target_path.display().to_string().green()
-        );
-        println!(
-            "  {} {}",
-            "Type:".cyan(),
-            format!("{:?}", file_info.doc_kind).magenta()
-        );
-    } else {
-        print!(".");
-        io::stdout().flush()?;
+    if cli.no_color {
+        colored::control::set_override(false);
     }
-    
-    // If not dry run, perform the operation
-    if !dry_run {
-        // Create target directory if it doesn't exist
-        if !target_dir.exists() {
-            fs::create_dir_all(&target_dir)?;
-        }
-        
-        // Check if source and target are the same
-        let source_canonical = fs::canonicalize(file_path).ok();
-        let target_canonical_exists = target_path.exists() && fs::canonicalize(&target_path).is_ok();
-        let target_canonical = if target_canonical_exists {
-            fs::canonicalize(&target_path).ok()
-        } else {
-            None
-        };
-        
-        // Skip if target already exists and is the same file
-        if source_canonical == target_canonical && target_canonical.is_some() {
-            if verbose {
-                println!(
-                    "  {} {}",
-                    "Skip:".yellow(),
-                    "Source and target are the same file".bright_black()
-                );
-            }
-            return Ok(());
-        }
-        
-        if target_path.exists() {
-            if verbose {
-                println!(
-                    "  {} {}",
-                    "Skip:".yellow(),
-                    "Target file already exists".bright_black()
-                );
-            }
-            return Ok(());
-        }
-        
-        // Copy the file - use a symlink for symlinks, copy for real files
-        if file_path.is_symlink() {
-            if verbose {
-                println!("  {} {}", "Info:".blue(), "Creating symlink");
-            }
-            
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::symlink;
-                let original_target = fs::read_link(file_path)?;
-                symlink(original_target, &target_path)?;
-            }
-            
-            #[cfg(windows)]
-            {
-                use std::os::windows::fs::{symlink_file, symlink_dir};
-                let original_target = fs::read_link(file_path)?;
-                if original_target.is_file() {
-                    symlink_file(original_target, &target_path)?;
-                } else {
-                    symlink_dir(original_target, &target_path)?;
-                }
-            }
-        } else {
-            // Regular file copy
-            fs::copy(file_path, &target_path)?;
-        }
-        
-        if verbose {
-            println!(
-                "  {} {}",
-                "Success:".green(),
-                "File processed".bright_green()
-            );
-        }
+    set_quiet(cli.quiet);
+    set_io_concurrency(cli.io_concurrency.get());
+    
+    let result = FileLogger::init(cli.log_file.as_deref(), cli.log_level).and_then(|()| {
+        log::info!("maid {}", std::env::args().skip(1).collect::<Vec<_>>().join(" "));
+        run(cli.command)
+    });
+    if let Err(e) = &result {
+        log::error!("{:#}", e);
     }
+    log::logger().flush();
     
-    Ok(())
-}
-
-/// Check if a file is hidden
-fn is_hidden(path: &Path) -> bool {
-    path.file_name()
-        .and_then(|name| name.to_str())
-        .map(|name| name.starts_with('.'))
-        .unwrap_or(false)
-}
-
-/// Clean up files in a directory
-fn clean_directory(
-    dir_path: &Path,
-    recursive: bool,
-    restructure: bool,
-    dry_run: bool,
-    verbose: bool,
-) -> Result<()> {
-    // Count all files
-    let mut processed_files = 0;
-    let mut skipped_files = 0;
-    let mut md_files = 0;
-    let mut sh_files = 0;
-    
-    // Count files first for progress bar
-    let file_paths = if recursive {
-        WalkDir::new(dir_path)
-            .into_iter()
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| entry.file_type().is_file())
-            .filter(|entry| {
-                let path = entry.path();
-                let ext = path.extension().and_then(|ext| ext.to_str());
-                matches!(ext, Some("md") | Some("sh"))
-            })
-            .map(|entry| entry.path().to_path_buf())
-            .collect::<Vec<_>>()
-    } else {
-        fs::read_dir(dir_path)
-            .context("Failed to read directory")?
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| entry.file_type().map(|ft| ft.is_file()).unwrap_or(false))
-            .map(|entry| entry.path())
-            .filter(|path| {
-                let ext = path.extension().and_then(|ext| ext.to_str());
-                matches!(ext, Some("md") | Some("sh"))
+    match result {
+        Ok(outcome) if outcome.failed > 0 => ExitCode::from(EXIT_FAILURE),
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            ExitCode::from(match e.downcast_ref::<MaidError>() {
+                Some(MaidError::Usage(_)) => EXIT_USAGE,
+                Some(MaidError::DirectoryNotFound(_) | MaidError::NotADirectory(_)) => EXIT_MISSING_DIRECTORY,
+                None => EXIT_FAILURE,
             })
-            .collect::<Vec<_>>()
-    };
-    
-    let total_files = file_paths.len();
-        
-    println!(
-        "{} {} files in {}{}",
-        "Found".cyan().bold(),
-        total_files.to_string().yellow().bold(),
-        dir_path.display().to_string().green(),
-        if dry_run {
-            " (DRY RUN)".bright_red().bold().to_string()
-        } else {
-            "".to_string()
-        }
-    );
-    
-    let progress_bar = if !verbose {
-        let pb = ProgressBar::new(total_files as u64);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
-                .unwrap()
-                .progress_chars("#>-"),
-        );
-        Some(pb)
-    } else {
-        None
-    };
-    
-    for file_path in file_paths {
-        // Update file type counts
-        if let Some(ext) = file_path.extension().and_then(|ext| ext.to_str()) {
-            match ext {
-                "md" => md_files += 1,
-                "sh" => sh_files += 1,
-                _ => {}
-            }
-        }
-        
-        match process_file(&file_path, dir_path, restructure, dry_run, verbose) {
-            Ok(()) => {
-                processed_files += 1;
-            }
-            Err(e) => {
-                skipped_files += 1;
-                if verbose {
-                    println!(
-                        "{} {} - Error: {}",
-                        "Error:".red().bold(),
-                        file_path.display().to_string().yellow(),
-                        e.to_string()
-                    );
-                }
-            }
         }
-        
-        if let Some(pb) = &progress_bar {
-            pb.inc(1);
-        }
-    }
-    
-    if let Some(pb) = progress_bar {
-        pb.finish_with_message("Done!");
-        println!(); // Add a blank line after the progress bar
     }
-    
-    // Print a summary
-    println!("\n{}", "📊 Summary".cyan().bold());
-    println!("  {} {}", "Total files found:".bright_white(), total_files);
-    println!("  {} {}", "Files processed:".green(), processed_files);
-    println!("  {} {}", "Files skipped:".yellow(), skipped_files);
-    println!("  {} {}", "Markdown files:".magenta(), md_files);
-    println!("  {} {}", "Shell scripts:".magenta(), sh_files);
-    
-    Ok(())
 }
 
-/// Structure to track file analysis results for keep command
-struct KeepAnalysis {
-    important_files: Vec<PathBuf>,
-    redundant_files: Vec<PathBuf>,
-    trash_dir: PathBuf,
-}
-
-impl KeepAnalysis {
-    fn new() -> Self {
-        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-        let trash_dir = PathBuf::from(format!("/tmp/maid-trash-bin-{}", timestamp));
-        
-        KeepAnalysis {
-            important_files: Vec::new(),
-            redundant_files: Vec::new(),
-            trash_dir,
-        }
-    }
-    
-    /// Evaluate files to determine which ones should be kept
-    fn evaluate_files(&mut self, file_paths: &[PathBuf], verbose: bool) -> Result<()> {
-        // Group files by document kind
-        let mut rubrics = Vec::new();
-        let mut reports = Vec::new();
-        let mut guides = Vec::new();
-        let mut summaries = Vec::new();
-        let mut scripts = Vec::new();
-        
-        for file_path in file_paths {
-            match FileInfo::new(file_path.clone()) {
-                Ok(info) => {
-                    match info.doc_kind {
-                        DocumentKind::Rubric => rubrics.push((file_path.clone(), info)),
-                        DocumentKind::Report => reports.push((file_path.clone(), info)),
-                        DocumentKind::Guide => guides.push((file_path.clone(), info)),
-                        DocumentKind::Summary => summaries.push((file_path.clone(), info)),
-                        DocumentKind::Script => scripts.push((file_path.clone(), info)),
-                        DocumentKind::Unknown => {
-                            // For unknown types, keep them by default
-                            self.important_files.push(file_path.clone());
-                        }
-                    }
-                },
-                Err(_) => {
-                    // If we can't analyze the file, keep it by default
-                    self.important_files.push(file_path.clone());
-                }
-            }
-        }
-        
-        // Keep the most comprehensive rubric, discard others
-        if !rubrics.is_empty() {
-            // Find the most comprehensive rubric (highest word count as a simple heuristic)
-            rubrics.sort_by(|(_, a), (_, b)| {
-                let a_words = a.content.split_whitespace().count();
-                let b_words = b.content.split_whitespace().count();
-                b_words.cmp(&a_words)
-            });
-            
-            // Keep the most comprehensive one
-            if let Some((path, _)) = rubrics.first() {
-                self.important_files.push(path.clone());
-                
-                if verbose {
-                    println!(
-                        "{} {} (most comprehensive rubric)",
-                        "Keeping:".green().bold(),
-                        path.display().to_string().green()
-                    );
-                }
-            }
-            
-            // Mark others as redundant
-            for (path, _) in rubrics.iter().skip(1) {
-                self.redundant_files.push(path.clone());
-                
-                if verbose {
-                    println!(
-                        "{} {} (redundant rubric)",
-                        "Discarding:".yellow().bold(),
-                        path.display().to_string().yellow()
-                    );
-                }
-            }
-        }
-        
-        // For reports, keep the most recent ones
-        if !reports.is_empty() {
-            // Sort by creation date if available, newest first
-            reports.sort_by(|(_, a), (_, b)| {
-                match (&a.created_date, &b.created_date) {
-                    (Some(a_date), Some(b_date)) => b_date.cmp(a_date),
-                    (Some(_), None) => std::cmp::Ordering::Less,
-                    (None, Some(_)) => std::cmp::Ordering::Greater,
-                    (None, None) => std::cmp::Ordering::Equal,
-                }
-            });
-            
-            // Keep the newest report
-            if let Some((path, _)) = reports.first() {
-                self.important_files.push(path.clone());
-                
-                if verbose {
-                    println!(
-                        "{} {} (most recent report)",
-                        "Keeping:".green().bold(),
-                        path.display().to_string().green()
-                    );
-                }
-            }
-            
-            // Mark older reports as redundant
-            for (path, _) in reports.iter().skip(1) {
-                self.redundant_files.push(path.clone());
-                
-                if verbose {
-                    println!(
-                        "{} {} (older report)",
-                        "Discarding:".yellow().bold(),
-                        path.display().to_string().yellow()
-                    );
-                }
-            }
-        }
-        
-        // Keep all guides
-        for (path, _) in guides {
-            self.important_files.push(path.clone());
+/// Carry out a subcommand, returning how a Clean or Keep run went
+fn run(command: Commands) -> Result<RunOutcome> {
+    let mut outcome = RunOutcome::default();
+    match command {
+        Commands::Clean {
+            path,
+            base_dir,
+            recursive,
+            max_depth,
+            stdin,
+            restructure,
+            flat,
+            preserve_tree,
+            docs_root,
+            scripts_root,
+            target_dir,
+            normalize_headings_to_sentence_case,
+            dry_run,
+            verbose,
+            move_files,
+            link,
+            preserve_timestamps,
+            on_conflict,
+            verify_shell_syntax,
+            dry_run_json_to,
+            keep_originals_index,
+            report_kind_transitions,
+            prune_empty,
+            cache_file,
+            classify_trace,
+            resume,
+            since_last_run,
+            since_commit,
+            newer_than,
+            older_than,
+            min_confidence,
+            annotate_confidence_in_name,
+            jobs,
+            preview_limit,
+            progress,
+            no_progress,
+            format,
+            interactive_classify,
+            interactive,
+            skip_error_output,
+            classify_report_vs_summary_by_structure,
+            exclude_content,
+            kind_color,
+            include_ext,
+            include_other,
+            include_extensionless,
+            add_extension,
+            strict_extensions,
+            name,
+            name_regex,
+            report_paths,
+            report,
+            output_report_template,
+            yes,
+        } => {
+            let dir_path = path.unwrap_or_else(|| PathBuf::from("."));
             
-            if verbose {
+            if format == OutputFormat::Text {
                 println!(
-                    "{} {} (guide)",
-                    "Keeping:".green().bold(),
-                    path.display().to_string().green()
+                    "{} {}",
+                    "Maid".bright_cyan().bold(),
+                    "is cleaning up your AI-generated files...".bright_white()
                 );
             }
-        }
-        
-        // Keep the most recent summary, discard others
-        if !summaries.is_empty() {
-            // Sort by creation date if available, newest first
-            summaries.sort_by(|(_, a), (_, b)| {
-                match (&a.created_date, &b.created_date) {
-                    (Some(a_date), Some(b_date)) => b_date.cmp(a_date),
-                    (Some(_), None) => std::cmp::Ordering::Less,
-                    (None, Some(_)) => std::cmp::Ordering::Greater,
-                    (None, None) => std::cmp::Ordering::Equal,
-                }
-            });
-            
-            // Keep the newest summary
-            if let Some((path, _)) = summaries.first() {
-                self.important_files.push(path.clone());
-                
-                if verbose {
-                    println!(
-                        "{} {} (most recent summary)",
-                        "Keeping:".green().bold(),
-                        path.display().to_string().green()
-                    );
-                }
-            }
             
-            // Mark older summaries as redundant
-            for (path, _) in summaries.iter().skip(1) {
-                self.redundant_files.push(path.clone());
-                
-                if verbose {
-                    println!(
-                        "{} {} (older summary)",
-                        "Discarding:".yellow().bold(),
-                        path.display().to_string().yellow()
-                    );
-                }
-            }
-        }
-        
-        // Keep all scripts but analyze them for duplication
-        let mut unique_scripts: Vec<(PathBuf, FileInfo)> = Vec::new();
-        
-        for (path, info) in scripts {
-            // Simplistic content-based deduplication
-            let mut is_duplicate = false;
-            
-            for (existing_path, existing_info) in &unique_scripts {
-                if info.content.trim() == existing_info.content.trim() {
-                    if verbose {
-                        println!(
-                            "{} {} (duplicate of {})",
-                            "Discarding:".yellow().bold(),
-                            path.display().to_string().yellow(),
-                            existing_path.display().to_string().green()
-                        );
-                    }
-                    
-                    self.redundant_files.push(path.clone());
-                    is_duplicate = true;
-                    break;
-                }
+            if !dir_path.exists() {
+                return Err(MaidError::DirectoryNotFound(dir_path).into());
             }
             
-            if !is_duplicate {
-                unique_scripts.push((path.clone(), info));
-                self.important_files.push(path.clone());
-                
-                if verbose {
-                    println!(
-                        "{} {} (unique script)",
-                        "Keeping:".green().bold(),
-                        path.display().to_string().green()
-                    );
-                }
+            if !dir_path.is_dir() && (stdin || !dir_path.is_file()) {
+                return Err(MaidError::NotADirectory(dir_path).into());
             }
-        }
-        
-        Ok(())
-    }
-    
-    /// Move redundant files to the trash bin
-    fn move_to_trash(&self, verbose: bool) -> Result<()> {
-        if self.redundant_files.is_empty() {
-            return Ok(());
-        }
-        
-        // Create trash directory
-        fs::create_dir_all(&self.trash_dir)?;
-        
-        // Move redundant files to trash
-        for file_path in &self.redundant_files {
-            let file_name = file_path
-                .file_name()
-                .unwrap_or_else(|| std::ffi::OsStr::new("unknown"))
-                .to_string_lossy();
-                
-            let target_path = self.trash_dir.join(file_name.to_string());
             
-            // Handle duplicate file names in trash
-            let mut actual_target_path = target_path.clone();
-            let mut counter = 1;
+            let options = CleanOptions {
+                recursive,
+                max_depth,
+                stdin,
+                restructure,
+                flat,
+                preserve_tree,
+                docs_root,
+                scripts_root,
+                target_dir,
+                normalize_headings_to_sentence_case,
+                dry_run,
+                verbose,
+                move_files,
+                link,
+                preserve_timestamps,
+                on_conflict,
+                verify_shell_syntax,
+                dry_run_json_to,
+                keep_originals_index,
+                report_kind_transitions,
+                prune_empty,
+                cache_file,
+                classify_trace,
+                resume,
+                since_last_run,
+                since_commit,
+                newer_than,
+                older_than,
+                min_confidence,
+                annotate_confidence_in_name,
+                jobs,
+                preview_limit,
+                progress,
+                no_progress,
+                format,
+                interactive_classify,
+                interactive,
+                skip_error_output,
+                classify_report_vs_summary_by_structure,
+                exclude_content,
+                kind_color,
+                include_ext,
+                include_other,
+                include_extensionless,
+                add_extension,
+                strict_extensions,
+                name,
+                name_regex,
+                report_paths,
+                report,
+                output_report_template,
+                yes,
+            };
             
-            while actual_target_path.exists() {
-                let new_name = format!(
-                    "{}-{}.{}",
-                    target_path.file_stem().unwrap().to_string_lossy(),
-                    counter,
-                    target_path.extension().unwrap_or_default().to_string_lossy()
-                );
-                actual_target_path = self.trash_dir.join(new_name);
-                counter += 1;
+            if dir_path.is_file() {
+                let operation = process_single_file(&dir_path, base_dir.as_deref(), &options)?;
+                outcome = RunOutcome::for_single_file(operation.as_ref());
+            } else {
+                outcome = clean_directory(&dir_path, &options)?;
             }
             
-            fs::rename(file_path, &actual_target_path)?;
-            
-            if verbose {
+            if format == OutputFormat::Text {
                 println!(
-                    "{} {} -> {}",
-                    "Moved:".yellow().bold(),
-                    file_path.display().to_string().yellow(),
-                    actual_target_path.display().to_string().bright_black()
+                    "\n{} {} {}\n",
+                    "✨".bright_yellow(),
+                    "Cleaning complete!".green().bold(),
+                    "✨".bright_yellow()
                 );
             }
         }
-        
-        // Set up self-destruct on terminal close
-        // We'll create a script that deletes the trash bin
-        let script_path = self.trash_dir.join("self_destruct.sh");
-        let script_content = format!(
-            r#"#!/bin/bash
-# This script will delete the maid trash bin when the terminal session ends
-trap "rm -rf {}" EXIT
-# Keep the terminal session open until explicit termination
-cat <(echo "Maid trash bin will be deleted when this terminal is closed.")
-# Execute the trap even if the script is killed
-exec bash"#,
-            self.trash_dir.display()
-        );
-        
-        let mut file = File::create(&script_path)?;
-        file.write_all(script_content.as_bytes())?;
-        
-        // Make the script executable
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let mut perms = fs::metadata(&script_path)?.permissions();
-            perms.set_mode(0o755);
-            fs::set_permissions(&script_path, perms)?;
-        }
-        
-        // Launch the self-destruct script in a new terminal
-        #[cfg(target_os = "macos")]
-        {
-            use std::process::Command;
-            Command::new("open")
-                .args(["-a", "Terminal", script_path.to_str().unwrap()])
-                .spawn()?;
-        }
-        
-        #[cfg(target_os = "linux")]
-        {
-            use std::process::Command;
-            Command::new("x-terminal-emulator")
-                .args(["-e", script_path.to_str().unwrap()])
-                .spawn()?;
-        }
-        
-        #[cfg(target_os = "windows")]
-        {
-            use std::process::Command;
-            Command::new("cmd")
-                .args(["/c", "start", "cmd", "/k", script_path.to_str().unwrap()])
-                .spawn()?;
-        }
-        
-        Ok(())
-    }
-    
-    /// Generate a comprehensive rubric based on kept files
-    fn generate_comprehensive_rubric(&self, base_dir: &Path, verbose: bool) -> Result<()> {
-        if self.important_files.is_empty() {
-            if verbose {
-                println!("{} No files to analyze for rubric generation", "Warning:".yellow().bold());
-            }
-            return Ok(());
-        }
-        
-        if verbose {
-            println!("{} Generating comprehensive rubric...", "Info:".blue().bold());
-        }
-        
-        let mut rubric_content = String::new();
-        rubric_content.push_str("# Comprehensive Project Rubric\n\n");
-        rubric_content.push_str("*Generated by Maid - AI-generated file organizer*\n\n");
-        rubric_content.push_str("## Overview\n\n");
-        rubric_content.push_str("This rubric is automatically generated based on the analysis of project documentation and scripts.\n\n");
-        
-        // Add current date
-        let current_date = chrono::Local::now().format("%Y-%m-%d").to_string();
-        rubric_content.push_str(&format!("Generated on: {}\n\n", current_date));
-        
-        // Extract key concepts from important files
-        rubric_content.push_str("## Key Project Components\n\n");
-        
-        let mut keywords = std::collections::HashMap::new();
-        
-        for file_path in &self.important_files {
-            if let Ok(info) = FileInfo::new(file_path.clone()) {
-                // Extract keywords from content
-                let content_words: Vec<&str> = info.content
-                    .split(|c: char| !c.is_alphanumeric() && c != '_')
-                    .filter(|s| !s.is_empty() && s.len() > 3)
-                    .collect();
-                    
-                for word in content_words {
-                    *keywords.entry(word.to_lowercase()).or_insert(0) += 1;
-                }
-            }
-        }
-        
-        // Get top keywords
-        let mut keyword_counts: Vec<(String, usize)> = keywords.into_iter().collect();
-        keyword_counts.sort_by(|a, b| b.1.cmp(&a.1));
-        
-        let top_keywords: Vec<String> = keyword_counts
-            .iter()
-            .take(15)
-            .map(|(word, _)| word.clone())
-            .collect();
-            
-        rubric_content.push_str("### Key Terms\n\n");
-        for keyword in &top_keywords {
-            rubric_content.push_str(&format!("- {}\n", keyword));
-        }
-        
-        rubric_content.push_str("\n## Evaluation Criteria\n\n");
-        
-        // Add sections based on file types we've kept
-        let mut has_documentation = false;
-        let mut has_scripts = false;
-        
-        for file_path in &self.important_files {
-            if let Ok(info) = FileInfo::new(file_path.clone()) {
-                match info.file_type {
-                    FileType::Markdown => has_documentation = true,
-                    FileType::Shell => has_scripts = true,
-                    _ => {}
-                }
-            }
-        }
-        
-        if has_documentation {
-            rubric_content.push_str("### Documentation Quality\n\n");
-            rubric_content.push_str("| Criterion | Poor | Satisfactory | Excellent |\n");
-            rubric_content.push_str("|-----------|------|--------------|----------|\n");
-            rubric_content.push_str("| Completeness | Documentation missing key components | Most features documented | Comprehensive documentation of all features |\n");
-            rubric_content.push_str("| Clarity | Confusing or unclear | Generally clear with some issues | Clear, concise, and well-organized |\n");
-            rubric_content.push_str("| Examples | Few or no examples | Some examples provided | Rich examples covering typical use cases |\n");
-            rubric_content.push_str("\n");
-        }
-        
-        if has_scripts {
-            rubric_content.push_str("### Script Quality\n\n");
-            rubric_content.push_str("| Criterion | Poor | Satisfactory | Excellent |\n");
-            rubric_content.push_str("|-----------|------|--------------|----------|\n");
-            rubric_content.push_str("| Functionality | Scripts fail to accomplish tasks | Scripts work but have limitations | Scripts work flawlessly for all use cases |\n");
-            rubric_content.push_str("| Readability | Poorly commented and structured | Adequate comments and structure | Well-commented, clear structure |\n");
-            rubric_content.push_str("| Error Handling | Little or no error handling | Basic error handling | Comprehensive error handling with helpful messages |\n");
-            rubric_content.push_str("\n");
-        }
-        
-        // Add references to hallucination issues
-        rubric_content.push_str("## Note on Documentation Management\n\n");
-        rubric_content.push_str("Research has shown that having too many redundant documentation files can lead to confusion and AI hallucinations when used as reference material. This rubric is generated as part of an effort to consolidate and organize project documentation.\n\n");
-        rubric_content.push_str("### References\n\n");
-        rubric_content.push_str("- Hallucination in Large Language Models: [https://arxiv.org/abs/2309.01219](https://arxiv.org/abs/2309.01219)\n");
-        rubric_content.push_str("- The Impact of Contradictory Data on AI Training: [https://www.nature.com/articles/s41467-023-42879-y](https://www.nature.com/articles/s41467-023-42879-y)\n");
-        
-        // Save rubric to file
-        let rubric_path = base_dir.join("COMPREHENSIVE_PROJECT_RUBRIC.md");
-        let mut file = File::create(&rubric_path)?;
-        file.write_all(rubric_content.as_bytes())?;
-        
-        if verbose {
-            println!(
-                "{} {}",
-                "Created:".green().bold(),
-                rubric_path.display().to_string().green()
-            );
-        }
-        
-        Ok(())
-    }
-}
-
-/// Keep important files and move others to trash
-fn keep_important_files(
-    dir_path: &Path,
-    recursive: bool,
-    verbose: bool,
-) -> Result<()> {
-    // Find all markdown and shell files
-    let file_paths = if recursive {
-        WalkDir::new(dir_path)
-            .into_iter()
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| entry.file_type().is_file())
-            .filter(|entry| {
-                let path = entry.path();
-                let ext = path.extension().and_then(|ext| ext.to_str());
-                matches!(ext, Some("md") | Some("sh"))
-            })
-            .map(|entry| entry.path().to_path_buf())
-            .collect::<Vec<_>>()
-    } else {
-        fs::read_dir(dir_path)
-            .context("Failed to read directory")?
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| entry.file_type().map(|ft| ft.is_file()).unwrap_or(false))
-            .map(|entry| entry.path())
-            .filter(|path| {
-                let ext = path.extension().and_then(|ext| ext.to_str());
-                matches!(ext, Some("md") | Some("sh"))
-            })
-            .collect::<Vec<_>>()
-    };
-    
-    let total_files = file_paths.len();
-    
-    println!(
-        "{} {} files in {}",
-        "Found".cyan().bold(),
-        total_files.to_string().yellow().bold(),
-        dir_path.display().to_string().green(),
-    );
-    
-    if total_files == 0 {
-        println!("{} No files to process", "Warning:".yellow().bold());
-        return Ok(());
-    }
-    
-    // Create and run the analysis
-    let mut analysis = KeepAnalysis::new();
-    analysis.evaluate_files(&file_paths, verbose)?;
-    
-    // Generate statistics
-    let important_count = analysis.important_files.len();
-    let redundant_count = analysis.redundant_files.len();
-    
-    println!("\n{}", "📊 Analysis Results".cyan().bold());
-    println!("  {} {}", "Files to keep:".green(), important_count);
-    println!("  {} {}", "Files to move to trash:".yellow(), redundant_count);
-    
-    // Confirm with the user
-    print!("\n{} This will move {} files to the trash bin. Continue? (y/N) ", 
-        "⚠️".yellow().bold(), 
-        redundant_count.to_string().yellow().bold()
-    );
-    io::stdout().flush()?;
-    
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-    
-    if !matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
-        println!("{} Operation cancelled", "Info:".blue().bold());
-        return Ok(());
-    }
-    
-    // Move redundant files to trash
-    analysis.move_to_trash(verbose)?;
-    
-    // Generate comprehensive rubric
-    analysis.generate_comprehensive_rubric(dir_path, verbose)?;
-    
-    // Print summary
-    println!("\n{}", "📊 Summary".cyan().bold());
-    println!("  {} {}", "Files kept:".green(), important_count);
-    println!("  {} {}", "Files moved to trash:".yellow(), redundant_count);
-    println!(
-        "  {} {}",
-        "Trash location:".bright_black(),
-        analysis.trash_dir.display().to_string().bright_black()
-    );
-    println!("  {} The trash bin will be automatically deleted when you close its terminal window", 
-        "Note:".blue().bold()
-    );
-    
-    Ok(())
-}
-
-fn main() -> Result<()> {
-    let cli = Cli::parse();
-    
-    match cli.command {
-        Commands::Clean {
+        Commands::Keep {
             path,
             recursive,
-            restructure,
-            dry_run,
+            max_depth,
             verbose,
+            dedupe_ignore_trailing_report_sections,
+            classify_report_vs_summary_by_structure,
+            recency_weight,
+            size_weight,
+            quality_weight,
+            trash_dir,
+            auto_purge,
+            trash_to_system,
+            keep_recent,
+            keep_last,
+            keep_all,
+            thin_by,
+            dedup_only,
+            exclude_content,
+            name,
+            name_regex,
+            dedupe_cross_extension,
+            dedupe_keep,
+            dedupe_keep_annotated,
+            max_content_bytes_for_keywords,
+            keyword_weighting,
+            max_keywords_per_file,
+            rubric_format,
+            stopwords_file,
         } => {
             let dir_path = path.unwrap_or_else(|| PathBuf::from("."));
             
             println!(
                 "{} {}",
                 "Maid".bright_cyan().bold(),
-                "is cleaning up your AI-generated files...".bright_white()
+                "is keeping your important files safe...".bright_white()
             );
             
             if !dir_path.exists() {
-                anyhow::bail!("Directory does not exist: {}", dir_path.display());
+                return Err(MaidError::DirectoryNotFound(dir_path).into());
             }
             
             if !dir_path.is_dir() {
-                anyhow::bail!("Not a directory: {}", dir_path.display());
+                return Err(MaidError::NotADirectory(dir_path).into());
             }
             
-            clean_directory(&dir_path, recursive, restructure, dry_run, verbose)?;
+            let options = KeepOptions {
+                recursive,
+                max_depth,
+                verbose,
+                dedupe_ignore_trailing_report_sections,
+                classify_report_vs_summary_by_structure,
+                recency_weight,
+                size_weight,
+                quality_weight,
+                trash_dir,
+                auto_purge,
+                trash_to_system,
+                keep_recent,
+                keep_last,
+                keep_all,
+                thin_by,
+                dedup_only,
+                exclude_content,
+                name,
+                name_regex,
+                dedupe_cross_extension,
+                dedupe_keep,
+                dedupe_keep_annotated,
+                max_content_bytes_for_keywords,
+                keyword_weighting,
+                max_keywords_per_file,
+                rubric_format,
+                stopwords_file,
+            };
+            
+            outcome = keep_important_files(&dir_path, &options)?;
             
             println!(
                 "\n{} {} {}\n",
                 "✨".bright_yellow(),
-                "Cleaning complete!".green().bold(),
+                "Operation complete!".green().bold(),
                 "✨".bright_yellow()
             );
         }
-        Commands::Keep {
+        Commands::Undo {
             path,
-            recursive,
+            dry_run,
             verbose,
         } => {
             let dir_path = path.unwrap_or_else(|| PathBuf::from("."));
@@ -1226,27 +856,56 @@ fn main() -> Result<()> {
             println!(
                 "{} {}",
                 "Maid".bright_cyan().bold(),
-                "is keeping your important files safe...".bright_white()
+                "is putting your files back...".bright_white()
             );
             
-            if !dir_path.exists() {
-                anyhow::bail!("Directory does not exist: {}", dir_path.display());
-            }
-            
             if !dir_path.is_dir() {
-                anyhow::bail!("Not a directory: {}", dir_path.display());
+                return Err(MaidError::NotADirectory(dir_path).into());
             }
             
-            keep_important_files(&dir_path, recursive, verbose)?;
+            undo_last_run(&dir_path, dry_run, verbose)?;
+        }
+        Commands::Restore { from, file } => {
+            println!(
+                "{} {}",
+                "Maid".bright_cyan().bold(),
+                "is fishing your files out of the trash...".bright_white()
+            );
             
+            restore_from_trash(from, file.as_deref())?;
+        }
+        Commands::ListTrash { purge_older_than } => {
             println!(
-                "\n{} {} {}\n",
-                "✨".bright_yellow(),
-                "Operation complete!".green().bold(),
-                "✨".bright_yellow()
+                "{} {}",
+                "Maid".bright_cyan().bold(),
+                "is checking for leftover trash...".bright_white()
             );
+            
+            list_trash(purge_older_than)?;
+        }
+        Commands::Stats { path, recursive } => {
+            let dir_path = path.unwrap_or_else(|| PathBuf::from("."));
+            
+            println!(
+                "{} {}",
+                "Maid".bright_cyan().bold(),
+                "is taking stock of your files...".bright_white()
+            );
+            
+            if !dir_path.is_dir() {
+                return Err(MaidError::NotADirectory(dir_path).into());
+            }
+            
+            print_stats(&dir_path, recursive)?;
+        }
+        Commands::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "maid", &mut io::stdout());
+        }
+        Commands::Config { action: ConfigAction::Init { global, force } } => {
+            let path = init_config(Path::new("."), global, force)?;
+            println!("{} {}", "Wrote".green(), path.display());
         }
     }
     
-    Ok(())
+    Ok(outcome)
 }
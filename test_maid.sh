@@ -91,11 +91,11 @@ fi
 
 # Test 1: Basic clean
 echo "Test 1: Basic clean"
-cd "$TEST_DIR" && maid clean --verbose
+cd "$TEST_DIR" && maid clean --yes --verbose
 
 # Test 2: Clean with restructure
 echo "Test 2: Clean with restructure"
-cd "$TEST_DIR" && maid clean --restructure --verbose
+cd "$TEST_DIR" && maid clean --yes --restructure --verbose
 
 # Test 3: Dry run
 echo "Test 3: Dry run"
@@ -105,6 +105,1468 @@ cd "$TEST_DIR" && maid clean --dry-run --verbose
 echo "Test 4: Keep command"
 cd "$TEST_DIR" && maid keep --verbose
 
+# Test 5: Dry-run plan export matches the real run
+echo "Test 5: Dry-run plan export"
+PLAN_DIR="$TEST_DIR/plan-check"
+mkdir -p "$PLAN_DIR"
+printf '# Q3 Status Report\n\nAll milestones met.\n' > "$PLAN_DIR/STATUS_REPORT_Q3.md"
+printf '#!/bin/bash\nnpm test\n' > "$PLAN_DIR/run_tests.sh"
+cd "$PLAN_DIR" && maid clean --restructure --dry-run --dry-run-json-to plan.json
+[ ! -d "$PLAN_DIR/docs" ] || { echo "❌ Dry run created directories"; exit 1; }
+cd "$PLAN_DIR" && maid clean --yes --restructure
+grep -o '"target": "[^"]*"' "$PLAN_DIR/plan.json" | cut -d'"' -f4 | while read -r target; do
+    [ -e "$PLAN_DIR/$target" ] || { echo "❌ Planned target missing: $target"; exit 1; }
+done
+
+# Test 6: Shared classification cache is reused across directories
+echo "Test 6: Shared classification cache"
+CACHE_DIR="$TEST_DIR/cache-check"
+mkdir -p "$CACHE_DIR/sub"
+printf '# Summary\n\nNotes.\n' > "$CACHE_DIR/sub/NOTES_SUMMARY.md"
+cd "$CACHE_DIR" && maid clean --path sub --cache-file "$CACHE_DIR/cache.json" --dry-run
+cd "$CACHE_DIR" && maid clean --recursive --cache-file "$CACHE_DIR/cache.json" --dry-run | grep -q "Cache hits: 1" \
+    || { echo "❌ Second run did not reuse the cached classification"; exit 1; }
+
+# Test 7: Move mode relocates files and prunes emptied dirs only when asked
+echo "Test 7: Move with and without --prune-empty"
+MOVE_DIR="$TEST_DIR/move-check"
+mkdir -p "$MOVE_DIR/notes"
+printf '# Summary\n\nNotes.\n' > "$MOVE_DIR/notes/NOTES_SUMMARY.md"
+cd "$MOVE_DIR" && maid clean --yes --recursive --restructure --move
+[ ! -e "$MOVE_DIR/notes/NOTES_SUMMARY.md" ] || { echo "❌ Source was not moved"; exit 1; }
+[ -d "$MOVE_DIR/notes" ] || { echo "❌ Emptied dir was pruned without --prune-empty"; exit 1; }
+mkdir -p "$MOVE_DIR/more-notes"
+printf '# Summary\n\nMore notes.\n' > "$MOVE_DIR/more-notes/MORE_SUMMARY.md"
+cd "$MOVE_DIR" && maid clean --yes --recursive --restructure --move --prune-empty
+[ ! -d "$MOVE_DIR/more-notes" ] || { echo "❌ Emptied dir was not pruned"; exit 1; }
+
+# Test 8: Scripts failing the syntax check are routed to needs-review
+echo "Test 8: Shell syntax verification"
+SYNTAX_DIR="$TEST_DIR/syntax-check"
+mkdir -p "$SYNTAX_DIR"
+printf '#!/bin/bash\nif then fi (\n' > "$SYNTAX_DIR/broken_setup.sh"
+cd "$SYNTAX_DIR" && maid clean --yes --restructure --verify-shell-syntax
+ls "$SYNTAX_DIR/scripts/needs-review/"*.sh > /dev/null 2>&1 \
+    || { echo "❌ Broken script was not routed to needs-review"; exit 1; }
+
+# Test 9: A cumulative report supersedes the report it extends
+echo "Test 9: Superset report deduplication"
+REPORT_DIR="$TEST_DIR/report-check"
+mkdir -p "$REPORT_DIR"
+printf '# Status Report\n\nDone A.\n\n## Update: day 2\n\nDone B.\n' > "$REPORT_DIR/REPORT_FULL.md"
+sleep 1
+printf '# Status Report\n\nDone A.\n' > "$REPORT_DIR/REPORT_PARTIAL.md"
+# The self-destruct terminal may be unavailable in headless environments
+echo y | maid keep --path "$REPORT_DIR" --dedupe-ignore-trailing-report-sections || true
+[ -e "$REPORT_DIR/REPORT_FULL.md" ] || { echo "❌ Superset report was trashed"; exit 1; }
+[ ! -e "$REPORT_DIR/REPORT_PARTIAL.md" ] || { echo "❌ Subset report was kept"; exit 1; }
+
+# Test 10: Undo reverses the last Clean run
+echo "Test 10: Undo"
+UNDO_DIR="$TEST_DIR/undo-check"
+mkdir -p "$UNDO_DIR/notes"
+printf '# Summary\n\nNotes.\n' > "$UNDO_DIR/notes/NOTES_SUMMARY.md"
+cd "$UNDO_DIR" && maid clean --yes --recursive --restructure --move
+cd "$UNDO_DIR" && maid undo
+[ -e "$UNDO_DIR/notes/NOTES_SUMMARY.md" ] || { echo "❌ Undo did not restore the moved file"; exit 1; }
+
+# Test 11: Bash completions cover the subcommands
+echo "Test 11: Shell completions"
+COMPLETIONS="$(maid completions bash)"
+for subcommand in clean keep; do
+    echo "$COMPLETIONS" | grep -qw "$subcommand" || { echo "❌ Completions missing $subcommand"; exit 1; }
+done
+
+# Test 12: Ranking weights change which rubric is kept
+echo "Test 12: Keep ranking weights"
+WEIGHT_DIR="$TEST_DIR/weight-check"
+mkdir -p "$WEIGHT_DIR"
+printf '# Rubric\n\nOne two three four five six seven eight.\n' > "$WEIGHT_DIR/BIG_RUBRIC.md"
+sleep 1
+printf '# Rubric\n\nShort.\n' > "$WEIGHT_DIR/NEW_RUBRIC.md"
+echo n | maid keep --path "$WEIGHT_DIR" --verbose | grep -q "Keeping: .*BIG_RUBRIC.md" \
+    || { echo "❌ Default weights did not keep the largest rubric"; exit 1; }
+echo n | maid keep --path "$WEIGHT_DIR" --verbose --recency-weight 1 --size-weight 0 | grep -q "Keeping: .*NEW_RUBRIC.md" \
+    || { echo "❌ Recency weight did not keep the newest rubric"; exit 1; }
+
+# Test 13: Symlinks to the same file are processed once
+echo "Test 13: Symlink aliases"
+ALIAS_DIR="$TEST_DIR/alias-check"
+mkdir -p "$ALIAS_DIR/links"
+printf '# Guide\n\nSteps.\n' > "$ALIAS_DIR/REAL_GUIDE.md"
+ln -s "$ALIAS_DIR/REAL_GUIDE.md" "$ALIAS_DIR/links/first.md"
+ln -s "$ALIAS_DIR/REAL_GUIDE.md" "$ALIAS_DIR/links/second.md"
+cd "$ALIAS_DIR/links" && maid clean --dry-run --dry-run-json-to "$ALIAS_DIR/plan.json"
+[ "$(grep -c '"source"' "$ALIAS_DIR/plan.json")" -eq 1 ] || { echo "❌ Aliased file was processed more than once"; exit 1; }
+
+# Test 14: A custom trash directory receives discarded files
+echo "Test 14: Custom trash directory"
+TRASH_DIR="$TEST_DIR/trash-check"
+mkdir -p "$TRASH_DIR/project"
+printf '# Rubric\n\nOne two three.\n' > "$TRASH_DIR/project/FULL_RUBRIC.md"
+printf '# Rubric\n\nOne.\n' > "$TRASH_DIR/project/SHORT_RUBRIC.md"
+echo y | maid keep --path "$TRASH_DIR/project" --trash-dir "$TRASH_DIR/trash"
+[ -e "$TRASH_DIR/trash/SHORT_RUBRIC.md" ] || { echo "❌ Discarded file not in custom trash"; exit 1; }
+[ ! -e "$TRASH_DIR/trash/self_destruct.sh" ] || { echo "❌ Custom trash should not self-destruct"; exit 1; }
+
+# Test 15: Path separators in titles don't leak into generated names
+echo "Test 15: Title sanitization"
+TITLE_DIR="$TEST_DIR/title-check"
+mkdir -p "$TITLE_DIR"
+printf '# Guide\n\nPipelines.\n' > "$TITLE_DIR/CI\\CD_GUIDE.md"
+cd "$TITLE_DIR" && maid clean --yes
+[ -e "$TITLE_DIR/Guide - Ci-cd Guide.md" ] || { echo "❌ Backslash was not sanitized"; exit 1; }
+
+# Test 16: Interactive corrections are saved as maid.toml rules
+echo "Test 16: Interactive classification"
+CLASSIFY_DIR="$TEST_DIR/classify-check"
+mkdir -p "$CLASSIFY_DIR"
+printf 'Some notes.\n' > "$CLASSIFY_DIR/notes.md"
+cd "$CLASSIFY_DIR" && printf '3\n\n' | maid clean --interactive-classify --dry-run
+grep -q 'pattern = "notes.md"' "$CLASSIFY_DIR/maid.toml" && grep -q 'kind = "Guide"' "$CLASSIFY_DIR/maid.toml" \
+    || { echo "❌ Correction was not saved to maid.toml"; exit 1; }
+
+# Test 17: Rolling retention thins summaries outside the recent window
+echo "Test 17: Rolling retention"
+RETENTION_DIR="$TEST_DIR/retention-check"
+mkdir -p "$RETENTION_DIR"
+printf '# Summary\n\nMonday.\n' > "$RETENTION_DIR/MONDAY_SUMMARY.md"
+sleep 1
+printf '# Summary\n\nTuesday.\n' > "$RETENTION_DIR/TUESDAY_SUMMARY.md"
+echo n | maid keep --path "$RETENTION_DIR" --verbose --keep-recent 2w | grep -c "recent summary" | grep -q 2 \
+    || { echo "❌ Recent summaries were not all kept"; exit 1; }
+echo n | maid keep --path "$RETENTION_DIR" --verbose --keep-recent 0d --thin-by week | grep -q "Discarding: .*MONDAY_SUMMARY.md" \
+    || { echo "❌ Older summary in the same week was not thinned"; exit 1; }
+
+# Test 18: Content duplicates are removed regardless of whitespace differences
+echo "Test 18: Content-hash deduplication"
+DEDUP_DIR="$TEST_DIR/dedup-check"
+mkdir -p "$DEDUP_DIR"
+printf 'Release checklist\n\nTag the build.\n' > "$DEDUP_DIR/notes.md"
+printf 'Release checklist  \r\n\r\nTag the build.\r\n\r\n' > "$DEDUP_DIR/release_checklist_notes.md"
+echo n | maid keep --path "$DEDUP_DIR" --verbose --dedup-only | grep -q "Discarding: .*/notes.md (duplicate of .*release_checklist_notes.md)" \
+    || { echo "❌ Duplicate content was not detected"; exit 1; }
+
+# Test 19: Restore moves trashed files back to their original location
+echo "Test 19: Restore from trash"
+RESTORE_DIR="$TEST_DIR/restore-check"
+mkdir -p "$RESTORE_DIR/project"
+printf '# Rubric\n\nOne two three.\n' > "$RESTORE_DIR/project/FULL_RUBRIC.md"
+printf '# Rubric\n\nOne.\n' > "$RESTORE_DIR/project/SHORT_RUBRIC.md"
+echo y | maid keep --path "$RESTORE_DIR/project" --trash-dir "$RESTORE_DIR/trash"
+[ ! -e "$RESTORE_DIR/project/SHORT_RUBRIC.md" ] || { echo "❌ Discarded file was not trashed"; exit 1; }
+maid restore --from "$RESTORE_DIR/trash" --file SHORT_RUBRIC.md
+[ -e "$RESTORE_DIR/project/SHORT_RUBRIC.md" ] || { echo "❌ File was not restored"; exit 1; }
+
+# Test 20: Refusal and error output is flagged as non-content
+echo "Test 20: Error output detection"
+REFUSAL_DIR="$TEST_DIR/refusal-check"
+mkdir -p "$REFUSAL_DIR"
+printf "I'm sorry, but I cannot help with that request.\n" > "$REFUSAL_DIR/API_GUIDE.md"
+printf '# Guide\n\nInstall the tool.\n' > "$REFUSAL_DIR/INSTALL_GUIDE.md"
+echo n | maid keep --path "$REFUSAL_DIR" --verbose | grep -q "Discarding: .*API_GUIDE.md (non-content/error output)" \
+    || { echo "❌ Refusal output was not flagged"; exit 1; }
+maid clean --yes --path "$REFUSAL_DIR" --skip-error-output --verbose | grep -q "Skipping: .*API_GUIDE.md - non-content/error output" \
+    || { echo "❌ Clean did not skip refusal output"; exit 1; }
+
+# Test 21: A custom report template is filled with run data
+echo "Test 21: Report template"
+REPORT_DIR="$TEST_DIR/report-check"
+mkdir -p "$REPORT_DIR/project"
+printf '# Guide\n\nSteps.\n' > "$REPORT_DIR/project/SETUP_GUIDE.md"
+printf '# Notes\n\nNothing.\n' > "$REPORT_DIR/project/notes.md"
+printf 'Run in {{ mode }}: {{total_files}} found, {{ markdown }} markdown\n' > "$REPORT_DIR/template.md"
+maid clean --path "$REPORT_DIR/project" --dry-run --report "$REPORT_DIR/report.md" --output-report-template "$REPORT_DIR/template.md"
+grep -qx "Run in dry run: 2 found, 2 markdown" "$REPORT_DIR/report.md" \
+    || { echo "❌ Report template was not rendered correctly"; exit 1; }
+printf '{{ not_a_field }}\n' > "$REPORT_DIR/bad-template.md"
+if maid clean --path "$REPORT_DIR/project" --dry-run --report "$REPORT_DIR/bad.md" --output-report-template "$REPORT_DIR/bad-template.md" 2>/dev/null; then
+    echo "❌ Unknown placeholder should fail"; exit 1
+fi
+
+# Test 22: Keywords from maid.toml extend the built-in classification
+echo "Test 22: Configured keywords"
+KEYWORD_DIR="$TEST_DIR/keyword-check"
+mkdir -p "$KEYWORD_DIR"
+printf '# Incident\n\nThe database went down.\n' > "$KEYWORD_DIR/db_postmortem.md"
+printf '[keywords.Report]\nfilename = ["postmortem"]\n' > "$KEYWORD_DIR/maid.toml"
+maid clean --path "$KEYWORD_DIR" --dry-run --verbose | grep -q "Type: Report" \
+    || { echo "❌ Configured keyword was not used"; exit 1; }
+printf '[keywords.Report\n' > "$KEYWORD_DIR/maid.toml"
+maid clean --path "$KEYWORD_DIR" --dry-run 2>&1 | grep -q "Failed to parse" \
+    || { echo "❌ Invalid maid.toml did not produce a clear error"; exit 1; }
+
+# Test 23: A single file is processed without touching its siblings
+echo "Test 23: Single-file clean"
+SINGLE_DIR="$TEST_DIR/single-check"
+mkdir -p "$SINGLE_DIR/notes"
+printf '# Guide\n\nSteps.\n' > "$SINGLE_DIR/notes/SETUP_GUIDE.md"
+printf '# Rubric\n\nCriteria.\n' > "$SINGLE_DIR/notes/GRADING_RUBRIC.md"
+maid clean --yes --path "$SINGLE_DIR/notes/SETUP_GUIDE.md" --base-dir "$SINGLE_DIR" --restructure
+ls "$SINGLE_DIR/docs/guides/"*.md > /dev/null 2>&1 || { echo "❌ Single file was not routed under the base dir"; exit 1; }
+[ ! -d "$SINGLE_DIR/docs/rubrics" ] || { echo "❌ Sibling file should not have been processed"; exit 1; }
+
+# Test 24: Copies keep the original modification time unless opted out
+echo "Test 24: Preserve timestamps"
+STAMP_DIR="$TEST_DIR/timestamp-check"
+mkdir -p "$STAMP_DIR"
+printf '# Guide\n\nSteps.\n' > "$STAMP_DIR/SETUP_GUIDE.md"
+touch -t 202001020304 "$STAMP_DIR/SETUP_GUIDE.md"
+maid clean --yes --path "$STAMP_DIR"
+STAMP_COPY=$(ls "$STAMP_DIR"/Guide*.md)
+[ ! "$STAMP_COPY" -nt "$STAMP_DIR/SETUP_GUIDE.md" ] || { echo "❌ Copy did not keep the original timestamp"; exit 1; }
+rm "$STAMP_COPY"
+maid clean --yes --path "$STAMP_DIR" --preserve-timestamps false
+[ "$STAMP_COPY" -nt "$STAMP_DIR/SETUP_GUIDE.md" ] || { echo "❌ Opting out should use the current time"; exit 1; }
+# Restructured markdown is rewritten after a move, which must not reset its time either
+STAMP_MOVE_DIR="$TEST_DIR/timestamp-move-check"
+mkdir -p "$STAMP_MOVE_DIR"
+printf '# Guide\n\n\n\n\nSteps.\n' > "$STAMP_MOVE_DIR/SETUP_GUIDE.md"
+touch -t 202001020304 "$STAMP_MOVE_DIR/SETUP_GUIDE.md" "$TEST_DIR/timestamp-reference"
+maid clean --yes --path "$STAMP_MOVE_DIR" --move --restructure > /dev/null
+STAMP_MOVED=$(find "$STAMP_MOVE_DIR/docs" -name 'Guide*.md')
+[ -n "$STAMP_MOVED" ] && [ ! "$STAMP_MOVED" -nt "$TEST_DIR/timestamp-reference" ] \
+    || { echo "❌ A restructured move did not keep the original timestamp"; exit 1; }
+
+# Test 25: Target conflicts are skipped, renamed or overwritten on request
+echo "Test 25: Conflict strategies"
+CONFLICT_DIR="$TEST_DIR/conflict-check"
+mkdir -p "$CONFLICT_DIR"
+printf '# Guide\n\nNew steps.\n' > "$CONFLICT_DIR/SETUP_GUIDE.md"
+printf 'Old steps.\n' > "$CONFLICT_DIR/Guide - Install Guide.md"
+maid clean --yes --path "$CONFLICT_DIR"
+grep -q "Old steps" "$CONFLICT_DIR/Guide - Install Guide.md" || { echo "❌ Default strategy should skip"; exit 1; }
+maid clean --yes --path "$CONFLICT_DIR" --on-conflict rename
+grep -q "New steps" "$CONFLICT_DIR/Guide - Install Guide (2).md" || { echo "❌ Rename strategy did not add a suffix"; exit 1; }
+maid clean --yes --path "$CONFLICT_DIR" --on-conflict overwrite
+grep -q "New steps" "$CONFLICT_DIR/Guide - Install Guide.md" || { echo "❌ Overwrite strategy did not replace the target"; exit 1; }
+
+# Test 26: Files with an opt-out marker are skipped by both commands
+echo "Test 26: Exclude by content"
+OPTOUT_DIR="$TEST_DIR/optout-check"
+mkdir -p "$OPTOUT_DIR"
+printf '# Rubric\n\nOne two three.\n' > "$OPTOUT_DIR/FULL_RUBRIC.md"
+printf '# Rubric\n\nDO NOT ORGANIZE\n' > "$OPTOUT_DIR/SHORT_RUBRIC.md"
+maid clean --path "$OPTOUT_DIR" --dry-run --verbose --exclude-content "DO NOT ORGANIZE" | grep -q "Skipping: .*SHORT_RUBRIC.md - content matches excluded pattern" \
+    || { echo "❌ Clean did not skip the opted-out file"; exit 1; }
+echo n | maid keep --path "$OPTOUT_DIR" --verbose --exclude-content "DO NOT ORGANIZE" | grep -q "Keeping: .*SHORT_RUBRIC.md (content matches excluded pattern)" \
+    || { echo "❌ Keep did not keep the opted-out file"; exit 1; }
+
+# Test 27: Each document kind is printed in its own color
+echo "Test 27: Kind colors"
+COLOR_DIR="$TEST_DIR/color-check"
+mkdir -p "$COLOR_DIR"
+printf '# Rubric\n\nCriteria.\n' > "$COLOR_DIR/GRADING_RUBRIC.md"
+printf '# Report\n\nDone.\n' > "$COLOR_DIR/STATUS_REPORT.md"
+COLOR_OUTPUT=$(CLICOLOR_FORCE=1 maid clean --path "$COLOR_DIR" --dry-run --verbose)
+RUBRIC_CODE=$(echo "$COLOR_OUTPUT" | grep -o $'\e\\[[0-9;]*mRubric')
+REPORT_CODE=$(echo "$COLOR_OUTPUT" | grep -o $'\e\\[[0-9;]*mReport')
+[ -n "$RUBRIC_CODE" ] && [ "${RUBRIC_CODE%Rubric}" != "${REPORT_CODE%Report}" ] \
+    || { echo "❌ Kinds should be printed in different colors"; exit 1; }
+if CLICOLOR_FORCE=1 maid --no-color clean --path "$COLOR_DIR" --dry-run --verbose | grep -q $'\e\\['; then
+    echo "❌ --no-color should disable colors"; exit 1
+fi
+
+# Test 28: Changelogs keep their name and licenses are never touched
+echo "Test 28: Changelog and license detection"
+LEGAL_DIR="$TEST_DIR/legal-check"
+mkdir -p "$LEGAL_DIR"
+printf '# Changelog\n\n## [Unreleased]\n' > "$LEGAL_DIR/CHANGELOG.md"
+printf 'MIT License\n' > "$LEGAL_DIR/LICENSE.md"
+maid clean --yes --path "$LEGAL_DIR" --restructure --move
+[ -f "$LEGAL_DIR/docs/CHANGELOG.md" ] || { echo "❌ Changelog was not routed to docs/ unchanged"; exit 1; }
+[ -f "$LEGAL_DIR/LICENSE.md" ] || { echo "❌ License file should stay in place"; exit 1; }
+
+# Test 29: Rubric key terms come from the content read during analysis, within the byte cap
+echo "Test 29: Rubric keyword scanning"
+RUBRIC_DIR="$TEST_DIR/rubric-check"
+mkdir -p "$RUBRIC_DIR"
+printf '# Guide\n\nlighthouse lighthouse lighthouse\n\npineapple pineapple pineapple pineapple\n' > "$RUBRIC_DIR/SETUP_GUIDE.md"
+# Rewrite the file after analysis but before confirming; a second read would pick up "walrus"
+{ sleep 1; printf 'walrus walrus walrus\n' > "$RUBRIC_DIR/SETUP_GUIDE.md"; echo y; } \
+    | maid keep --path "$RUBRIC_DIR" --max-content-bytes-for-keywords 50
+grep -q "lighthouse" "$RUBRIC_DIR/COMPREHENSIVE_PROJECT_RUBRIC.md" || { echo "❌ Rubric is missing key terms"; exit 1; }
+! grep -q "walrus" "$RUBRIC_DIR/COMPREHENSIVE_PROJECT_RUBRIC.md" || { echo "❌ Files were read again during rubric generation"; exit 1; }
+! grep -q "pineapple" "$RUBRIC_DIR/COMPREHENSIVE_PROJECT_RUBRIC.md" || { echo "❌ Key terms were scanned past the byte cap"; exit 1; }
+
+# Test 30: Stats reports classifications without touching the directory
+echo "Test 30: Stats"
+STATS_DIR="$TEST_DIR/stats-check"
+mkdir -p "$STATS_DIR"
+printf '# Report\n\nDone.\n' > "$STATS_DIR/status_report.md"
+printf '# Rubric\n\nCriteria.\n' > "$STATS_DIR/GRADING_RUBRIC.md"
+STATS_BEFORE=$(ls -la "$STATS_DIR")
+maid stats --path "$STATS_DIR" | grep -q "status_report.md *Report *Report - Status Report.md" \
+    || { echo "❌ Stats did not list the planned name"; exit 1; }
+[ "$STATS_BEFORE" = "$(ls -la "$STATS_DIR")" ] || { echo "❌ Stats changed the directory"; exit 1; }
+
+# Test 31: A markdown file wrapping a script is a duplicate of the .sh file
+echo "Test 31: Cross-extension deduplication"
+CROSS_DIR="$TEST_DIR/cross-check"
+mkdir -p "$CROSS_DIR"
+printf '#!/bin/bash\napt-get install -y jq\necho done\n' > "$CROSS_DIR/install.sh"
+printf '# Install\n\nRun this:\n\n```bash\napt-get install -y jq\necho done\n```\n' > "$CROSS_DIR/install.md"
+echo n | maid keep --path "$CROSS_DIR" --verbose --dedupe-cross-extension | grep -q "Discarding: .*install.md (same script as .*install.sh)" \
+    || { echo "❌ Markdown-wrapped script was not deduplicated"; exit 1; }
+echo n | maid keep --path "$CROSS_DIR" --verbose --dedupe-cross-extension md | grep -q "Discarding: .*install.sh (same script as .*install.md)" \
+    || { echo "❌ Preferring markdown did not discard the script"; exit 1; }
+
+# Test 32: Generated names are stable when fed back in
+echo "Test 32: Idempotent renaming"
+RERUN_DIR="$TEST_DIR/rerun-check"
+mkdir -p "$RERUN_DIR"
+for name in status_report setup_guide project_summary grading_rubric test_runner; do
+    printf '# Notes\n\nSome text.\n' > "$RERUN_DIR/$name.md"
+done
+maid clean --yes --path "$RERUN_DIR" --move
+RERUN_FIRST=$(ls "$RERUN_DIR")
+maid clean --yes --path "$RERUN_DIR" --move
+[ "$RERUN_FIRST" = "$(ls "$RERUN_DIR")" ] || { echo "❌ Second run renamed files again"; exit 1; }
+if maid stats --path "$RERUN_DIR" | grep -q "Report - Report"; then
+    echo "❌ Kind prefix was applied twice"; exit 1
+fi
+
+# Test 33: --report-paths controls the path form in machine outputs
+echo "Test 33: Report path modes"
+PATHS_DIR="$TEST_DIR/paths-check"
+mkdir -p "$PATHS_DIR"
+printf '# Report\n\nDone.\n' > "$PATHS_DIR/status_report.md"
+maid clean --path "$PATHS_DIR" --restructure --dry-run --format json --report-paths absolute \
+    | grep -q "\"target\": \"$PATHS_DIR/docs/reports/Report - Status Report.md\"" \
+    || { echo "❌ Absolute mode did not print the full path"; exit 1; }
+maid clean --path "$PATHS_DIR" --restructure --dry-run --format json --report-paths relative \
+    | grep -q '"target": "docs/reports/Report - Status Report.md"' \
+    || { echo "❌ Relative mode did not print a path relative to the cleaned directory"; exit 1; }
+maid clean --path "$PATHS_DIR" --restructure --dry-run --format json --report-paths name-only \
+    | grep -q '"target": "Report - Status Report.md"' \
+    || { echo "❌ Name-only mode did not print just the file name"; exit 1; }
+
+# Test 34: --include-ext picks up extra extensions
+echo "Test 34: Include extra extensions"
+EXT_DIR="$TEST_DIR/ext-check"
+mkdir -p "$EXT_DIR"
+printf 'Meeting notes.\n' > "$EXT_DIR/weekly_status_report.txt"
+maid clean --yes --path "$EXT_DIR"
+[ ! -e "$EXT_DIR/Report - Weekly Status Report.txt" ] || { echo "❌ .txt files should be ignored by default"; exit 1; }
+maid clean --yes --path "$EXT_DIR" --include-ext .txt
+[ -e "$EXT_DIR/Report - Weekly Status Report.txt" ] || { echo "❌ Included extension was not renamed"; exit 1; }
+
+# Test 35: --restructure normalizes markdown and previews it as a diff
+echo "Test 35: Markdown restructuring diff"
+MD_DIR="$TEST_DIR/md-restructure"
+mkdir -p "$MD_DIR"
+printf '# Setup\n\nIntro.\n\n\n\n# Details\n\n```\n# kept\n```' > "$MD_DIR/SETUP_GUIDE.md"
+MD_DIFF=$(maid clean --path "$MD_DIR" --restructure --dry-run)
+echo "$MD_DIFF" | grep -q '^+## Details' || { echo "❌ Dry run did not show the restructuring diff"; exit 1; }
+[ -e "$MD_DIR/SETUP_GUIDE.md" ] || { echo "❌ Dry run changed files"; exit 1; }
+maid clean --yes --path "$MD_DIR" --restructure
+RESTRUCTURED=$(find "$MD_DIR/docs/guides" -name '*.md' | head -n 1)
+grep -q '^## Details' "$RESTRUCTURED" || { echo "❌ Later H1 was not demoted"; exit 1; }
+grep -q '^# kept' "$RESTRUCTURED" || { echo "❌ Fenced code was modified"; exit 1; }
+[ "$(grep -c '^$' "$RESTRUCTURED")" -eq 3 ] || { echo "❌ Blank lines were not collapsed"; exit 1; }
+
+# Test 36: A rubric generated by an earlier keep run doesn't compete with real ones
+echo "Test 36: Previously generated rubric"
+REGEN_DIR="$TEST_DIR/regen-check"
+mkdir -p "$REGEN_DIR/project"
+printf '# Grading Rubric\n\nCriteria for the deliverable.\n' > "$REGEN_DIR/project/GRADING_RUBRIC.md"
+{ printf '# Comprehensive Project Rubric\n\n*Generated by Maid - AI-generated file organizer*\n\nGenerated on: 2000-01-01\n\n'; for i in $(seq 1 50); do echo "Old criterion $i"; done; } > "$REGEN_DIR/project/COMPREHENSIVE_PROJECT_RUBRIC.md"
+REGEN_OUTPUT=$(echo y | maid keep --path "$REGEN_DIR/project" --trash-dir "$REGEN_DIR/trash" --verbose)
+echo "$REGEN_OUTPUT" | grep -q "Keeping: .*GRADING_RUBRIC.md (most comprehensive rubric" \
+    || { echo "❌ Generated rubric won the best-rubric choice"; exit 1; }
+[ -e "$REGEN_DIR/project/GRADING_RUBRIC.md" ] || { echo "❌ Project rubric was trashed"; exit 1; }
+if grep -q "2000-01-01" "$REGEN_DIR/project/COMPREHENSIVE_PROJECT_RUBRIC.md"; then
+    echo "❌ Generated rubric was not regenerated"; exit 1
+fi
+
+# Test 37: --trash-to-system uses the platform trash (only when built with it)
+if maid keep --help | grep -q -- "--trash-to-system"; then
+    echo "Test 37: System trash"
+    SYSTRASH_DIR="$TEST_DIR/systrash-check"
+    mkdir -p "$SYSTRASH_DIR/project"
+    printf '# Rubric\n\nShort.\n' > "$SYSTRASH_DIR/project/SHORT_RUBRIC.md"
+    printf '# Rubric\n\nA longer set of grading criteria.\n' > "$SYSTRASH_DIR/project/FULL_RUBRIC.md"
+    echo y | XDG_DATA_HOME="$SYSTRASH_DIR/data" maid keep --path "$SYSTRASH_DIR/project" --trash-to-system
+    [ -e "$SYSTRASH_DIR/data/Trash/files/SHORT_RUBRIC.md" ] || { echo "❌ File was not sent to the system trash"; exit 1; }
+    [ -e "$SYSTRASH_DIR/data/Trash/info/SHORT_RUBRIC.md.trashinfo" ] || { echo "❌ System trash has no restore info"; exit 1; }
+fi
+
+# Test 38: Binary and non-UTF-8 files are skipped with a reason
+echo "Test 38: Binary files"
+BINARY_DIR="$TEST_DIR/binary-check"
+mkdir -p "$BINARY_DIR"
+head -c 65536 /dev/urandom > "$BINARY_DIR/weekly_report.md"
+printf 'caf\xe9 status report\n' > "$BINARY_DIR/latin1_report.md"
+BINARY_OUTPUT=$(maid clean --yes --path "$BINARY_DIR" --verbose)
+echo "$BINARY_OUTPUT" | grep -q "Skipping: .*weekly_report.md - not a text file (contains NUL bytes)" \
+    || { echo "❌ Binary file was not skipped with a reason"; exit 1; }
+echo "$BINARY_OUTPUT" | grep -q "Skipping: .*latin1_report.md - not a text file (invalid UTF-8)" \
+    || { echo "❌ Non-UTF-8 file was not skipped with a reason"; exit 1; }
+echo "$BINARY_OUTPUT" | grep -q "Files skipped: 2" || { echo "❌ Skipped files were not counted"; exit 1; }
+
+# Test 39: Structure decides between report and summary keywords
+echo "Test 39: Report vs summary by structure"
+STRUCT_DIR="$TEST_DIR/structure-check"
+mkdir -p "$STRUCT_DIR"
+printf '# Status\n\nAll tasks are done.\n' > "$STRUCT_DIR/status_summary.md"
+printf '# Summary\n\n## Scope\n\nA.\n\n## Results\n\nB.\n\n## Risks\n\nC.\n\n## Next steps\n\nD.\n' > "$STRUCT_DIR/sprint_summary_status.md"
+maid clean --path "$STRUCT_DIR" --dry-run --verbose | grep -A1 "status_summary.md ->" | grep -q "Type: Report" \
+    || { echo "❌ Keyword order should make the short file a report by default"; exit 1; }
+STRUCT_OUTPUT=$(maid clean --path "$STRUCT_DIR" --dry-run --verbose --classify-report-vs-summary-by-structure)
+echo "$STRUCT_OUTPUT" | grep -A1 "/status_summary.md ->" | grep -q "Type: Summary" \
+    || { echo "❌ Short file with few sections was not classified as a summary"; exit 1; }
+echo "$STRUCT_OUTPUT" | grep -A1 "/sprint_summary_status.md ->" | grep -q "Type: Report" \
+    || { echo "❌ File with many sections was not classified as a report"; exit 1; }
+
+# Test 40: --max-depth limits how far a recursive run descends
+echo "Test 40: Max depth"
+DEPTH_DIR="$TEST_DIR/depth-check"
+mkdir -p "$DEPTH_DIR/one/two"
+printf '# Report\n\nTop.\n' > "$DEPTH_DIR/top_report.md"
+printf '# Report\n\nOne.\n' > "$DEPTH_DIR/one/one_report.md"
+printf '# Report\n\nTwo.\n' > "$DEPTH_DIR/one/two/two_report.md"
+DEPTH_OUTPUT=$(maid clean --path "$DEPTH_DIR" --recursive --max-depth 2 --dry-run --verbose)
+echo "$DEPTH_OUTPUT" | grep -q "one_report.md ->" || { echo "❌ File within the depth limit was not processed"; exit 1; }
+if echo "$DEPTH_OUTPUT" | grep -q "two_report.md"; then
+    echo "❌ File below the depth limit was processed"; exit 1
+fi
+DEPTH_OUTPUT=$(maid clean --path "$DEPTH_DIR" --max-depth 3 --dry-run --verbose)
+echo "$DEPTH_OUTPUT" | grep -q "Found 1 files" \
+    || { echo "❌ Depth should be 1 without --recursive"; exit 1; }
+
+# Test 41: An interrupted run is resumed from its journal without redoing moves
+echo "Test 41: Resume an interrupted run"
+RESUME_DIR="$TEST_DIR/resume-check"
+mkdir -p "$RESUME_DIR"
+for name in alpha beta gamma; do
+    printf '# Report\n\n%s\n' "$name" > "$RESUME_DIR/${name}_report.md"
+done
+maid clean --yes --path "$RESUME_DIR" --restructure --move
+RESUME_JOURNAL=$(ls "$RESUME_DIR"/.maid/history/*.jsonl)
+[ "$(wc -l < "$RESUME_JOURNAL")" -eq 3 ] || { echo "❌ Journal did not record each operation"; exit 1; }
+# Simulate an interruption before gamma was moved
+grep -v gamma "$RESUME_JOURNAL" > "$RESUME_JOURNAL.tmp" && mv "$RESUME_JOURNAL.tmp" "$RESUME_JOURNAL"
+mv "$RESUME_DIR/docs/reports/Report - Gamma Report.md" "$RESUME_DIR/gamma_report.md"
+RESUME_OUTPUT=$(maid clean --yes --path "$RESUME_DIR" --restructure --move --recursive --verbose --resume "$RESUME_JOURNAL")
+echo "$RESUME_OUTPUT" | grep -q "Skipping 2 files already done" || { echo "❌ Finished moves were not skipped"; exit 1; }
+if echo "$RESUME_OUTPUT" | grep -q "Processing: .*Alpha"; then
+    echo "❌ A finished move was redone"; exit 1
+fi
+[ -e "$RESUME_DIR/docs/reports/Report - Gamma Report.md" ] || { echo "❌ Resumed run did not finish the remaining move"; exit 1; }
+[ "$(wc -l < "$RESUME_JOURNAL")" -eq 3 ] || { echo "❌ Resumed run did not add to the journal"; exit 1; }
+maid undo --path "$RESUME_DIR"
+[ -e "$RESUME_DIR/alpha_report.md" ] && [ -e "$RESUME_DIR/gamma_report.md" ] \
+    || { echo "❌ Undo did not reverse the resumed run"; exit 1; }
+
+# Test 42: Keep writes an audit report of its decisions
+echo "Test 42: Keep report"
+AUDIT_DIR="$TEST_DIR/audit-check"
+mkdir -p "$AUDIT_DIR/project"
+printf '# Rubric\n\nShort.\n' > "$AUDIT_DIR/project/SHORT_RUBRIC.md"
+printf '# Rubric\n\nA longer set of grading criteria.\n' > "$AUDIT_DIR/project/FULL_RUBRIC.md"
+printf '# Guide\n\nSteps.\n' > "$AUDIT_DIR/project/setup_guide.md"
+cp "$AUDIT_DIR/project/setup_guide.md" "$AUDIT_DIR/project/guide.md"
+echo y | maid keep --path "$AUDIT_DIR/project" --trash-dir "$AUDIT_DIR/trash"
+AUDIT_REPORT="$AUDIT_DIR/project/maid-keep-report.json"
+[ -f "$AUDIT_REPORT" ] || { echo "❌ Keep report was not written"; exit 1; }
+grep -A4 '"path": ".*/FULL_RUBRIC.md"' "$AUDIT_REPORT" | grep -q '"reason": "most comprehensive rubric' \
+    || { echo "❌ Keep report has no reason for the kept rubric"; exit 1; }
+grep -A4 '"path": ".*/SHORT_RUBRIC.md"' "$AUDIT_REPORT" | tr -d '\n ' | grep -q '"doc_kind":"Rubric","word_count":3,"decision":"discarded"' \
+    || { echo "❌ Keep report has no kind, word count or decision for the discarded rubric"; exit 1; }
+grep -A4 '"path": ".*/guide.md"' "$AUDIT_REPORT" | grep -q '"reason": "duplicate of .*setup_guide.md"' \
+    || { echo "❌ Keep report has no reason for the duplicate"; exit 1; }
+
+# Test 43: Clean asks before changing anything unless given --yes
+echo "Test 43: Clean confirmation"
+CONFIRM_DIR="$TEST_DIR/confirm-check"
+mkdir -p "$CONFIRM_DIR"
+printf '# Report\n\nDone.\n' > "$CONFIRM_DIR/status_report.md"
+CONFIRM_OUTPUT=$(echo n | maid clean --path "$CONFIRM_DIR")
+echo "$CONFIRM_OUTPUT" | grep -q "This will carry out 1 planned operations" \
+    || { echo "❌ Clean did not show the planned operation count"; exit 1; }
+[ ! -e "$CONFIRM_DIR/Report - Status Report.md" ] || { echo "❌ Clean went ahead without confirmation"; exit 1; }
+CONFIRM_OUTPUT=$(maid clean --path "$CONFIRM_DIR" --dry-run < /dev/null)
+if echo "$CONFIRM_OUTPUT" | grep -q "Continue?"; then
+    echo "❌ Dry run asked for confirmation"; exit 1
+fi
+echo y | maid clean --path "$CONFIRM_DIR" > /dev/null
+[ -e "$CONFIRM_DIR/Report - Status Report.md" ] || { echo "❌ Clean did not go ahead after confirmation"; exit 1; }
+
+# Test 44: Data dictionaries and field tables are classified as Data
+echo "Test 44: Data descriptions"
+DATA_DIR="$TEST_DIR/data-check"
+mkdir -p "$DATA_DIR"
+printf '# Orders\n\nExported nightly.\n' > "$DATA_DIR/data_dictionary.md"
+printf '# Customers\n\n| Column | Type | Description |\n|--------|------|-------------|\n| id | int | Customer id |\n' > "$DATA_DIR/customers_status.md"
+printf '# Status report\n\n| Task | Owner |\n|------|-------|\n| Export | Sam |\n' > "$DATA_DIR/weekly_report.md"
+maid clean --yes --path "$DATA_DIR" --restructure
+[ -e "$DATA_DIR/docs/data/Data - Data Dictionary.md" ] || { echo "❌ data_dictionary.md was not classified as Data"; exit 1; }
+[ -e "$DATA_DIR/docs/data/Data - Customers Status.md" ] || { echo "❌ Field description table did not mark the file as Data"; exit 1; }
+[ -e "$DATA_DIR/docs/reports/Report - Weekly Report.md" ] || { echo "❌ Report with an ordinary table was classified as Data"; exit 1; }
+
+# Test 45: Duplicates at a shallower path are preferred
+echo "Test 45: Dedupe keeps the shortest path"
+SHALLOW_DIR="$TEST_DIR/shallow-check"
+mkdir -p "$SHALLOW_DIR/scratch/tmp/run1"
+printf '# Notes\n\nSame content.\n' > "$SHALLOW_DIR/release_notes.md"
+cp "$SHALLOW_DIR/release_notes.md" "$SHALLOW_DIR/scratch/tmp/run1/release_notes.md"
+SHALLOW_OUTPUT=$(echo n | maid keep --path "$SHALLOW_DIR" --recursive --dedup-only --verbose)
+echo "$SHALLOW_OUTPUT" | grep -q "Discarding: .*run1/release_notes.md (duplicate of $SHALLOW_DIR/release_notes.md)" \
+    || { echo "❌ Equally named duplicates did not fall back to the shortest path"; exit 1; }
+mv "$SHALLOW_DIR/scratch/tmp/run1/release_notes.md" "$SHALLOW_DIR/scratch/tmp/run1/final_release_notes.md"
+SHALLOW_OUTPUT=$(echo n | maid keep --path "$SHALLOW_DIR" --recursive --dedup-only --verbose --dedupe-keep shortest-path)
+echo "$SHALLOW_OUTPUT" | grep -q "Discarding: .*run1/final_release_notes.md (duplicate of $SHALLOW_DIR/release_notes.md)" \
+    || { echo "❌ --dedupe-keep shortest-path did not keep the shallower duplicate"; exit 1; }
+
+# Test 46: A concise, well-structured rubric beats a longer repetitive one
+echo "Test 46: Keep quality score"
+QUALITY_DIR="$TEST_DIR/quality-check"
+mkdir -p "$QUALITY_DIR"
+{
+    printf '# Rubric\n\n'
+    for i in $(seq 1 40); do printf 'The rubric is a rubric for the rubric and the rubric.\n'; done
+} > "$QUALITY_DIR/BLOATED_RUBRIC.md"
+cat > "$QUALITY_DIR/CONCISE_RUBRIC.md" << 'EOF'
+# Grading Rubric
+
+## Criteria
+
+| Criterion | Points | Description |
+|-----------|--------|-------------|
+| Correctness | 40 | Output matches the [specification](SPEC.md) |
+| Style | 20 | Follows the [style guide](STYLE.md) |
+
+## Running the checks
+
+```bash
+./run_checks.sh --all
+```
+
+## Submission
+
+Submit through the course portal before the deadline.
+EOF
+QUALITY_OUTPUT=$(echo n | maid keep --path "$QUALITY_DIR" --verbose)
+echo "$QUALITY_OUTPUT" | grep -q "Keeping: .*CONCISE_RUBRIC.md (most comprehensive rubric, quality [0-9.]*)" \
+    || { echo "❌ Quality score did not keep the well-structured rubric"; exit 1; }
+echo "$QUALITY_OUTPUT" | grep -q "BLOATED_RUBRIC.md (redundant rubric, quality [0-9.]*)" \
+    || { echo "❌ Verbose output did not show the discarded rubric's quality score"; exit 1; }
+
+# Test 47: --keep-all keeps every report instead of only the newest
+echo "Test 47: Keep all of a kind"
+KEEP_ALL_DIR="$TEST_DIR/keep-all-check"
+mkdir -p "$KEEP_ALL_DIR"
+printf '# Milestone 1 Report\n\nFirst milestone results.\n' > "$KEEP_ALL_DIR/MILESTONE_1_REPORT.md"
+printf '# Milestone 2 Report\n\nSecond milestone results.\n' > "$KEEP_ALL_DIR/MILESTONE_2_REPORT.md"
+KEEP_ALL_OUTPUT=$(echo n | maid keep --path "$KEEP_ALL_DIR" --verbose --keep-all reports)
+for report in MILESTONE_1_REPORT MILESTONE_2_REPORT; do
+    echo "$KEEP_ALL_OUTPUT" | grep -q "Keeping: .*$report.md (--keep-all report)" \
+        || { echo "❌ --keep-all did not keep $report"; exit 1; }
+done
+if maid keep --path "$KEEP_ALL_DIR" --keep-all memos > /dev/null 2>&1; then
+    echo "❌ --keep-all accepted an unknown kind"; exit 1
+fi
+
+# Test 48: --since-last-run only processes files newer than the stored timestamp
+echo "Test 48: Since last run"
+SINCE_DIR="$TEST_DIR/since-check"
+mkdir -p "$SINCE_DIR/.maid"
+printf '# Old Guide\n\nSteps.\n' > "$SINCE_DIR/OLD_GUIDE.md"
+touch -d "2 days ago" "$SINCE_DIR/OLD_GUIDE.md"
+date -d "1 day ago" --iso-8601=seconds > "$SINCE_DIR/.maid/last-run"
+printf '# New Guide\n\nSteps.\n' > "$SINCE_DIR/NEW_GUIDE.md"
+maid clean --path "$SINCE_DIR" --since-last-run --yes > /dev/null
+[ -e "$SINCE_DIR/Guide - New Guide.md" ] || { echo "❌ File newer than the last run was not processed"; exit 1; }
+[ ! -e "$SINCE_DIR/Guide - Old Guide.md" ] || { echo "❌ File older than the last run was processed"; exit 1; }
+if grep -q "$(date -d '1 day ago' +%Y-%m-%d)T" "$SINCE_DIR/.maid/last-run"; then
+    echo "❌ Last-run timestamp was not updated"; exit 1
+fi
+
+# Test 49: --stdin cleans only the listed files
+echo "Test 49: Paths from stdin"
+STDIN_DIR="$TEST_DIR/stdin-check"
+mkdir -p "$STDIN_DIR/sub"
+printf '# Listed Guide\n\nSteps.\n' > "$STDIN_DIR/sub/LISTED_GUIDE.md"
+printf '# Unlisted Guide\n\nSteps.\n' > "$STDIN_DIR/UNLISTED_GUIDE.md"
+printf 'plain text\n' > "$STDIN_DIR/notes.txt"
+STDIN_OUTPUT=$(cd "$STDIN_DIR" && printf 'sub/LISTED_GUIDE.md\nnotes.txt\ndeleted.md\n' | maid clean --stdin --yes)
+echo "$STDIN_OUTPUT" | grep -q "Total files found: 1" \
+    || { echo "❌ --stdin did not apply the extension filter"; exit 1; }
+[ -e "$STDIN_DIR/sub/Guide - Listed Guide.md" ] || { echo "❌ Listed file was not cleaned"; exit 1; }
+[ ! -e "$STDIN_DIR/Guide - Unlisted Guide.md" ] || { echo "❌ Unlisted file was cleaned"; exit 1; }
+if echo "sub/LISTED_GUIDE.md" | maid clean --path "$STDIN_DIR" --stdin > /dev/null 2>&1; then
+    echo "❌ --stdin ran without --yes or --dry-run"; exit 1
+fi
+
+# Test 50: list-trash lists temporary trash bins and purges stale ones
+echo "Test 50: List and purge trash bins"
+LIST_TRASH_TMP="$TEST_DIR/list-trash-tmp"
+OLD_BIN="$LIST_TRASH_TMP/maid-trash-bin-20000101_000000"
+NEW_BIN="$LIST_TRASH_TMP/maid-trash-bin-$(date +%Y%m%d_%H%M%S)"
+mkdir -p "$OLD_BIN" "$NEW_BIN"
+printf 'stale\n' > "$OLD_BIN/OLD_REPORT.md"
+LIST_TRASH_OUTPUT=$(TMPDIR="$LIST_TRASH_TMP" maid list-trash)
+echo "$LIST_TRASH_OUTPUT" | grep -q "2000-01-01 00:00:00 .*maid-trash-bin-20000101_000000 (1 files, 6 B)" \
+    || { echo "❌ list-trash did not describe the old bin"; exit 1; }
+TMPDIR="$LIST_TRASH_TMP" maid list-trash --purge-older-than 7d > /dev/null
+[ ! -e "$OLD_BIN" ] || { echo "❌ Stale trash bin was not purged"; exit 1; }
+[ -d "$NEW_BIN" ] || { echo "❌ Recent trash bin was purged"; exit 1; }
+
+# Test 51: --include-other renames other extensions and routes them to misc/
+echo "Test 51: Include other file types"
+OTHER_DIR="$TEST_DIR/other-check"
+mkdir -p "$OTHER_DIR"
+printf '{"status": "ok"}\n' > "$OTHER_DIR/api_response_sample.json"
+maid clean --yes --path "$OTHER_DIR" --restructure > /dev/null
+[ ! -e "$OTHER_DIR/misc" ] || { echo "❌ Other file types should be ignored by default"; exit 1; }
+maid clean --yes --path "$OTHER_DIR" --restructure --include-other > /dev/null
+[ -e "$OTHER_DIR/misc/Api Response Sample.json" ] || { echo "❌ --include-other did not route the .json file to misc/"; exit 1; }
+
+# Test 52: --report-kind-transitions lists files whose kind changed since a saved plan
+echo "Test 52: Kind transitions"
+TRANSITION_DIR="$TEST_DIR/transition-check"
+mkdir -p "$TRANSITION_DIR/project"
+printf '# Weekly Report\n\nResults.\n' > "$TRANSITION_DIR/project/STATUS_REPORT.md"
+printf '# Guide\n\nSteps.\n' > "$TRANSITION_DIR/project/SETUP_GUIDE.md"
+maid clean --path "$TRANSITION_DIR/project" --dry-run --dry-run-json-to "$TRANSITION_DIR/plan.json" > /dev/null
+printf '[[rules]]\npattern = "STATUS_*.md"\nkind = "Summary"\n' > "$TRANSITION_DIR/project/maid.toml"
+TRANSITION_OUTPUT=$(maid clean --path "$TRANSITION_DIR/project" --dry-run --report-kind-transitions "$TRANSITION_DIR/plan.json")
+echo "$TRANSITION_OUTPUT" | grep -q "STATUS_REPORT.md: Report -> Summary" \
+    || { echo "❌ Kind change was not reported"; exit 1; }
+if echo "$TRANSITION_OUTPUT" | grep -q "SETUP_GUIDE.md:"; then
+    echo "❌ Unchanged file was reported as a kind change"; exit 1
+fi
+
+# Test 53: Task lists stay in place under Clean and are always kept by Keep
+echo "Test 53: Task lists"
+TASK_DIR="$TEST_DIR/task-check"
+mkdir -p "$TASK_DIR"
+printf '# TODO\n\n- [ ] Write docs\n- [x] Fix build\n' > "$TASK_DIR/TODO.md"
+printf '# Next Steps\n\n- [ ] Ship it\n- [ ] Announce\n- [x] Test\n' > "$TASK_DIR/NEXT_STEPS.md"
+cp "$TASK_DIR/TODO.md" "$TASK_DIR/TASKS.md"
+maid clean --yes --path "$TASK_DIR" --restructure > /dev/null
+for task_list in TODO.md NEXT_STEPS.md; do
+    [ -e "$TASK_DIR/$task_list" ] || { echo "❌ Task list $task_list was moved"; exit 1; }
+done
+[ -z "$(find "$TASK_DIR" -mindepth 2 -name '*.md')" ] || { echo "❌ Clean restructured a task list"; exit 1; }
+TASK_OUTPUT=$(echo n | maid keep --path "$TASK_DIR" --verbose)
+for task_list in TODO.md TASKS.md NEXT_STEPS.md; do
+    echo "$TASK_OUTPUT" | grep -q "Keeping: .*/$task_list (protected TaskList)" \
+        || { echo "❌ Keep did not protect $task_list"; exit 1; }
+done
+
+# Test 54: --keyword-weighting stops one repetitive file dominating the key terms
+echo "Test 54: Keyword weighting"
+WEIGHTING_DIR="$TEST_DIR/weighting-check"
+mkdir -p "$WEIGHTING_DIR"
+{ printf '# Zebra Guide\n\n'; for i in $(seq 1 30); do printf 'zebrafish '; done; printf '\n'; } > "$WEIGHTING_DIR/ZEBRA_GUIDE.md"
+printf '# Deploy Guide\n\nThe pipeline handles deployment.\n' > "$WEIGHTING_DIR/DEPLOY_GUIDE.md"
+printf '# Release Guide\n\nEach pipeline run ends in deployment.\n' > "$WEIGHTING_DIR/RELEASE_GUIDE.md"
+top_key_term() {
+    grep -A2 "### Key Terms" "$WEIGHTING_DIR/COMPREHENSIVE_PROJECT_RUBRIC.md" | tail -1
+}
+echo y | maid keep --path "$WEIGHTING_DIR" --trash-dir "$WEIGHTING_DIR/trash" > /dev/null
+[ "$(top_key_term)" = "- zebrafish" ] || { echo "❌ Counting every occurrence should favor the repetitive file"; exit 1; }
+for weighting in presence capped; do
+    echo y | maid keep --path "$WEIGHTING_DIR" --trash-dir "$WEIGHTING_DIR/trash" --keyword-weighting "$weighting" --max-keywords-per-file 1 > /dev/null
+    [ "$(top_key_term)" != "- zebrafish" ] || { echo "❌ Repetitive file still dominates with --keyword-weighting $weighting"; exit 1; }
+done
+
+# Test 55: --log-file records operations and errors as JSON Lines
+echo "Test 55: Log file"
+LOG_DIR="$TEST_DIR/log-check"
+mkdir -p "$LOG_DIR/project"
+printf '# Guide\n\nSteps.\n' > "$LOG_DIR/project/SETUP_GUIDE.md"
+maid clean --yes --path "$LOG_DIR/project" --log-file "$LOG_DIR/maid.log" > /dev/null
+grep -q '"level":"INFO","message":"copy .*SETUP_GUIDE.md -> .*Guide - Install Guide.md (Guide)"' "$LOG_DIR/maid.log" \
+    || { echo "❌ Log file is missing the copy operation"; exit 1; }
+if maid clean --path "$LOG_DIR/missing" --log-file "$LOG_DIR/maid.log" --log-level error > /dev/null 2>&1; then
+    echo "❌ Cleaning a missing directory succeeded"; exit 1
+fi
+grep -q '"level":"ERROR","message":"Directory does not exist' "$LOG_DIR/maid.log" \
+    || { echo "❌ Log file is missing the error"; exit 1; }
+[ "$(grep -c '"level":"INFO"' "$LOG_DIR/maid.log")" -eq 2 ] \
+    || { echo "❌ --log-level error still logged info messages"; exit 1; }
+
+# Test 56: An unreadable confirmation answer cancels instead of aborting
+echo "Test 56: Unreadable confirmation"
+STDIN_FAIL_DIR="$TEST_DIR/stdin-fail-check"
+mkdir -p "$STDIN_FAIL_DIR"
+printf '# Report\n\nFirst.\n' > "$STDIN_FAIL_DIR/OLD_REPORT.md"
+sleep 1
+printf '# Report\n\nSecond.\n' > "$STDIN_FAIL_DIR/NEW_REPORT.md"
+# Bytes that aren't UTF-8 make reading the answer fail
+KEEP_FAIL_OUTPUT=$(printf '\377\n' | maid keep --path "$STDIN_FAIL_DIR") \
+    || { echo "❌ Keep aborted on an unreadable answer"; exit 1; }
+echo "$KEEP_FAIL_OUTPUT" | grep -q "Couldn't read an answer from stdin" \
+    || { echo "❌ Keep did not explain the unreadable answer"; exit 1; }
+CLEAN_FAIL_OUTPUT=$(printf '\377\n' | maid clean --path "$STDIN_FAIL_DIR") \
+    || { echo "❌ Clean aborted on an unreadable answer"; exit 1; }
+echo "$CLEAN_FAIL_OUTPUT" | grep -q "Operation cancelled" || { echo "❌ Clean was not cancelled"; exit 1; }
+[ "$(ls "$STDIN_FAIL_DIR")" = "$(printf 'NEW_REPORT.md\nOLD_REPORT.md')" ] \
+    || { echo "❌ Files changed after an unreadable answer"; exit 1; }
+
+# Test 57: Frontmatter type/kind overrides keyword detection and stays out of key terms
+echo "Test 57: Frontmatter kind"
+FRONT_DIR="$TEST_DIR/frontmatter-check"
+mkdir -p "$FRONT_DIR"
+printf -- '---\ntype: guide\ntags: [quasarmetadata, quasarmetadata, quasarmetadata]\n---\n# Notes\n\nHow we deploy.\n' > "$FRONT_DIR/notes.md"
+printf -- '---\nkind: "Summary"\n---\n# Weekly Report\n\nShort recap.\n' > "$FRONT_DIR/WEEKLY_REPORT.md"
+maid clean --yes --path "$FRONT_DIR" > /dev/null
+[ -e "$FRONT_DIR/Guide - Notes.md" ] || { echo "❌ Frontmatter type did not classify the file"; exit 1; }
+[ -e "$FRONT_DIR/Summary - Weekly Report.md" ] \
+    || { echo "❌ Frontmatter kind did not override the filename keywords"; exit 1; }
+echo y | maid keep --path "$FRONT_DIR" --trash-dir "$FRONT_DIR/trash" > /dev/null
+if grep -q "quasarmetadata" "$FRONT_DIR/COMPREHENSIVE_PROJECT_RUBRIC.md"; then
+    echo "❌ Frontmatter leaked into the rubric key terms"; exit 1
+fi
+
+# Test 58: --flat restructures names without creating subdirectories
+echo "Test 58: Flat restructure"
+FLAT_DIR="$TEST_DIR/flat-check"
+mkdir -p "$FLAT_DIR/notes"
+printf '# Guide\n\nSteps.\n' > "$FLAT_DIR/notes/SETUP_GUIDE.md"
+printf '# Report\n\nResults.\n' > "$FLAT_DIR/STATUS_REPORT.md"
+FLAT_PLAN=$(maid clean --path "$FLAT_DIR" --recursive --restructure --flat --dry-run --format json)
+echo "$FLAT_PLAN" | grep -q '"target": "Guide - Install Guide.md"' \
+    || { echo "❌ Dry run did not plan a flat target"; exit 1; }
+maid clean --yes --path "$FLAT_DIR" --recursive --restructure --flat > /dev/null
+[ -e "$FLAT_DIR/Guide - Install Guide.md" ] && [ -e "$FLAT_DIR/Report - Status Report.md" ] \
+    || { echo "❌ --flat did not put renamed files in the top directory"; exit 1; }
+[ ! -e "$FLAT_DIR/docs" ] || { echo "❌ --flat created the docs/ tree"; exit 1; }
+
+# Test 59: --normalize-headings-to-sentence-case rewrites headings while restructuring
+echo "Test 59: Sentence case headings"
+HEADINGS_DIR="$TEST_DIR/headings-check"
+mkdir -p "$HEADINGS_DIR"
+printf '# Setup Guide\n\n## GETTING STARTED\n\n## Getting Started\n\n## Using The JSON API With `MyConfig`\n\n```sh\n# KEEP THIS COMMENT\n```\n' > "$HEADINGS_DIR/SETUP_GUIDE.md"
+maid clean --yes --path "$HEADINGS_DIR" --restructure --normalize-headings-to-sentence-case > /dev/null
+HEADINGS_FILE=$(find "$HEADINGS_DIR/docs" -name '*.md' | head -n 1)
+[ "$(grep -c '^## Getting started$' "$HEADINGS_FILE")" -eq 2 ] \
+    || { echo "❌ Headings were not put in sentence case"; exit 1; }
+grep -q '^## Using the JSON API with `MyConfig`$' "$HEADINGS_FILE" \
+    || { echo "❌ Acronyms or inline code were not preserved"; exit 1; }
+grep -q '^# KEEP THIS COMMENT$' "$HEADINGS_FILE" \
+    || { echo "❌ Fenced code was rewritten"; exit 1; }
+
+# Test 60: Keep never trashes every copy of a duplicate group
+echo "Test 60: Last copy guardrail"
+LAST_COPY_DIR="$TEST_DIR/last-copy-check"
+mkdir -p "$LAST_COPY_DIR/project"
+# Two copies of a short rubric that then lose to a longer rubric, which would
+# leave none of them behind
+printf '# Rubric\n\nOne.\n' > "$LAST_COPY_DIR/project/SHORT_RUBRIC.md"
+printf '# Rubric\n\nOne.\n' > "$LAST_COPY_DIR/project/SHORT_RUBRIC_COPY.md"
+printf '# Rubric\n\nOne two three four five six.\n' > "$LAST_COPY_DIR/project/FULL_RUBRIC.md"
+LAST_COPY_OUTPUT=$(echo y | maid keep --path "$LAST_COPY_DIR/project" --trash-dir "$LAST_COPY_DIR/trash" 2>&1)
+echo "$LAST_COPY_OUTPUT" | grep -q "Every copy of .*SHORT_RUBRIC.* was marked for the trash" \
+    || { echo "❌ Emptied duplicate group was not reported"; exit 1; }
+[ -e "$LAST_COPY_DIR/project/SHORT_RUBRIC.md" ] && [ -e "$LAST_COPY_DIR/project/SHORT_RUBRIC_COPY.md" ] \
+    || { echo "❌ Every copy of a duplicate group was trashed"; exit 1; }
+grep -q "last copy of a duplicate group" "$LAST_COPY_DIR/project/maid-keep-report.json" \
+    || { echo "❌ Keep report does not explain the kept copies"; exit 1; }
+
+# Test 61: The temporary trash bin is only purged with --auto-purge
+echo "Test 61: Opt-in auto purge"
+PURGE_DIR="$TEST_DIR/auto-purge-check"
+mkdir -p "$PURGE_DIR/project" "$PURGE_DIR/tmp" "$PURGE_DIR/empty-path"
+printf '# Rubric\n\nOne two three.\n' > "$PURGE_DIR/project/FULL_RUBRIC.md"
+printf '# Rubric\n\nOne.\n' > "$PURGE_DIR/project/SHORT_RUBRIC.md"
+PURGE_OUTPUT=$(echo y | TMPDIR="$PURGE_DIR/tmp" maid keep --path "$PURGE_DIR/project")
+echo "$PURGE_OUTPUT" | grep -q "The trash bin stays until you delete it" \
+    || { echo "❌ Keep did not say the trash bin is left in place"; exit 1; }
+[ -z "$(find "$PURGE_DIR/tmp" -name self_destruct.sh)" ] \
+    || { echo "❌ Self-destruct script written without --auto-purge"; exit 1; }
+# No terminal emulator can be found on an empty PATH, which must not be fatal
+printf '# Rubric\n\nOne.\n' > "$PURGE_DIR/project/SHORT_RUBRIC.md"
+MAID_BIN=$(command -v maid)
+PURGE_OUTPUT=$(echo y | TMPDIR="$PURGE_DIR/tmp" PATH="$PURGE_DIR/empty-path" "$MAID_BIN" keep --path "$PURGE_DIR/project" --auto-purge) \
+    || { echo "❌ A missing terminal emulator failed the command"; exit 1; }
+echo "$PURGE_OUTPUT" | grep -q "Couldn't open a terminal to purge the trash bin" \
+    || { echo "❌ Missing terminal emulator was not reported"; exit 1; }
+
+# Test 62: --docs-root and --scripts-root move the docs/ and scripts/ trees
+echo "Test 62: Separate docs and scripts roots"
+ROOTS_DIR="$TEST_DIR/roots-check"
+mkdir -p "$ROOTS_DIR/project"
+printf '# Guide\n\nSteps.\n' > "$ROOTS_DIR/project/SETUP_GUIDE.md"
+printf 'print("deploying")\n' > "$ROOTS_DIR/project/deploy.py"
+maid clean --yes --path "$ROOTS_DIR/project" --restructure \
+    --docs-root "$ROOTS_DIR/documentation" --scripts-root "$ROOTS_DIR/automation" > /dev/null
+[ -n "$(find "$ROOTS_DIR/documentation/guides" -name '*.md' 2>/dev/null)" ] \
+    || { echo "❌ Markdown did not land under --docs-root"; exit 1; }
+[ -n "$(find "$ROOTS_DIR/automation/python" -name '*.py' 2>/dev/null)" ] \
+    || { echo "❌ Script did not land under --scripts-root"; exit 1; }
+[ ! -e "$ROOTS_DIR/project/docs" ] && [ ! -e "$ROOTS_DIR/project/scripts" ] \
+    || { echo "❌ Default docs/ or scripts/ tree was still created"; exit 1; }
+
+# Test 63: --keep-last keeps several of the newest reports
+echo "Test 63: Keep last N reports"
+KEEP_LAST_DIR="$TEST_DIR/keep-last-check"
+mkdir -p "$KEEP_LAST_DIR"
+for n in 1 2 3; do
+    printf '# Status Report\n\nSprint %s results.\n' "$n" > "$KEEP_LAST_DIR/SPRINT_${n}_REPORT.md"
+done
+KEEP_LAST_OUTPUT=$(echo n | maid keep --path "$KEEP_LAST_DIR" --verbose --keep-last 2)
+[ "$(echo "$KEEP_LAST_OUTPUT" | grep -c "Keeping: .*REPORT.md (one of the 2 most recent reports)")" -eq 2 ] \
+    || { echo "❌ --keep-last 2 did not keep two reports"; exit 1; }
+[ "$(echo "$KEEP_LAST_OUTPUT" | grep -c "Discarding: .*REPORT.md (older report)")" -eq 1 ] \
+    || { echo "❌ --keep-last 2 did not discard the third report"; exit 1; }
+
+# Test 64: --classify-trace records matched and unmatched checks
+echo "Test 64: Classification trace"
+TRACE_DIR="$TEST_DIR/trace-check"
+mkdir -p "$TRACE_DIR"
+printf '# Weekly Status\n\nAll green.\n' > "$TRACE_DIR/WEEKLY_STATUS.md"
+printf '[[rules]]\npattern = "*_NOTES.md"\nkind = "Summary"\n' > "$TRACE_DIR/maid.toml"
+maid clean --path "$TRACE_DIR" --dry-run --classify-trace "$TRACE_DIR/trace.jsonl" > /dev/null
+TRACE_LINE=$(grep 'WEEKLY_STATUS.md' "$TRACE_DIR/trace.jsonl")
+echo "$TRACE_LINE" | grep -q '"stage":"filename keyword","pattern":"status","kind":"Report","matched":true' \
+    || { echo "❌ Trace did not record the matching keyword"; exit 1; }
+echo "$TRACE_LINE" | grep -q '"pattern":"rubric","kind":"Rubric","matched":false' \
+    || { echo "❌ Trace did not record an unmatched keyword"; exit 1; }
+echo "$TRACE_LINE" | grep -q '"stage":"rule","pattern":"\*_NOTES.md","kind":"Summary","matched":false' \
+    || { echo "❌ Trace did not record the unmatched rule"; exit 1; }
+echo "$TRACE_LINE" | grep -q '"decision":"Report"' \
+    || { echo "❌ Trace did not record the final decision"; exit 1; }
+
+# Test 65: Keep dates every file, falling back to the modification time
+echo "Test 65: Date source in the keep report"
+DATE_SOURCE_DIR="$TEST_DIR/date-source-check"
+mkdir -p "$DATE_SOURCE_DIR"
+printf '# Status Report\n\nFirst.\n' > "$DATE_SOURCE_DIR/FIRST_REPORT.md"
+printf '# Status Report\n\nSecond.\n' > "$DATE_SOURCE_DIR/SECOND_REPORT.md"
+echo y | maid keep --path "$DATE_SOURCE_DIR" --trash-dir "$DATE_SOURCE_DIR-trash" > /dev/null
+[ "$(grep -cE '"date_source": "(created|modified)"' "$DATE_SOURCE_DIR/maid-keep-report.json")" -eq 2 ] \
+    || { echo "❌ Keep report did not record how each file was dated"; exit 1; }
+
+# Test 66: Reports that rank the same are taken in path order
+echo "Test 66: Deterministic ties"
+TIE_DIR="$TEST_DIR/tie-check"
+mkdir -p "$TIE_DIR"
+for name in C_REPORT A_REPORT B_REPORT; do
+    printf '# Status Report\n\n%s results.\n' "$name" > "$TIE_DIR/$name.md"
+done
+# With no weight on recency every report scores the same, as undated ones do
+for run in 1 2 3; do
+    TIE_OUTPUT=$(echo n | maid keep --path "$TIE_DIR" --verbose --recency-weight 0)
+    echo "$TIE_OUTPUT" | grep -q "Keeping: .*/A_REPORT.md (most recent report)" \
+        || { echo "❌ Tied reports did not keep the first by path"; exit 1; }
+done
+
+# Test 67: --quiet prints only errors and fails when a file does
+echo "Test 67: Quiet mode"
+QUIET_DIR="$TEST_DIR/quiet-check"
+mkdir -p "$QUIET_DIR"
+printf '# Guide\n\nSteps.\n' > "$QUIET_DIR/SETUP_GUIDE.md"
+QUIET_OUTPUT=$(maid clean --quiet --yes --path "$QUIET_DIR" 2>&1)
+[ -z "$QUIET_OUTPUT" ] || { echo "❌ --quiet still printed: $QUIET_OUTPUT"; exit 1; }
+# A file where docs/ should be makes the restructured copy fail
+printf 'not a directory' > "$QUIET_DIR/docs"
+if QUIET_STDOUT=$(maid -q clean --yes --path "$QUIET_DIR" --restructure 2> "$QUIET_DIR.err"); then
+    echo "❌ --quiet exited zero although a file failed"; exit 1
+fi
+[ -z "$QUIET_STDOUT" ] || { echo "❌ --quiet printed to stdout on failure"; exit 1; }
+grep -q "SETUP_GUIDE.md" "$QUIET_DIR.err" \
+    || { echo "❌ --quiet did not report the failed file on stderr"; exit 1; }
+
+# Test 68: --include-extensionless picks up shebang scripts without an extension
+echo "Test 68: Extensionless scripts"
+EXTLESS_DIR="$TEST_DIR/extensionless-check"
+mkdir -p "$EXTLESS_DIR"
+printf '#!/bin/bash\necho "installing"\n' > "$EXTLESS_DIR/install"
+printf 'plain notes\n' > "$EXTLESS_DIR/NOTES"
+EXTLESS_PLAN=$(maid clean --path "$EXTLESS_DIR" --restructure --dry-run --format json)
+echo "$EXTLESS_PLAN" | grep -q '"source": "install"' \
+    && { echo "❌ Extensionless file processed without --include-extensionless"; exit 1; }
+printf '#!/usr/bin/env perl\nprint "hi";\n' > "$EXTLESS_DIR/greet"
+maid clean --yes --path "$EXTLESS_DIR" --restructure --include-extensionless > /dev/null
+[ -e "$EXTLESS_DIR/scripts/setup/Install" ] \
+    || { echo "❌ Extensionless shebang script was not routed into scripts/"; exit 1; }
+[ -z "$(find "$EXTLESS_DIR" -mindepth 2 -name 'NOTES*')" ] \
+    || { echo "❌ Extensionless file without a shebang was processed"; exit 1; }
+[ -z "$(find "$EXTLESS_DIR" -mindepth 2 -name 'greet*')" ] \
+    || { echo "❌ Extensionless file with a non-shell shebang was processed"; exit 1; }
+
+# Test 69: README files are never renamed, moved, edited or trashed
+echo "Test 69: README protection"
+README_DIR="$TEST_DIR/readme-check"
+mkdir -p "$README_DIR"
+printf '# Project Status Report\n\n\n\nHow to use it.\n' > "$README_DIR/README.md"
+printf '# Status Report\n\nA newer, much longer status report with more to say.\n' > "$README_DIR/STATUS_REPORT.md"
+README_SUM=$(sha256sum < "$README_DIR/README.md")
+maid clean --yes --path "$README_DIR" --restructure --move > /dev/null
+[ -e "$README_DIR/README.md" ] && [ "$(sha256sum < "$README_DIR/README.md")" = "$README_SUM" ] \
+    || { echo "❌ Clean changed README.md"; exit 1; }
+[ -z "$(find "$README_DIR" -mindepth 2 -iname '*readme*')" ] \
+    || { echo "❌ Clean moved a copy of README.md"; exit 1; }
+echo y | maid keep --path "$README_DIR" --trash-dir "$README_DIR-trash" > /dev/null
+[ -e "$README_DIR/README.md" ] && [ "$(sha256sum < "$README_DIR/README.md")" = "$README_SUM" ] \
+    || { echo "❌ Keep trashed or changed README.md"; exit 1; }
+grep -q '"reason": "protected README"' "$README_DIR/maid-keep-report.json" \
+    || { echo "❌ Keep report does not mark README.md as protected"; exit 1; }
+
+# Test 70: Parallel dedup hashing picks the same survivors as a single thread
+echo "Test 70: Parallel dedup determinism"
+PARALLEL_DEDUP_DIR="$TEST_DIR/parallel-dedup-check"
+mkdir -p "$PARALLEL_DEDUP_DIR"
+for n in $(seq 1 30); do
+    printf '# Notes\n\nTopic %s.\n' "$((n % 7))" > "$PARALLEL_DEDUP_DIR/notes_copy_$n.md"
+done
+SERIAL_DEDUP=$(echo n | RAYON_NUM_THREADS=1 maid keep --path "$PARALLEL_DEDUP_DIR" --verbose --dedup-only | grep -E "Keeping|Discarding")
+for run in 1 2 3; do
+    PARALLEL_DEDUP=$(echo n | RAYON_NUM_THREADS=8 maid keep --path "$PARALLEL_DEDUP_DIR" --verbose --dedup-only | grep -E "Keeping|Discarding")
+    [ "$PARALLEL_DEDUP" = "$SERIAL_DEDUP" ] \
+        || { echo "❌ Parallel hashing changed which duplicates survive"; exit 1; }
+done
+[ "$(echo "$SERIAL_DEDUP" | grep -c "Keeping")" -eq 7 ] \
+    || { echo "❌ Dedup did not keep one file per distinct content"; exit 1; }
+
+# Test 71: --preview-limit caps the verbose per-file output
+echo "Test 71: Verbose preview limit"
+PREVIEW_LIMIT_DIR="$TEST_DIR/preview-limit-check"
+mkdir -p "$PREVIEW_LIMIT_DIR"
+for n in 1 2 3 4 5; do
+    printf '# Note %s\n\nSome text.\n' "$n" > "$PREVIEW_LIMIT_DIR/note_$n.md"
+done
+PREVIEW_OUTPUT=$(maid clean --path "$PREVIEW_LIMIT_DIR" --verbose --preview-limit 2 --dry-run 2>&1)
+[ "$(echo "$PREVIEW_OUTPUT" | grep -c "Processing:")" -eq 2 ] \
+    || { echo "❌ --preview-limit did not cap the detailed lines at 2"; exit 1; }
+echo "$PREVIEW_OUTPUT" | grep -q "\.\.\. and 3 more files" \
+    || { echo "❌ --preview-limit did not report the remaining files"; exit 1; }
+
+# Test 72: --name and --name-regex select which files are processed
+echo "Test 72: Name filters"
+NAME_FILTER_DIR="$TEST_DIR/name-filter-check"
+mkdir -p "$NAME_FILTER_DIR"
+printf '# Weekly report\n\nFindings.\n' > "$NAME_FILTER_DIR/weekly_report_1.md"
+printf '# Monthly report\n\nFindings.\n' > "$NAME_FILTER_DIR/monthly_report_2.md"
+printf '# Notes\n\nIdeas.\n' > "$NAME_FILTER_DIR/notes_3.md"
+printf '# Plan\n\nSteps.\n' > "$NAME_FILTER_DIR/plan.md"
+NAME_OUTPUT=$(maid clean --path "$NAME_FILTER_DIR" --verbose --dry-run --name 'weekly_*' --name 'monthly_*' --name-regex '^notes_\d+')
+[ "$(echo "$NAME_OUTPUT" | grep -c "Processing:")" -eq 3 ] \
+    || { echo "❌ --name/--name-regex did not OR together"; exit 1; }
+if echo "$NAME_OUTPUT" | grep "Processing:" | grep -q "plan.md"; then
+    echo "❌ --name processed a file that matches no filter"
+    exit 1
+fi
+if NAME_ERROR=$(maid clean --path "$NAME_FILTER_DIR" --dry-run --name-regex '(unclosed' 2>&1); then
+    echo "❌ An invalid --name-regex was accepted"
+    exit 1
+fi
+echo "$NAME_ERROR" | grep -q "invalid value" \
+    || { echo "❌ An invalid --name-regex gave no clear error"; exit 1; }
+
+# Test 73: Q&A-structured documents are FAQs, a guide with one FAQ section isn't
+echo "Test 73: FAQ detection"
+FAQ_DIR="$TEST_DIR/faq-check"
+mkdir -p "$FAQ_DIR"
+cat > "$FAQ_DIR/common_issues.md" << 'EOF'
+# Common issues
+
+### Q: Why does the build fail?
+Run the setup script first.
+
+### Q: Where are the logs?
+In the logs directory.
+
+### Q: How do I reset my settings?
+Delete the config file.
+EOF
+cat > "$FAQ_DIR/setup_guide.md" << 'EOF'
+# Setup guide
+
+## Installing
+Run the installer.
+
+## Configuring
+Edit the config file.
+
+## Running
+Start the server.
+
+## Upgrading
+Install the new version.
+
+## FAQ
+
+**Q:** Does it run on Windows?
+Yes.
+
+**Q:** Is it free?
+Yes.
+
+**Q:** Can I contribute?
+Yes.
+EOF
+FAQ_OUTPUT=$(maid clean --path "$FAQ_DIR" --verbose --dry-run --restructure)
+echo "$FAQ_OUTPUT" | grep -A1 "common_issues.md ->" | grep -q "Type: Faq" \
+    || { echo "❌ Q&A-structured file was not classified as an FAQ"; exit 1; }
+echo "$FAQ_OUTPUT" | grep -q "docs/faq/FAQ - Common Issues.md" \
+    || { echo "❌ FAQ was not routed to docs/faq with an FAQ prefix"; exit 1; }
+echo "$FAQ_OUTPUT" | grep -A1 "setup_guide.md ->" | grep -q "Type: Guide" \
+    || { echo "❌ A guide with one FAQ section was classified as an FAQ"; exit 1; }
+
+# Test 74: --add-extension gives extensionless shell scripts a .sh extension
+echo "Test 74: Adding extensions to extensionless scripts"
+ADD_EXT_DIR="$TEST_DIR/add-extension-check"
+mkdir -p "$ADD_EXT_DIR"
+printf '#!/usr/bin/env bash\necho "deploying"\n' > "$ADD_EXT_DIR/deploy"
+printf '#!/bin/zsh\necho "building"\n' > "$ADD_EXT_DIR/build"
+maid clean --yes --path "$ADD_EXT_DIR" --include-extensionless --add-extension > /dev/null
+[ -e "$ADD_EXT_DIR/Deploy.sh" ] && [ -e "$ADD_EXT_DIR/Build.sh" ] \
+    || { echo "❌ --add-extension did not add .sh to extensionless shell scripts"; exit 1; }
+
+# Test 75: --dedupe-keep-annotated keeps the hand-marked copy of a duplicate
+echo "Test 75: Annotated duplicate survives"
+ANNOTATED_DIR="$TEST_DIR/annotated-dedup-check"
+mkdir -p "$ANNOTATED_DIR"
+printf '<!-- reviewed -->\n# Deployment notes\n\nRoll out on Tuesdays.\n' > "$ANNOTATED_DIR/notes.md"
+touch -d '2 days ago' "$ANNOTATED_DIR/notes.md"
+printf '# Deployment notes\n\nRoll out on Tuesdays.\n' > "$ANNOTATED_DIR/deployment_notes_final_copy.md"
+ANNOTATED_OUTPUT=$(echo n | maid keep --path "$ANNOTATED_DIR" --verbose --dedup-only --dedupe-keep-annotated '<!-- reviewed -->')
+echo "$ANNOTATED_OUTPUT" | grep "Keeping" | grep -q "notes.md" \
+    || { echo "❌ The annotated duplicate was not kept"; exit 1; }
+echo "$ANNOTATED_OUTPUT" | grep "Discarding" | grep -q "deployment_notes_final_copy.md" \
+    || { echo "❌ The newer unannotated duplicate was not discarded"; exit 1; }
+
+# Test 76: --newer-than/--older-than only process files dated inside the window
+echo "Test 76: Date window filters"
+DATE_WINDOW_DIR="$TEST_DIR/date-window-check"
+mkdir -p "$DATE_WINDOW_DIR"
+printf '# Fresh report\n\nNew findings.\n' > "$DATE_WINDOW_DIR/fresh_report.md"
+printf '# Stale report\n\nOld findings.\n' > "$DATE_WINDOW_DIR/stale_report.md"
+touch -d '10 days ago' "$DATE_WINDOW_DIR/stale_report.md"
+printf '# Retro notes\n\nWhat went well.\n' > "$DATE_WINDOW_DIR/retro_2019-04-12.md"
+# Creation time can't be backdated, so both files may count as new; the
+# window is also checked against a date from before either file existed
+NEWER_OUTPUT=$(maid clean --path "$DATE_WINDOW_DIR" --verbose --dry-run --newer-than 1h)
+echo "$NEWER_OUTPUT" | grep "Processing:" | grep -q "fresh_report.md" \
+    || { echo "❌ --newer-than skipped a file inside the window"; exit 1; }
+# A date in the name wins over the file's fresh timestamps, as it does for keep
+echo "$NEWER_OUTPUT" | grep "Processing:" | grep -q "retro_2019-04-12.md" \
+    && { echo "❌ --newer-than ignored the date in a file's name"; exit 1; }
+OLDER_OUTPUT=$(maid clean --path "$DATE_WINDOW_DIR" --verbose --dry-run --older-than 2000-01-01)
+echo "$OLDER_OUTPUT" | grep -q "Processing:" \
+    && { echo "❌ --older-than processed a file outside the window"; exit 1; }
+echo "$OLDER_OUTPUT" | grep -q "Skipping 3 files outside the --newer-than/--older-than window" \
+    || { echo "❌ --older-than did not report the skipped files"; exit 1; }
+if maid clean --path "$DATE_WINDOW_DIR" --dry-run --newer-than yesterday 2>/dev/null; then
+    echo "❌ An invalid --newer-than was accepted"
+    exit 1
+fi
+
+# Test 77: --rubric-format html writes the rubric as HTML with its tables
+echo "Test 77: HTML rubric"
+HTML_RUBRIC_DIR="$TEST_DIR/html-rubric-check"
+mkdir -p "$HTML_RUBRIC_DIR"
+printf '# Setup guide\n\nHow to install the project.\n' > "$HTML_RUBRIC_DIR/setup_guide.md"
+echo y | maid keep --path "$HTML_RUBRIC_DIR" --rubric-format html > /dev/null
+[ -f "$HTML_RUBRIC_DIR/COMPREHENSIVE_PROJECT_RUBRIC.html" ] \
+    || { echo "❌ --rubric-format html did not write COMPREHENSIVE_PROJECT_RUBRIC.html"; exit 1; }
+[ ! -e "$HTML_RUBRIC_DIR/COMPREHENSIVE_PROJECT_RUBRIC.md" ] \
+    || { echo "❌ --rubric-format html also wrote the markdown rubric"; exit 1; }
+grep -q "<table>" "$HTML_RUBRIC_DIR/COMPREHENSIVE_PROJECT_RUBRIC.html" \
+    || { echo "❌ The HTML rubric has no table for its criteria"; exit 1; }
+
+# Test 78: Key terms tied on count come out in the same order every run
+echo "Test 78: Reproducible rubric key terms"
+TIED_TERMS_DIR="$TEST_DIR/tied-terms-check"
+mkdir -p "$TIED_TERMS_DIR"
+printf '# Guide\n\nzebra yak xenon walrus violet umbra tulip sierra quartz pluto oscar nectar mango lemon kiwi juniper hazel garnet falcon ember delta\n' \
+    > "$TIED_TERMS_DIR/setup_guide.md"
+echo y | maid keep --path "$TIED_TERMS_DIR" > /dev/null
+FIRST_TERMS=$(sed -n '/### Key Terms/,/^## /p' "$TIED_TERMS_DIR/COMPREHENSIVE_PROJECT_RUBRIC.md")
+for run in 1 2 3; do
+    echo y | maid keep --path "$TIED_TERMS_DIR" > /dev/null
+    [ "$(sed -n '/### Key Terms/,/^## /p' "$TIED_TERMS_DIR/COMPREHENSIVE_PROJECT_RUBRIC.md")" = "$FIRST_TERMS" ] \
+        || { echo "❌ Tied key terms changed order between runs"; exit 1; }
+done
+echo "$FIRST_TERMS" | grep -q "^- delta$" \
+    || { echo "❌ Tied key terms were not broken alphabetically"; exit 1; }
+
+# Test 79: Extensions match case-insensitively; --strict-extensions fixes report.md.txt
+echo "Test 79: Mixed-case and double extensions"
+STRICT_EXT_DIR="$TEST_DIR/strict-extensions-check"
+mkdir -p "$STRICT_EXT_DIR"
+printf '# Meeting notes\n\nDecisions.\n' > "$STRICT_EXT_DIR/meeting_notes.MD"
+printf '#!/bin/bash\necho "deploying"\n' > "$STRICT_EXT_DIR/deploy.Sh"
+printf '# Weekly report\n\nFindings.\n' > "$STRICT_EXT_DIR/weekly_report.md.txt"
+CASE_OUTPUT=$(maid clean --path "$STRICT_EXT_DIR" --verbose --dry-run)
+echo "$CASE_OUTPUT" | grep -q "meeting_notes.MD -> .*Meeting Notes.md" \
+    || { echo "❌ A .MD file was not processed as markdown"; exit 1; }
+echo "$CASE_OUTPUT" | grep -q "deploy.Sh -> .*Deploy.sh" \
+    || { echo "❌ A .Sh file was not processed as a shell script"; exit 1; }
+echo "$CASE_OUTPUT" | grep -q "weekly_report.md.txt" \
+    && { echo "❌ report.md.txt was processed without --strict-extensions"; exit 1; }
+STRICT_OUTPUT=$(maid clean --path "$STRICT_EXT_DIR" --verbose --dry-run --strict-extensions)
+echo "$STRICT_OUTPUT" | grep -q "weekly_report.md.txt -> .*/Report - Weekly Report.md$" \
+    || { echo "❌ --strict-extensions did not drop the trailing .txt"; exit 1; }
+
+# Test 80: Stopwords, link noise and numbers stay out of the rubric's key terms
+echo "Test 80: Rubric stopwords"
+STOPWORDS_DIR="$TEST_DIR/stopwords-check"
+mkdir -p "$STOPWORDS_DIR"
+printf '# Guide\n\nThis file explains that with https://example.com and 2024 you deploy the lighthouse beacon.\n' \
+    > "$STOPWORDS_DIR/setup_guide.md"
+printf 'beacon # project name\n' > "$STOPWORDS_DIR/stopwords.txt"
+echo y | maid keep --path "$STOPWORDS_DIR" --stopwords-file "$STOPWORDS_DIR/stopwords.txt" > /dev/null
+STOPWORD_TERMS=$(sed -n '/### Key Terms/,/^## /p' "$STOPWORDS_DIR/COMPREHENSIVE_PROJECT_RUBRIC.md")
+echo "$STOPWORD_TERMS" | grep -q "^- lighthouse$" \
+    || { echo "❌ A meaningful key term was filtered out"; exit 1; }
+for junk in this that with file https 2024 beacon; do
+    echo "$STOPWORD_TERMS" | grep -q "^- $junk$" \
+        && { echo "❌ '$junk' was listed as a key term"; exit 1; }
+done
+if echo y | maid keep --path "$STOPWORDS_DIR" --stopwords-file "$STOPWORDS_DIR/missing.txt" > /dev/null 2>&1; then
+    echo "❌ A missing stopword file was accepted"
+    exit 1
+fi
+
+# Test 81: --link symlinks files into place and leaves the originals alone
+echo "Test 81: Linking instead of copying"
+LINK_DIR="$TEST_DIR/link-check"
+mkdir -p "$LINK_DIR"
+printf '# Status report\n\nAll green.\n' > "$LINK_DIR/status_report.md"
+maid clean --yes --path "$LINK_DIR" --restructure --link > /dev/null
+LINKED_REPORT="$LINK_DIR/docs/reports/Report - Status Report.md"
+[ -L "$LINKED_REPORT" ] || { echo "❌ --link did not create a symlink"; exit 1; }
+[ "$(readlink -f "$LINKED_REPORT")" = "$(readlink -f "$LINK_DIR/status_report.md")" ] \
+    || { echo "❌ The --link symlink does not point at the original"; exit 1; }
+[ -f "$LINK_DIR/status_report.md" ] && [ ! -L "$LINK_DIR/status_report.md" ] \
+    || { echo "❌ --link did not leave the original in place"; exit 1; }
+if maid clean --yes --path "$LINK_DIR" --link --move > /dev/null 2>&1; then
+    echo "❌ --link was accepted together with --move"
+    exit 1
+fi
+
+# Test 82: --keep-originals-index lists every renamed file's old and new name
+echo "Test 82: Originals index"
+ORIGINALS_DIR="$TEST_DIR/originals-index-check"
+mkdir -p "$ORIGINALS_DIR"
+printf '# Status report\n\nAll green.\n' > "$ORIGINALS_DIR/status_report.md"
+printf '# Install guide\n\nRun make.\n' > "$ORIGINALS_DIR/install_guide.md"
+printf '#!/bin/bash\necho "testing"\n' > "$ORIGINALS_DIR/run_tests.sh"
+maid clean --yes --path "$ORIGINALS_DIR" --restructure --move \
+    --keep-originals-index "$TEST_DIR/originals-index.csv" > /dev/null
+[ "$(head -1 "$TEST_DIR/originals-index.csv")" = "original_name,new_name,original_path,new_path" ] \
+    || { echo "❌ The CSV originals index has no header"; exit 1; }
+[ "$(tail -n +2 "$TEST_DIR/originals-index.csv" | wc -l)" -eq 3 ] \
+    || { echo "❌ The originals index does not list every renamed file"; exit 1; }
+while IFS=, read -r original_name new_name original_path new_path; do
+    [ "$original_name" = "original_name" ] && continue
+    [ -n "$new_name" ] && [ "$original_name" != "$new_name" ] \
+        || { echo "❌ $original_name is missing its new name in the originals index"; exit 1; }
+    [ -e "$ORIGINALS_DIR/$new_path" ] && [ ! -e "$ORIGINALS_DIR/$original_path" ] \
+        || { echo "❌ The originals index maps $original_path to the wrong place"; exit 1; }
+done < "$TEST_DIR/originals-index.csv"
+for original in status_report.md install_guide.md run_tests.sh; do
+    grep -q "^$original," "$TEST_DIR/originals-index.csv" \
+        || { echo "❌ $original is missing from the originals index"; exit 1; }
+done
+
+# Test 83: --no-progress prints no progress output, even without --verbose
+echo "Test 83: No progress output"
+NO_PROGRESS_DIR="$TEST_DIR/no-progress-check"
+mkdir -p "$NO_PROGRESS_DIR"
+for n in 1 2 3; do
+    printf '# Report %s\n\nFindings.\n' "$n" > "$NO_PROGRESS_DIR/report_$n.md"
+done
+PROGRESS_OUTPUT=$(maid clean --path "$NO_PROGRESS_DIR" --dry-run 2>&1)
+echo "$PROGRESS_OUTPUT" | grep -qx '\.\.\.' \
+    || { echo "❌ Non-verbose runs no longer show progress"; exit 1; }
+NO_PROGRESS_OUTPUT=$(maid clean --path "$NO_PROGRESS_DIR" --dry-run --no-progress 2>&1)
+echo "$NO_PROGRESS_OUTPUT" | grep -qE '^\.+$|\[[#=>-]*\]|[0-9]+/[0-9]+ \(' \
+    && { echo "❌ --no-progress still rendered progress"; exit 1; }
+echo "$NO_PROGRESS_OUTPUT" | grep -q "Files processed: 3" \
+    || { echo "❌ --no-progress hid the summary"; exit 1; }
+
+# Test 84: --prune-empty removes emptied directories bottom-up and counts them
+echo "Test 84: Pruning emptied directories"
+PRUNE_DIR="$TEST_DIR/prune-count-check"
+mkdir -p "$PRUNE_DIR/drafts/old/older" "$PRUNE_DIR/assets"
+printf '# Older report\n\nFindings.\n' > "$PRUNE_DIR/drafts/old/older/older_report.md"
+printf '# Asset report\n\nFindings.\n' > "$PRUNE_DIR/assets/asset_report.md"
+printf 'binary-ish\n' > "$PRUNE_DIR/assets/logo.dat"
+PRUNE_OUTPUT=$(maid clean --yes --path "$PRUNE_DIR" --recursive --restructure --move --prune-empty)
+[ ! -e "$PRUNE_DIR/drafts" ] || { echo "❌ Emptied nested directories were not pruned"; exit 1; }
+[ -f "$PRUNE_DIR/assets/logo.dat" ] || { echo "❌ A directory with other files in it was pruned"; exit 1; }
+echo "$PRUNE_OUTPUT" | grep -q "Empty directories pruned: 3" \
+    || { echo "❌ The summary did not report how many directories were pruned"; exit 1; }
+
+# Test 85: --min-confidence leaves weakly classified files alone and --verbose shows confidence
+echo "Test 85: Minimum classification confidence"
+CONF_DIR="$TEST_DIR/confidence-check"
+mkdir -p "$CONF_DIR"
+printf '# Notes\n\nRead this step by step.\n' > "$CONF_DIR/notes.md"
+printf '# How to install\n\nGo step by step.\n' > "$CONF_DIR/setup_guide.md"
+CONF_OUTPUT=$(maid clean --dry-run --verbose --path "$CONF_DIR" --min-confidence 0.7)
+echo "$CONF_OUTPUT" | grep -q "Type: Guide (confidence 1.00)" \
+    || { echo "❌ Verbose output did not show the classification confidence"; exit 1; }
+echo "$CONF_OUTPUT" | grep -q "notes.md - Guide with confidence 0.25, below --min-confidence" \
+    || { echo "❌ A weakly classified file was not left alone"; exit 1; }
+maid clean --yes --path "$CONF_DIR" --restructure --move --min-confidence 0.7 > /dev/null
+[ -f "$CONF_DIR/notes.md" ] || { echo "❌ A file below --min-confidence was moved"; exit 1; }
+[ ! -f "$CONF_DIR/setup_guide.md" ] || { echo "❌ A confidently classified file was not restructured"; exit 1; }
+if maid clean --dry-run --path "$CONF_DIR" --min-confidence 1.5 > /dev/null 2>&1; then
+    echo "❌ A --min-confidence above 1 was accepted"; exit 1
+fi
+# --annotate-confidence-in-name processes them instead, marked for review
+REVIEW_DIR="$TEST_DIR/review-check"
+mkdir -p "$REVIEW_DIR"
+printf '# Notes\n\nRead this step by step.\n' > "$REVIEW_DIR/misc_notes.md"
+printf 'Nothing to see here.\n' > "$REVIEW_DIR/scratch.md"
+printf '# Status Report\n\nAll tasks on track.\n' > "$REVIEW_DIR/status_report.md"
+maid clean --yes --move --min-confidence 0.5 --annotate-confidence-in-name --path "$REVIEW_DIR" > /dev/null
+[ -f "$REVIEW_DIR/Guide - Misc Notes (review).md" ] \
+    || { echo "❌ A low-confidence file's name did not carry the review marker"; exit 1; }
+[ -f "$REVIEW_DIR/Scratch (review).md" ] \
+    || { echo "❌ An unclassified file's name did not carry the review marker"; exit 1; }
+[ -f "$REVIEW_DIR/Report - Status Report.md" ] \
+    || { echo "❌ A confident file was marked for review"; exit 1; }
+
+# Test 86: A dated version header outranks filesystem timestamps when keeping the newest report
+echo "Test 86: Version header dates"
+HEADER_DIR="$TEST_DIR/header-date-check"
+mkdir -p "$HEADER_DIR"
+printf '# Release report\n\n## [2.0.0] - 2024-06-01\n\nShipped.\n\n## [1.0.0] - 2024-01-10\n\nFirst.\n' \
+    > "$HEADER_DIR/RELEASE_V2_REPORT.md"
+sleep 1
+printf '# Release report\n\n## [1.0.0] - 2024-01-10\n\nFirst.\n' > "$HEADER_DIR/RELEASE_V1_REPORT.md"
+HEADER_OUTPUT=$(echo n | maid keep --path "$HEADER_DIR" --verbose)
+echo "$HEADER_OUTPUT" | grep -q "Keeping: .*RELEASE_V2_REPORT.md (most recent report)" \
+    || { echo "❌ The report with the newest version header was not kept"; exit 1; }
+echo "$HEADER_OUTPUT" | grep -q "Discarding: .*RELEASE_V1_REPORT.md (older report)" \
+    || { echo "❌ The report with an older version header was not discarded"; exit 1; }
+
+# Test 87: --io-concurrency bounds file IO without changing results, and must be at least 1
+echo "Test 87: IO concurrency limit"
+IO_DIR="$TEST_DIR/io-concurrency-check"
+mkdir -p "$IO_DIR"
+for n in 1 2 3 4 5 6; do
+    printf '# Setup guide %s\n\nHow to use it.\n' "$n" > "$IO_DIR/setup_guide_$n.md"
+done
+maid clean --yes --path "$IO_DIR" --restructure --move --jobs 4 --io-concurrency 1 > /dev/null
+[ "$(find "$IO_DIR/docs" -name '*.md' | wc -l)" -eq 6 ] \
+    || { echo "❌ Not every file was processed with --io-concurrency 1"; exit 1; }
+if maid clean --dry-run --path "$IO_DIR" --io-concurrency 0 > /dev/null 2>&1; then
+    echo "❌ --io-concurrency 0 was accepted"; exit 1
+fi
+
+# Test 88: --target-dir organizes into a separate tree and leaves the scanned directory as it was
+echo "Test 88: Separate target directory"
+DUMP_DIR="$TEST_DIR/target-dir-check/dump"
+ORGANIZED_DIR="$TEST_DIR/target-dir-check/organized"
+mkdir -p "$DUMP_DIR/nested"
+printf '# Setup guide\n\nHow to use it.\n' > "$DUMP_DIR/setup_guide.md"
+printf '# Status Report\n\nAll done.\n' > "$DUMP_DIR/nested/STATUS_REPORT.md"
+printf '#!/bin/bash\necho building\n' > "$DUMP_DIR/build.sh"
+DUMP_BEFORE=$(cd "$DUMP_DIR" && find . -path ./.maid -prune -o -type f -print | sort)
+maid clean --yes --path "$DUMP_DIR" --recursive --restructure --target-dir "$ORGANIZED_DIR" > /dev/null
+DUMP_AFTER=$(cd "$DUMP_DIR" && find . -path ./.maid -prune -o -type f -print | sort)
+[ "$DUMP_BEFORE" = "$DUMP_AFTER" ] || { echo "❌ The scanned directory was changed"; exit 1; }
+[ ! -e "$DUMP_DIR/docs" ] || { echo "❌ A docs/ tree was built in the scanned directory"; exit 1; }
+[ -n "$(find "$ORGANIZED_DIR/docs/guides" -name '*.md')" ] \
+    && [ -n "$(find "$ORGANIZED_DIR/docs/reports" -name '*.md')" ] \
+    && [ -n "$(find "$ORGANIZED_DIR/scripts/build" -name '*.sh')" ] \
+    || { echo "❌ Files were not organized under --target-dir"; exit 1; }
+
+# Test 89: API references are detected by name, headings or dense tagged code, and all kept
+echo "Test 89: API reference detection"
+API_DIR="$TEST_DIR/api-reference-check"
+mkdir -p "$API_DIR"
+printf '# Users service\n\n## Endpoints\n\n### GET /users\n\n### Parameters\n\n- `limit`: page size\n' > "$API_DIR/users_service.md"
+printf '# Client library\n\n```python\nclient.connect()\n```\n\n```python\nclient.fetch(1)\n```\n\n```json\n{"id": 1}\n```\n' > "$API_DIR/client.md"
+printf '# Storage\n\nStores things.\n' > "$API_DIR/storage_api.md"
+printf '# Preferences\n\nDark mode is on.\n' > "$API_DIR/preferences.md"
+API_OUTPUT=$(maid clean --path "$API_DIR" --verbose --dry-run --restructure)
+for name in users_service client storage_api; do
+    echo "$API_OUTPUT" | grep -A1 "$name.md ->" | grep -q "Type: ApiReference" \
+        || { echo "❌ $name.md was not classified as an API reference"; exit 1; }
+done
+echo "$API_OUTPUT" | grep -A1 "preferences.md ->" | grep -q "Type: ApiReference" \
+    && { echo "❌ A filename merely containing 'reference' was taken for an API reference"; exit 1; }
+echo "$API_OUTPUT" | grep -q "docs/reference/API Reference - Users Service.md" \
+    || { echo "❌ API reference was not routed to docs/reference"; exit 1; }
+API_KEEP_OUTPUT=$(echo n | maid keep --path "$API_DIR" --verbose)
+[ "$(echo "$API_KEEP_OUTPUT" | grep -c "(API reference)")" -eq 3 ] \
+    || { echo "❌ Keep did not keep every API reference"; exit 1; }
+# Scripts that document their return values are still scripts
+API_SCRIPT_DIR="$TEST_DIR/api-script-check"
+mkdir -p "$API_SCRIPT_DIR"
+printf '#!/bin/bash\n# Install dependencies\n# Returns: 0 on success\napt-get install -y jq\n' > "$API_SCRIPT_DIR/install_deps.sh"
+printf '#!/bin/bash\ncurl "$API_URL"\n' > "$API_SCRIPT_DIR/api_client.sh"
+API_SCRIPT_OUTPUT=$(maid clean --dry-run --verbose --restructure --path "$API_SCRIPT_DIR")
+for name in install_deps api_client; do
+    echo "$API_SCRIPT_OUTPUT" | grep -A1 "$name.sh ->" | grep -q "Type: Script" \
+        || { echo "❌ $name.sh was not classified as a script"; exit 1; }
+done
+echo "$API_SCRIPT_OUTPUT" | grep -q "scripts/setup/Install Deps.sh" \
+    || { echo "❌ A script mentioning 'Returns:' was not routed to scripts/"; exit 1; }
+
+# Test 90: Verbose lines are printed above the progress bar instead of through it
+echo "Test 90: Progress bar with verbose output"
+if command -v script > /dev/null; then
+    VERBOSE_BAR_DIR="$TEST_DIR/verbose-bar-check"
+    mkdir -p "$VERBOSE_BAR_DIR"
+    for n in $(seq 1 20); do
+        printf '# Report %s\n\nFindings.\n' "$n" > "$VERBOSE_BAR_DIR/report_$n.md"
+    done
+    # script gives maid a terminal, so the bar is drawn
+    VERBOSE_BAR_OUTPUT=$(script -qc "maid clean --path '$VERBOSE_BAR_DIR' --dry-run --verbose --progress --no-color" /dev/null \
+        | tr '\r' '\n')
+    [ "$(echo "$VERBOSE_BAR_OUTPUT" | grep -c "Processing:")" -eq 20 ] \
+        || { echo "❌ Verbose lines went missing with the progress bar shown"; exit 1; }
+    echo "$VERBOSE_BAR_OUTPUT" | grep -q "20/20" \
+        || { echo "❌ The progress bar was not shown with --verbose --progress"; exit 1; }
+    echo "$VERBOSE_BAR_OUTPUT" | grep "Processing:\|Type:" | grep -q "/20 (" \
+        && { echo "❌ Verbose lines were printed through the progress bar"; exit 1; }
+else
+    echo "  (skipped: needs script(1) to run maid in a terminal)"
+fi
+
+# Test 91: --interactive asks about each file; n leaves it, a approves the rest, q changes nothing
+echo "Test 91: Interactive per-file confirmation"
+INTERACTIVE_DIR="$TEST_DIR/interactive-check"
+mkdir -p "$INTERACTIVE_DIR"
+for name in a_report b_report c_report d_report; do
+    printf '# Status Report\n\n%s findings.\n' "$name" > "$INTERACTIVE_DIR/$name.md"
+done
+QUIT_OUTPUT=$(printf 'y\nq\n' | maid clean --path "$INTERACTIVE_DIR" --restructure --move --interactive)
+echo "$QUIT_OUTPUT" | grep -q "Operation cancelled" || { echo "❌ Quitting did not cancel the run"; exit 1; }
+[ ! -e "$INTERACTIVE_DIR/docs" ] || { echo "❌ Quitting still changed files"; exit 1; }
+INTERACTIVE_OUTPUT=$(printf 'y\nn\na\n' | maid clean --path "$INTERACTIVE_DIR" --restructure --move --interactive)
+[ "$(echo "$INTERACTIVE_OUTPUT" | grep -o "Apply? \[y\]es / \[n\]o / \[a\]ll / \[q\]uit" | wc -l)" -eq 3 ] \
+    || { echo "❌ --interactive did not stop asking after 'a'"; exit 1; }
+echo "$INTERACTIVE_OUTPUT" | grep -q "Processing: .*a_report.md -> .*docs/reports" \
+    || { echo "❌ --interactive did not show the proposed move"; exit 1; }
+[ -f "$INTERACTIVE_DIR/b_report.md" ] || { echo "❌ A declined file was moved"; exit 1; }
+[ ! -e "$INTERACTIVE_DIR/a_report.md" ] && [ ! -e "$INTERACTIVE_DIR/c_report.md" ] && [ ! -e "$INTERACTIVE_DIR/d_report.md" ] \
+    || { echo "❌ Approved files were not moved"; exit 1; }
+[ "$(find "$INTERACTIVE_DIR/docs/reports" -name '*.md' | wc -l)" -eq 3 ] \
+    || { echo "❌ Approved files did not land in docs/reports"; exit 1; }
+
+# Test 92: Two files mapping to the same name in one run both survive, the second as " (2)"
+echo "Test 92: Same generated name within a run"
+SAME_NAME_DIR="$TEST_DIR/same-name-check"
+mkdir -p "$SAME_NAME_DIR"
+printf '# Status Report\n\nFrontend findings.\n' > "$SAME_NAME_DIR/status_report.md"
+printf '# Status Report\n\nBackend findings.\n' > "$SAME_NAME_DIR/status-report.md"
+SAME_NAME_PLAN=$(maid clean --dry-run --verbose --path "$SAME_NAME_DIR" --restructure --jobs 1)
+echo "$SAME_NAME_PLAN" | grep -q "Another file in this run has the same name, renaming to .*Report - Status Report (2).md" \
+    || { echo "❌ The dry run did not show the within-run rename"; exit 1; }
+maid clean --yes --path "$SAME_NAME_DIR" --restructure --move > /dev/null
+[ -f "$SAME_NAME_DIR/docs/reports/Report - Status Report.md" ] \
+    && [ -f "$SAME_NAME_DIR/docs/reports/Report - Status Report (2).md" ] \
+    || { echo "❌ Both files mapping to the same name were not kept"; exit 1; }
+cat "$SAME_NAME_DIR"/docs/reports/*.md | grep -q "Frontend findings" \
+    && cat "$SAME_NAME_DIR"/docs/reports/*.md | grep -q "Backend findings" \
+    || { echo "❌ A file's content was lost to a same-name collision"; exit 1; }
+
+# Test 93: --since-commit only processes files changed in commits since a ref
+echo "Test 93: Files changed since a commit"
+GIT_DIR_CHECK="$TEST_DIR/since-commit-check"
+mkdir -p "$GIT_DIR_CHECK"
+printf '# Old Report\n\nFindings.\n' > "$GIT_DIR_CHECK/old_report.md"
+git -C "$GIT_DIR_CHECK" init -q
+git -C "$GIT_DIR_CHECK" -c user.name=maid -c user.email=maid@example.com add old_report.md
+git -C "$GIT_DIR_CHECK" -c user.name=maid -c user.email=maid@example.com commit -qm "Old report"
+git -C "$GIT_DIR_CHECK" tag before-session
+printf '# New Report\n\nFindings.\n' > "$GIT_DIR_CHECK/new_report.md"
+git -C "$GIT_DIR_CHECK" -c user.name=maid -c user.email=maid@example.com add new_report.md
+git -C "$GIT_DIR_CHECK" -c user.name=maid -c user.email=maid@example.com commit -qm "New report"
+SINCE_COMMIT_OUTPUT=$(maid clean --dry-run --verbose --path "$GIT_DIR_CHECK" --since-commit before-session)
+echo "$SINCE_COMMIT_OUTPUT" | grep -q "Processing: .*new_report.md" \
+    || { echo "❌ A file changed since the ref was not processed"; exit 1; }
+echo "$SINCE_COMMIT_OUTPUT" | grep -q "old_report.md" \
+    && { echo "❌ A file unchanged since the ref was processed"; exit 1; }
+echo "$SINCE_COMMIT_OUTPUT" | grep -q "Skipping 1 files not changed since before-session" \
+    || { echo "❌ --since-commit did not report the files it left out"; exit 1; }
+NOT_GIT_DIR="$TEST_DIR/not-git-check"
+mkdir -p "$NOT_GIT_DIR"
+NOT_GIT_OUTPUT=$(GIT_CEILING_DIRECTORIES="$TEST_DIR" maid clean --dry-run --path "$NOT_GIT_DIR" --since-commit HEAD 2>&1) \
+    && { echo "❌ --since-commit outside a git repository did not fail"; exit 1; }
+echo "$NOT_GIT_OUTPUT" | grep -q "is not in a git repository" \
+    || { echo "❌ --since-commit outside a git repository gave an unclear error"; exit 1; }
+
+# Test 94: Clean and Keep summaries report bytes processed and moved to trash
+echo "Test 94: Byte totals in summaries"
+BYTES_DIR="$TEST_DIR/bytes-check"
+mkdir -p "$BYTES_DIR"
+{ printf '# Status Report\n\n'; head -c 2031 /dev/zero | tr '\0' 'x'; } > "$BYTES_DIR/old_report.md"
+sleep 1
+{ printf '# Status Report\n\n'; head -c 1007 /dev/zero | tr '\0' 'y'; } > "$BYTES_DIR/new_report.md"
+BYTES_CLEAN_OUTPUT=$(maid clean --dry-run --path "$BYTES_DIR")
+echo "$BYTES_CLEAN_OUTPUT" | grep -q "Bytes processed: 3.0 KiB" \
+    || { echo "❌ Clean did not report the bytes it processed"; exit 1; }
+BYTES_JSON=$(maid clean --dry-run --path "$BYTES_DIR" --format json)
+echo "$BYTES_JSON" | grep -q '"bytes_processed": 3072' \
+    || { echo "❌ Clean's JSON summary has no bytes_processed"; exit 1; }
+BYTES_KEEP_OUTPUT=$(echo y | maid keep --path "$BYTES_DIR" --trash-dir "$TEST_DIR/bytes-trash")
+echo "$BYTES_KEEP_OUTPUT" | grep -q "Bytes to move to trash: 2.0 KiB" \
+    || { echo "❌ Keep did not report the bytes it would trash"; exit 1; }
+echo "$BYTES_KEEP_OUTPUT" | grep -q "Bytes moved to trash: 2.0 KiB" \
+    || { echo "❌ Keep did not report the bytes it trashed"; exit 1; }
+echo "$BYTES_KEEP_OUTPUT" | grep -q "Bytes processed: 3.0 KiB" \
+    || { echo "❌ Keep did not report the bytes it processed"; exit 1; }
+
+# Test 95: config init scaffolds a maid.toml that maid can load
+echo "Test 95: config init"
+INIT_DIR="$TEST_DIR/config-init"
+mkdir -p "$INIT_DIR"
+printf '# How to install\n\nStep 1: run the installer.\n' > "$INIT_DIR/setup.md"
+(cd "$INIT_DIR" && maid config init > /dev/null)
+grep -q '^\[keywords.Guide\]' "$INIT_DIR/maid.toml" \
+    || { echo "❌ config init did not write the built-in keywords"; exit 1; }
+grep -q '^error_phrases = ' "$INIT_DIR/maid.toml" \
+    || { echo "❌ config init did not write the error phrases"; exit 1; }
+if (cd "$INIT_DIR" && maid config init > /dev/null 2>&1); then
+    echo "❌ config init overwrote an existing maid.toml without --force"
+    exit 1
+fi
+(cd "$INIT_DIR" && maid config init --force > /dev/null)
+INIT_STATS=$(maid stats --path "$INIT_DIR")
+echo "$INIT_STATS" | grep -q "Guide - Install.md" \
+    || { echo "❌ The scaffolded maid.toml changed how files are classified"; exit 1; }
+XDG_CONFIG_HOME="$TEST_DIR/xdg" maid config init --global > /dev/null
+[ -f "$TEST_DIR/xdg/maid/maid.toml" ] || { echo "❌ config init --global did not write to the config directory"; exit 1; }
+
+# Test 96: --preserve-tree keeps each file's subdirectory under its kind folder
+echo "Test 96: Preserve directory structure"
+TREE_DIR="$TEST_DIR/tree-check"
+mkdir -p "$TREE_DIR/team/weekly"
+printf '# Status Report\n\nAll tasks on track.\n' > "$TREE_DIR/team/weekly/status_report.md"
+printf '# Status Report\n\nAll tasks on track.\n' > "$TREE_DIR/status_report.md"
+echo y | maid clean --recursive --restructure --preserve-tree --path "$TREE_DIR" > /dev/null
+[ -f "$TREE_DIR/docs/reports/team/weekly/Report - Status Report.md" ] \
+    || { echo "❌ --preserve-tree did not keep the file's subdirectory"; exit 1; }
+[ -f "$TREE_DIR/docs/reports/Report - Status Report.md" ] \
+    || { echo "❌ --preserve-tree moved a top-level file into a subdirectory"; exit 1; }
+
+# Test 97: the exit code says how the run went
+echo "Test 97: Exit codes"
+EXIT_DIR="$TEST_DIR/exit-check"
+mkdir -p "$EXIT_DIR"
+printf '# Status Report\n\nAll tasks on track.\n' > "$EXIT_DIR/status_report.md"
+EXIT_CODE=0; maid clean --dry-run --path "$EXIT_DIR" > /dev/null || EXIT_CODE=$?
+[ "$EXIT_CODE" -eq 0 ] || { echo "❌ A clean run exited with $EXIT_CODE instead of 0"; exit 1; }
+EXIT_CODE=0; maid clean --dry-run --path "$EXIT_DIR/missing" > /dev/null 2>&1 || EXIT_CODE=$?
+[ "$EXIT_CODE" -eq 3 ] || { echo "❌ A missing directory exited with $EXIT_CODE instead of 3"; exit 1; }
+EXIT_CODE=0; maid clean --stdin --path "$EXIT_DIR" < /dev/null > /dev/null 2>&1 || EXIT_CODE=$?
+[ "$EXIT_CODE" -eq 2 ] || { echo "❌ A usage error exited with $EXIT_CODE instead of 2"; exit 1; }
+# A file where docs/ should be means the report can't be moved into it
+touch "$EXIT_DIR/docs"
+EXIT_CODE=0; maid clean --restructure --yes --path "$EXIT_DIR" > /dev/null 2>&1 || EXIT_CODE=$?
+[ "$EXIT_CODE" -eq 1 ] || { echo "❌ A run with a failed file exited with $EXIT_CODE instead of 1"; exit 1; }
+# Valid text up front passes the sniff, but the file can't be read as UTF-8
+EXIT_BROKEN_DIR="$TEST_DIR/exit-broken"
+mkdir -p "$EXIT_BROKEN_DIR"
+{ printf '# Notes\n\n'; head -c 9000 /dev/zero | tr '\0' 'x'; printf '\377\n'; } > "$EXIT_BROKEN_DIR/broken_notes.md"
+EXIT_CODE=0; maid clean --dry-run --path "$EXIT_BROKEN_DIR" > /dev/null 2>&1 || EXIT_CODE=$?
+[ "$EXIT_CODE" -eq 1 ] || { echo "❌ A run with an unreadable file exited with $EXIT_CODE instead of 1"; exit 1; }
+EXIT_CODE=0; maid clean --dry-run --path "$EXIT_BROKEN_DIR/broken_notes.md" > /dev/null 2>&1 || EXIT_CODE=$?
+[ "$EXIT_CODE" -eq 1 ] || { echo "❌ A single-file run on an unreadable file exited with $EXIT_CODE instead of 1"; exit 1; }
+
 echo "✅ All tests completed successfully!"
 echo "🧹 Test directory: $TEST_DIR"
 echo "📝 You can inspect the results manually or delete with: rm -rf $TEST_DIR"
@@ -0,0 +1,48 @@
+use maid::{determine_document_kind, DocumentKind, FileInfo, FileType};
+use std::path::PathBuf;
+
+#[test]
+fn classifies_file_info_from_content() {
+    let info = FileInfo::from_content(
+        PathBuf::from("notes/SETUP_GUIDE.md"),
+        "# Guide\n\nStep by step instructions.\n".to_string(),
+    );
+    
+    assert_eq!(info.file_type, FileType::Markdown);
+    assert_eq!(info.doc_kind, DocumentKind::Guide);
+    assert_eq!(info.generate_new_filename(), "Guide - Install Guide.md");
+    assert!(info.created_date.is_none());
+}
+
+#[test]
+fn frontmatter_kind_overrides_keywords() {
+    let info = FileInfo::from_content(
+        PathBuf::from("STATUS_REPORT.md"),
+        "---\ntype: summary\n---\n# Status\n".to_string(),
+    );
+    
+    assert_eq!(info.doc_kind, DocumentKind::Summary);
+}
+
+#[test]
+fn extensionless_shebang_content_is_a_script() {
+    let info = FileInfo::from_content(PathBuf::from("install"), "#!/bin/bash\necho hi\n".to_string());
+    
+    assert_eq!(info.file_type, FileType::Shell);
+    assert_eq!(info.doc_kind, DocumentKind::Script);
+}
+
+#[test]
+fn determines_kind_from_name_and_content() {
+    assert_eq!(determine_document_kind("CHANGELOG", "## [Unreleased]").0, DocumentKind::Changelog);
+    assert_eq!(determine_document_kind("notes", "Nothing to see"), (DocumentKind::Unknown, 0.0));
+}
+
+#[test]
+fn stronger_evidence_gives_higher_confidence() {
+    let (kind, weak) = determine_document_kind("notes", "Read this step by step");
+    assert_eq!(kind, DocumentKind::Guide);
+    let (kind, strong) = determine_document_kind("SETUP_GUIDE", "# How to install\nGo step by step");
+    assert_eq!(kind, DocumentKind::Guide);
+    assert!(weak < strong && strong <= 1.0);
+}
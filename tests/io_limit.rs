@@ -0,0 +1,64 @@
+use maid::IoLimit;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Opens files under the limit, tracking how many are open at the same time
+struct CountingOpener<'a> {
+    limit: &'a IoLimit,
+    open: AtomicUsize,
+    most_open: AtomicUsize,
+}
+
+impl CountingOpener<'_> {
+    fn read(&self, path: &Path) -> String {
+        let _permit = self.limit.acquire();
+        let mut file = File::open(path).unwrap();
+        let open = self.open.fetch_add(1, Ordering::SeqCst) + 1;
+        self.most_open.fetch_max(open, Ordering::SeqCst);
+        
+        let mut content = String::new();
+        file.read_to_string(&mut content).unwrap();
+        thread::sleep(std::time::Duration::from_millis(5));
+        
+        self.open.fetch_sub(1, Ordering::SeqCst);
+        content
+    }
+}
+
+#[test]
+fn never_opens_more_files_than_the_limit() {
+    let dir = std::env::temp_dir().join(format!("maid-io-limit-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let paths: Vec<_> = (0..64)
+        .map(|n| {
+            let path = dir.join(format!("{}.md", n));
+            fs::write(&path, format!("# File {}\n", n)).unwrap();
+            path
+        })
+        .collect();
+    
+    let limit = IoLimit::new(4);
+    let opener = CountingOpener {
+        limit: &limit,
+        open: AtomicUsize::new(0),
+        most_open: AtomicUsize::new(0),
+    };
+    thread::scope(|scope| {
+        for chunk in paths.chunks(4) {
+            let opener = &opener;
+            scope.spawn(move || {
+                for path in chunk {
+                    assert!(opener.read(path).starts_with("# File"));
+                }
+            });
+        }
+    });
+    fs::remove_dir_all(&dir).unwrap();
+    
+    let most_open = opener.most_open.load(Ordering::SeqCst);
+    assert!(most_open <= 4, "{} files were open at once", most_open);
+    assert!(most_open > 1, "reads never overlapped, so the limit wasn't exercised");
+}